@@ -1,14 +1,22 @@
 use calendar_lib::api::{permissions::types::Permissions, utils::TableId};
 use chrono::NaiveDate;
+use itertools::Itertools;
 
-use super::{AppView, EventsView};
+use super::{
+    impersonation::Impersonation, preview::Preview, ui::batch_ui::EventBatchInput, AppView,
+    EventsView,
+};
 use crate::{
     app_local_storage::AppLocalStorage,
-    state::{main_state::UserState, State},
+    config::Config,
+    preferences::Preferences,
+    state::{capabilities::Capabilities, main_state::UserState, State},
+    tables::{DbTable, DbTableGetById},
     ui::popups::popup_manager::PopupManager,
 };
 
 pub struct CalendarApp {
+    pub config: Config,
     pub(super) local_storage: AppLocalStorage,
     pub state: State,
     pub(super) view: AppView,
@@ -16,24 +24,41 @@ pub struct CalendarApp {
     pub burger_menu_expanded: bool,
     pub selected_user_id: TableId,
     pub selected_date: NaiveDate,
+
+    pub(super) preview: Option<Preview>,
+    pub(super) impersonation: Option<Impersonation>,
+    pub(super) batch_input: EventBatchInput,
 }
 
 impl CalendarApp {
     pub fn new(cc: &eframe::CreationContext<'_>) -> Self {
         let mut local_storage = AppLocalStorage::new();
-        let state = State::new();
-        match local_storage.get_jwt() {
-            Some(jwt) => {
-                state.login_by_jwt(jwt);
-            }
-            _ => {
-                println!("Auth info not found");
+        if let Some(preferences) = local_storage.get_preferences() {
+            *Preferences::get() = preferences;
+        }
+        let config = Config::load();
+        let mut state = State::new(&config);
+        // Share links are opened without logging in
+        if let Some(token) = config.share_token.clone() {
+            state.load_shared_calendar(token);
+        } else {
+            match local_storage.get_jwt() {
+                Some(jwt) => {
+                    if let Some(cache) = local_storage.get_state_cache(&jwt) {
+                        state.restore_cache(cache);
+                    }
+                    state.login_by_jwt(jwt);
+                }
+                _ => {
+                    println!("Auth info not found");
+                }
             }
         }
 
         Self::configure_styles(&cc.egui_ctx);
 
         Self {
+            config,
             local_storage,
             state,
             view: EventsView::Days.into(),
@@ -41,6 +66,10 @@ impl CalendarApp {
             burger_menu_expanded: true,
             selected_user_id: -1,
             selected_date: chrono::Local::now().naive_local().date(),
+
+            preview: None,
+            impersonation: None,
+            batch_input: EventBatchInput::new(),
         }
     }
 }
@@ -48,6 +77,7 @@ impl CalendarApp {
 impl CalendarApp {
     pub(super) fn logout(&mut self) {
         self.local_storage.clear_jwt();
+        self.local_storage.clear_state_cache();
         PopupManager::get().clear();
         self.view = EventsView::Month.into();
         self.state.logout();
@@ -55,6 +85,40 @@ impl CalendarApp {
         self.burger_menu_expanded = true;
         self.selected_user_id = -1;
         self.selected_date = chrono::Local::now().naive_local().date();
+        Preferences::get().clear_reminded_shares();
+        self.preview = None;
+        self.impersonation = None;
+    }
+
+    pub(super) fn remind_expiring_shares(&mut self) {
+        if self.state.is_stale() {
+            return;
+        }
+        let shares = self
+            .state
+            .get_expiring_shares()
+            .into_iter()
+            .filter(|(gp, _)| !Preferences::get().is_share_reminded(gp.id))
+            .collect_vec();
+        if shares.is_empty() {
+            return;
+        }
+
+        Preferences::get().mark_shares_reminded(shares.iter().map(|(gp, _)| gp.id));
+        let shares = shares
+            .into_iter()
+            .map(|(gp, days_left)| {
+                let name = self
+                    .state
+                    .user_state
+                    .users
+                    .get_table()
+                    .get_by_id(gp.receiver_user_id)
+                    .map_or_else(|| "Unknown user".to_owned(), |user| user.name.clone());
+                (name, days_left)
+            })
+            .collect_vec();
+        PopupManager::get().open_share_expiry_reminder(shares);
     }
 
     pub fn get_selected_user_state(&self) -> &UserState {
@@ -62,7 +126,20 @@ impl CalendarApp {
     }
 
     pub fn get_selected_user_permissions(&self) -> Permissions {
-        self.state.get_user_permissions(self.selected_user_id)
+        match &self.preview {
+            Some(preview) if preview.user_id == self.selected_user_id => preview.permissions,
+            _ => self.state.get_user_permissions(self.selected_user_id),
+        }
+    }
+
+    /// Nothing can be changed while previewing
+    pub fn get_selected_capabilities(&self) -> Capabilities {
+        let capabilities = Capabilities::new(self.get_selected_user_permissions());
+        if self.is_preview() {
+            capabilities.read_only()
+        } else {
+            capabilities
+        }
     }
 
     pub fn get_selected_access_level(&self) -> i32 {
@@ -0,0 +1,26 @@
+use calendar_lib::api::utils::TableId;
+
+use super::CalendarApp;
+use crate::state::backup::CalendarBackup;
+
+impl CalendarApp {
+    fn restore_request_id() -> egui::Id {
+        egui::Id::new("backup_restore_request")
+    }
+
+    /// Popups can't change the state, so the restore is started on the next update
+    pub fn request_restore(ctx: &egui::Context, user_id: TableId, backup: CalendarBackup) {
+        ctx.data_mut(|data| data.insert_temp(Self::restore_request_id(), (user_id, backup)));
+    }
+
+    pub(super) fn update_restore_request(&mut self, ctx: &egui::Context) {
+        let request = ctx.data_mut(|data| {
+            let request = data.get_temp::<(TableId, CalendarBackup)>(Self::restore_request_id());
+            data.remove::<(TableId, CalendarBackup)>(Self::restore_request_id());
+            request
+        });
+        if let Some((user_id, backup)) = request {
+            self.state.restore_backup(user_id, backup);
+        }
+    }
+}
@@ -0,0 +1,88 @@
+use calendar_lib::api::utils::TableId;
+
+use super::{AdminPanelUserDataView, AdminPanelView, CalendarApp, EventsView};
+use crate::{
+    tables::{DbTable, DbTableGetById},
+    ui::table_view::TableView,
+};
+
+/// Admin working in the calendar of another user, with full permissions
+#[derive(Debug, Clone)]
+pub struct Impersonation {
+    pub user_id: TableId,
+    pub user_name: String,
+}
+
+impl CalendarApp {
+    pub(super) fn start_impersonation(&mut self, user_id: TableId) {
+        let user_name = self
+            .state
+            .admin_state
+            .users
+            .get_table()
+            .get_by_id(user_id)
+            .map(|user| user.name.clone())
+            .unwrap_or_default();
+        if self.state.try_get_user_state(user_id).is_none() {
+            self.state.admin_state.load_user_state(user_id);
+        }
+        self.exit_preview();
+        self.selected_user_id = user_id;
+        self.view = EventsView::Month.into();
+        self.impersonation = Some(Impersonation { user_id, user_name });
+        self.state.clear_events(self.selected_user_id);
+    }
+
+    /// Goes back to the admin panel, to the data of the impersonated user
+    pub(super) fn exit_impersonation(&mut self) {
+        if let Some(impersonation) = self.impersonation.take() {
+            self.selected_user_id = self.state.get_me().id;
+            self.view = AdminPanelView::UserData {
+                user_id: impersonation.user_id,
+                view: AdminPanelUserDataView::Events {
+                    table: TableView::new("admin_events_table"),
+                },
+            }
+            .into();
+            self.state.clear_events(self.selected_user_id);
+        }
+    }
+
+    pub(super) fn update_impersonation(&mut self) {
+        if let Some(impersonation) = &self.impersonation {
+            // Any other navigation ends it, e.g. "Your Calendar"
+            if impersonation.user_id != self.selected_user_id || !self.view.is_calendar() {
+                self.impersonation = None;
+            }
+        }
+    }
+
+    pub fn is_impersonating(&self) -> bool {
+        self.impersonation.is_some()
+    }
+
+    pub(super) fn impersonation_banner(&mut self, ui: &mut egui::Ui) {
+        let impersonation = match &self.impersonation {
+            Some(impersonation) => impersonation,
+            None => return,
+        };
+        let text = format!(
+            "Acting as {}: all changes are made to their calendar",
+            impersonation.user_name
+        );
+        let mut exit = false;
+        egui::Frame::none()
+            .fill(ui.visuals().error_fg_color.gamma_multiply(0.2))
+            .rounding(4.)
+            .inner_margin(4.)
+            .show(ui, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label(text);
+                    exit = ui.button("Stop").clicked();
+                });
+            });
+        if exit {
+            self.exit_impersonation();
+        }
+    }
+}
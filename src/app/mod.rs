@@ -1,4 +1,7 @@
 pub mod app;
+pub mod backup;
+pub mod impersonation;
+pub mod preview;
 pub mod ui;
 pub mod view;
 
@@ -0,0 +1,100 @@
+use calendar_lib::api::{permissions::types::Permissions, utils::TableId};
+
+use super::{CalendarApp, CalendarView, EventsView};
+use crate::{tables::DbTable, utils::access_levels_human_name};
+
+/// Calendar of the owner, shown the way a share recipient sees it
+#[derive(Debug, Clone)]
+pub struct Preview {
+    pub user_id: TableId,
+    pub recipient_name: String,
+    pub permissions: Permissions,
+}
+
+impl CalendarApp {
+    fn preview_request_id() -> egui::Id {
+        egui::Id::new("preview_request")
+    }
+
+    /// Popups can't change the app, so the preview starts on the next frame
+    pub fn request_preview(ctx: &egui::Context, preview: Preview) {
+        ctx.data_mut(|data| data.insert_temp(Self::preview_request_id(), preview));
+    }
+
+    pub(super) fn update_preview(&mut self, ctx: &egui::Context) {
+        let request = ctx.data_mut(|data| {
+            let request = data.get_temp::<Preview>(Self::preview_request_id());
+            data.remove::<Preview>(Self::preview_request_id());
+            request
+        });
+        if let Some(preview) = request {
+            self.start_preview(preview);
+        }
+
+        // Preview only makes sense for the calendar it was started on
+        if let Some(preview) = &self.preview {
+            if preview.user_id != self.selected_user_id || !self.view.is_calendar() {
+                self.exit_preview();
+            }
+        }
+    }
+
+    pub fn start_preview(&mut self, preview: Preview) {
+        self.selected_user_id = preview.user_id;
+        let permissions = preview.permissions;
+        self.view = if permissions.events.view {
+            EventsView::Month.into()
+        } else if permissions.schedules.view {
+            CalendarView::Schedules.into()
+        } else if permissions.event_templates.view {
+            CalendarView::EventTemplates.into()
+        } else {
+            EventsView::Month.into()
+        };
+        self.preview = Some(preview);
+        // Days are prepared for one access level at a time
+        self.state.clear_events(self.selected_user_id);
+    }
+
+    pub fn exit_preview(&mut self) {
+        if self.preview.take().is_some() {
+            self.state.clear_events(self.selected_user_id);
+        }
+    }
+
+    pub fn is_preview(&self) -> bool {
+        self.preview.is_some()
+    }
+
+    pub(super) fn preview_banner(&mut self, ui: &mut egui::Ui) {
+        let preview = match &self.preview {
+            Some(preview) => preview,
+            None => return,
+        };
+        let text = format!(
+            "Previewing as {} ({})",
+            preview.recipient_name,
+            access_levels_human_name(
+                self.get_selected_user_state()
+                    .access_levels
+                    .get_table()
+                    .get(),
+                preview.permissions.access_level
+            )
+        );
+        let mut exit = false;
+        egui::Frame::none()
+            .fill(ui.visuals().warn_fg_color.gamma_multiply(0.2))
+            .rounding(4.)
+            .inner_margin(4.)
+            .show(ui, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label(text);
+                    exit = ui.button("Exit preview").clicked();
+                });
+            });
+        if exit {
+            self.exit_preview();
+        }
+    }
+}
@@ -1,57 +1,105 @@
-use super::super::{
-    view::{AdminPanelUserDataView, AdminPanelView},
-    CalendarApp,
+use super::{
+    super::{
+        view::{AdminPanelUserDataView, AdminPanelView},
+        CalendarApp,
+    },
+    usage_ui::UsageMetric,
 };
 use crate::{
+    state::capabilities::{CapabilityAction, CapabilityTable},
     tables::{DbTable, DbTableGetById},
     ui::{
-        popups::{popup::PopupType, popup_manager::PopupManager},
-        table_view::{TableView, TableViewActions},
+        event_selection::EventSelection,
+        popups::{
+            admin_user_confirm::AdminUserAction, popup::PopupType, popup_manager::PopupManager,
+        },
+        table_view::{TableView, TableViewActions, TableViewSelection},
+        tables::users::AdminUserRow,
+        utils::UiUtils,
     },
 };
 use calendar_lib::api::{
     event_templates::types::EventTemplate, events::types::Event, schedules::types::Schedule,
-    utils::User,
 };
+use itertools::Itertools;
 
 impl CalendarApp {
-    pub(super) fn admin_panel_view(&mut self, ui: &mut egui::Ui, _view: AdminPanelView) {
+    pub(super) fn admin_panel_view(&mut self, ui: &mut egui::Ui, view: AdminPanelView) {
         ui.horizontal(|ui| {
             ui.heading("Admin Panel");
-            /*egui::ComboBox::from_id_source("admin panel view picker")
-            .selected_text(match view {
-                AdminPanelView::Users { table: _ } => "Users",
-            })
-            .show_ui(ui, |ui| {
-                let mut view = view;
-                ui.selectable_value(
-                    &mut view,
-                    AdminPanelView::Users {
-                        table: TableView::new("users_table"),
-                    },
-                    "Users",
-                );
-                self.set_view(view);
-            });*/
+            egui::ComboBox::from_id_source("admin panel view picker")
+                .selected_text(match view {
+                    AdminPanelView::Users { .. } | AdminPanelView::UserData { .. } => "Users",
+                    AdminPanelView::Usage { .. } => "Usage",
+                })
+                .show_ui(ui, |ui| {
+                    if ui.selectable_label(!view.is_usage(), "Users").clicked() && view.is_usage() {
+                        self.set_view(AdminPanelView::Users {
+                            table: TableView::new("users_table"),
+                        });
+                    }
+                    if ui.selectable_label(view.is_usage(), "Usage").clicked() && !view.is_usage() {
+                        let days = 30;
+                        self.set_view(AdminPanelView::Usage {
+                            days,
+                            metric: UsageMetric::Storage,
+                            table: TableView::new("usage_table"),
+                        });
+                        self.state.admin_state.load_usage(days);
+                    }
+                });
         });
     }
 
-    pub(super) fn admin_panel_users_view(&mut self, ui: &mut egui::Ui, table: TableView<User>) {
+    pub(super) fn admin_panel_users_view(
+        &mut self,
+        ui: &mut egui::Ui,
+        table: TableView<AdminUserRow>,
+    ) {
+        if ui
+            .add_enabled(
+                !PopupManager::get().is_open(PopupType::is_admin_new_user),
+                egui::Button::new("Create User"),
+            )
+            .clicked()
+        {
+            PopupManager::get().open_admin_new_user();
+        }
+
+        let admin_state = &self.state.admin_state;
+        let rows = admin_state
+            .users
+            .get_table()
+            .get()
+            .iter()
+            .map(|user| AdminUserRow {
+                user: user.clone(),
+                info: admin_state.users_info.get(&user.id).cloned(),
+            })
+            .collect_vec();
         let actions = table
             .show(
                 ui,
-                self.state.admin_state.users.get_table().get(),
+                &rows,
                 Some(TableViewActions::new(
-                    vec![(0, "Data".to_owned())],
-                    |user: &User| user.id,
+                    vec![
+                        (0, "Data".to_owned()),
+                        (1, "Edit".to_owned()),
+                        (2, "Password".to_owned()),
+                        (3, "Disable/Enable".to_owned()),
+                        (4, "Delete".to_owned()),
+                    ],
+                    |row: &AdminUserRow| row.user.id,
                 )),
             )
             .inner;
 
-        actions
-            .actions
-            .into_iter()
-            .for_each(|(act, user_id)| match act {
+        actions.actions.into_iter().for_each(|(act, user_id)| {
+            let row = match rows.iter().find(|row| row.user.id == user_id) {
+                Some(row) => row,
+                None => return,
+            };
+            match act {
                 0 => {
                     self.set_view(AdminPanelView::UserData {
                         user_id,
@@ -61,8 +109,25 @@ impl CalendarApp {
                     });
                     self.state.admin_state.load_user_state(user_id);
                 }
+                1 => PopupManager::get().open_admin_update_user(&row.user),
+                2 => PopupManager::get().open_admin_password_reset(&row.user),
+                3 => {
+                    let is_disabled = row.info.as_ref().is_some_and(|info| info.disabled);
+                    PopupManager::get().open_admin_user_confirm(
+                        &row.user,
+                        if is_disabled {
+                            AdminUserAction::Enable
+                        } else {
+                            AdminUserAction::Disable
+                        },
+                    );
+                }
+                4 => {
+                    PopupManager::get().open_admin_user_confirm(&row.user, AdminUserAction::Delete)
+                }
                 _ => {}
-            });
+            }
+        });
     }
 
     pub(super) fn admin_panel_user_data_view(
@@ -116,6 +181,36 @@ impl CalendarApp {
             if ui.button("Reload").clicked() {
                 self.state.admin_state.load_user_state(user_id);
             }
+            ui.separator();
+            if let Some(user_state) = self.state.admin_state.users_data.get(&user_id) {
+                if ui
+                    .button("Backup")
+                    .on_hover_text("Copy the whole calendar to the clipboard")
+                    .clicked()
+                {
+                    ui.output_mut(|o| o.copied_text = user_state.make_backup().to_json());
+                }
+            }
+            if ui
+                .add_enabled(
+                    !PopupManager::get().is_open(PopupType::is_backup_restore),
+                    egui::Button::new("Restore"),
+                )
+                .clicked()
+            {
+                PopupManager::get().open_backup_restore(user_id);
+            }
+            ui.separator();
+            if ui
+                .add_enabled(
+                    user_id != self.state.get_me().id,
+                    egui::Button::new("Impersonate"),
+                )
+                .on_hover_text("Open the calendar of the user with full permissions")
+                .clicked()
+            {
+                self.start_impersonation(user_id);
+            }
         });
     }
 
@@ -125,33 +220,59 @@ impl CalendarApp {
         user_id: i32,
         table: TableView<Event>,
     ) {
-        if ui
-            .add_enabled(
-                !PopupManager::get().is_open(PopupType::is_new_event),
-                egui::Button::new("Add Event"),
-            )
-            .clicked()
-        {
-            PopupManager::get().open_new_event(user_id);
-        }
+        ui.horizontal(|ui| {
+            let can_create = self
+                .state
+                .get_capabilities(user_id)
+                .table(CapabilityTable::Events, CapabilityAction::Create);
+            if ui
+                .add_enabled_permitted(
+                    !PopupManager::get().is_open(PopupType::is_new_event),
+                    can_create,
+                    egui::Button::new("Add Event"),
+                )
+                .clicked()
+            {
+                PopupManager::get().open_new_event(user_id);
+            }
+            if ui
+                .add_enabled_permitted(
+                    !PopupManager::get().is_open(PopupType::is_table_import),
+                    can_create,
+                    egui::Button::new("Import"),
+                )
+                .clicked()
+            {
+                PopupManager::get().open_table_import::<Event>(user_id);
+            }
+        });
         if let Some(user_state) = self.state.admin_state.users_data.get(&user_id) {
+            let mut selection = EventSelection::load(ui.ctx(), user_id);
             let actions = table
-                .show(
+                .show_selectable(
                     ui,
                     user_state.events.get_table().get(),
                     Some(TableViewActions::new(
-                        vec![(0, "Delete".to_owned())],
+                        vec![(0, "Edit".to_owned()), (1, "Delete".to_owned())],
                         |event: &Event| event.id,
                     )),
+                    TableViewSelection::new(selection.get_selected_mut(), |event: &Event| event.id),
                 )
                 .inner;
+            selection.store(ui.ctx());
 
             actions.actions.into_iter().for_each(|(act, id)| match act {
                 0 => {
+                    if let Some(item) = user_state.events.get_table().get_by_id(id) {
+                        PopupManager::get().open_update_event(item);
+                    }
+                }
+                1 => {
                     user_state.events.delete(id);
                 }
                 _ => {}
             });
+            self.event_batch_bar(ui, user_id, self.state.get_capabilities(user_id));
         } else {
             // TODO: Some visual that load is in progress
         }
@@ -163,22 +284,39 @@ impl CalendarApp {
         user_id: i32,
         table: TableView<EventTemplate>,
     ) {
-        if ui
-            .add_enabled(
-                !PopupManager::get().is_open(PopupType::is_new_event_template),
-                egui::Button::new("Add Template"),
-            )
-            .clicked()
-        {
-            PopupManager::get().open_new_event_template(user_id);
-        }
+        ui.horizontal(|ui| {
+            let can_create = self
+                .state
+                .get_capabilities(user_id)
+                .table(CapabilityTable::EventTemplates, CapabilityAction::Create);
+            if ui
+                .add_enabled_permitted(
+                    !PopupManager::get().is_open(PopupType::is_new_event_template),
+                    can_create,
+                    egui::Button::new("Add Template"),
+                )
+                .clicked()
+            {
+                PopupManager::get().open_new_event_template(user_id);
+            }
+            if ui
+                .add_enabled_permitted(
+                    !PopupManager::get().is_open(PopupType::is_table_import),
+                    can_create,
+                    egui::Button::new("Import"),
+                )
+                .clicked()
+            {
+                PopupManager::get().open_table_import::<EventTemplate>(user_id);
+            }
+        });
         if let Some(user_state) = self.state.admin_state.users_data.get(&user_id) {
             let actions = table
                 .show(
                     ui,
                     user_state.event_templates.get_table().get(),
                     Some(TableViewActions::new(
-                        vec![(0, "Delete".to_owned())],
+                        vec![(0, "Edit".to_owned()), (1, "Delete".to_owned())],
                         |template: &EventTemplate| template.id,
                     )),
                 )
@@ -186,6 +324,11 @@ impl CalendarApp {
 
             actions.actions.into_iter().for_each(|(act, id)| match act {
                 0 => {
+                    if let Some(item) = user_state.event_templates.get_table().get_by_id(id) {
+                        PopupManager::get().open_update_event_template(item);
+                    }
+                }
+                1 => {
                     user_state.event_templates.delete(id);
                 }
                 _ => {}
@@ -201,22 +344,39 @@ impl CalendarApp {
         user_id: i32,
         table: TableView<Schedule>,
     ) {
-        if ui
-            .add_enabled(
-                !PopupManager::get().is_open(PopupType::is_new_schedule),
-                egui::Button::new("Add Schedule"),
-            )
-            .clicked()
-        {
-            PopupManager::get().open_new_schedule(user_id);
-        }
+        ui.horizontal(|ui| {
+            let can_create = self
+                .state
+                .get_capabilities(user_id)
+                .table(CapabilityTable::Schedules, CapabilityAction::Create);
+            if ui
+                .add_enabled_permitted(
+                    !PopupManager::get().is_open(PopupType::is_new_schedule),
+                    can_create,
+                    egui::Button::new("Add Schedule"),
+                )
+                .clicked()
+            {
+                PopupManager::get().open_new_schedule(user_id);
+            }
+            if ui
+                .add_enabled_permitted(
+                    !PopupManager::get().is_open(PopupType::is_table_import),
+                    can_create,
+                    egui::Button::new("Import"),
+                )
+                .clicked()
+            {
+                PopupManager::get().open_table_import::<Schedule>(user_id);
+            }
+        });
         if let Some(user_state) = self.state.admin_state.users_data.get(&user_id) {
             let actions: crate::ui::table_view::TableViewResponse = table
                 .show(
                     ui,
                     user_state.schedules.get_table().get(),
                     Some(TableViewActions::new(
-                        vec![(0, "Delete".to_owned())],
+                        vec![(0, "Edit".to_owned()), (1, "Delete".to_owned())],
                         |schedule: &Schedule| schedule.id,
                     )),
                 )
@@ -224,6 +384,11 @@ impl CalendarApp {
 
             actions.actions.into_iter().for_each(|(act, id)| match act {
                 0 => {
+                    if let Some(item) = user_state.schedules.get_table().get_by_id(id) {
+                        PopupManager::get().open_update_schedule(item);
+                    }
+                }
+                1 => {
                     user_state.schedules.delete(id);
                 }
                 _ => {}
@@ -5,6 +5,7 @@ use super::super::{
 use crate::{
     app::ManageAccessView,
     db::aliases::UserUtils,
+    preferences::Preferences,
     state::custom_requests::LoginRequest,
     tables::DbTable,
     ui::{
@@ -20,8 +21,20 @@ use itertools::Itertools;
 impl CalendarApp {
     fn top_panel(&mut self, ui: &mut egui::Ui) {
         ui.with_layout(Layout::left_to_right(Align::TOP), |ui| {
-            let calendar_name = if self.state.try_get_me().is_none() {
+            let calendar_name = if let Some(user_id) = self.state.get_public_user_id() {
+                match self
+                    .state
+                    .granted_states
+                    .iter()
+                    .find(|gs| gs.user.id == user_id)
+                {
+                    Some(gs) => format!("{} Calendar", gs.user.name),
+                    None => "Shared Calendar".to_owned(),
+                }
+            } else if self.state.try_get_me().is_none() {
                 "Calendar".to_owned()
+            } else if let Some(impersonation) = &self.impersonation {
+                format!("{} Calendar", impersonation.user_name)
             } else if self.selected_user_id == self.state.get_me().id {
                 "Your Calendar".to_owned()
             } else {
@@ -58,6 +71,8 @@ impl CalendarApp {
                                 }
                             }
                         }
+                    } else if self.state.is_public_view() {
+                        ui.label("Read-only");
                     } else {
                         if ui
                             .add_enabled(
@@ -82,6 +97,10 @@ impl CalendarApp {
                     if self.state.any_pending_requests() {
                         ui.spinner();
                     }
+                    if self.state.is_stale() {
+                        ui.label("Showing saved data")
+                            .on_hover_text("Fresh data is being loaded");
+                    }
                 },
             );
         });
@@ -227,6 +246,47 @@ impl CalendarApp {
             self.burger_menu_collapsed(ctx);
         }
     }
+
+    /// Events, created from schedules in the background, are listed until dismissed
+    fn materialized_banner(&mut self, ui: &mut egui::Ui) {
+        let materializer = self.state.get_materializer();
+        let created = materializer.get_created();
+        let failed = materializer.get_failed();
+        if created.is_empty() && failed == 0 {
+            return;
+        }
+        let mut text = format!("{} events were created from schedules", created.len());
+        if failed > 0 {
+            text += &format!(", {failed} failed");
+        }
+        let mut dismiss = false;
+        egui::Frame::none()
+            .fill(ui.visuals().selection.bg_fill.gamma_multiply(0.3))
+            .rounding(4.)
+            .inner_margin(4.)
+            .show(ui, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label(text);
+                    dismiss = ui.button("OK").clicked();
+                });
+                if !created.is_empty() {
+                    CollapsingHeader::new("Created events")
+                        .id_source("materialized_events")
+                        .show(ui, |ui| {
+                            created.iter().for_each(|event| {
+                                ui.label(format!(
+                                    "{} {}",
+                                    event.start.format("%Y-%m-%d %H:%M"),
+                                    event.name
+                                ));
+                            });
+                        });
+                }
+            });
+        if dismiss {
+            self.state.dismiss_materialized();
+        }
+    }
 }
 
 impl CalendarApp {
@@ -285,6 +345,13 @@ impl CalendarApp {
                             }
                         }
                     }
+                    AdminPanelView::Usage {
+                        days,
+                        metric,
+                        table,
+                    } => {
+                        self.admin_panel_usage_view(ui, days, metric, table);
+                    }
                 }
             }
             AppView::ManageAccess(manage_access_view) => {
@@ -296,6 +363,9 @@ impl CalendarApp {
                     ManageAccessView::AccessLevels => {
                         self.manage_access_access_levels_view(ui);
                     }
+                    ManageAccessView::Activity { actor_id, table } => {
+                        self.manage_access_activity_view(ui, actor_id, table);
+                    }
                 }
             }
         }
@@ -311,6 +381,7 @@ impl CalendarApp {
 
     fn calendar_events_view(&mut self, ui: &mut egui::Ui, view: EventsView) {
         self.events_view_picker(ui, view);
+        self.event_batch_bar(ui, self.selected_user_id, self.get_selected_capabilities());
     }
 
     fn calendar_events_month_view(&mut self, ui: &mut egui::Ui, date: NaiveDate) {
@@ -344,14 +415,30 @@ impl eframe::App for CalendarApp {
             if self.selected_user_id == -1 {
                 self.selected_user_id = self.state.get_me().id;
             }
+            // Share could have expired or been revoked
+            if !self.state.get_me().is_admin()
+                && self
+                    .state
+                    .try_get_user_state(self.selected_user_id)
+                    .is_none()
+            {
+                self.selected_user_id = self.state.get_me().id;
+            }
+        }
+        if let Some(user_id) = self.state.get_public_user_id() {
+            self.selected_user_id = user_id;
         }
 
+        self.update_impersonation();
         // Admins have different view
-        if self.state.get_me().is_admin() && self.view.is_calendar() {
+        if self.state.get_me().is_admin() && self.view.is_calendar() && !self.is_impersonating() {
             self.view = AppView::AdminPanel(AdminPanelView::Users {
                 table: TableView::new("users_table"),
             });
         }
+        self.update_preview(ctx);
+        self.update_restore_request(ctx);
+        self.undo_shortcuts(ctx);
 
         if self.state.try_get_me().is_some() {
             self.burger_menu(ctx);
@@ -363,19 +450,40 @@ impl eframe::App for CalendarApp {
 
             self.top_panel(ui);
             ui.separator();
+            self.preview_banner(ui);
+            self.impersonation_banner(ui);
+            self.materialized_banner(ui);
 
             ui.horizontal_top(|ui| {
-                if self.state.try_get_me().is_some() {
+                if self.state.try_get_me().is_some() || self.state.is_public_view() {
                     ui.with_layout(Layout::top_down_justified(Align::LEFT), |ui| {
                         self.view_dispatcher(ui);
                     });
                 }
             });
         });
+        self.undo_toast(ctx);
 
         self.state.update();
         if let Some(Ok(login_response)) = self.state.find_response_by_type::<LoginRequest>() {
             self.local_storage.store_jwt(login_response.jwt.clone());
         }
+        self.remind_expiring_shares();
+        {
+            let mut preferences = Preferences::get();
+            if preferences.take_changed() {
+                self.local_storage.store_preferences(&preferences);
+            }
+        }
+        if self.state.take_cache_discarded() {
+            self.local_storage.clear_state_cache();
+        }
+        if self.state.take_cache_outdated() {
+            if let (Some(jwt), Some(cache)) =
+                (self.local_storage.get_jwt(), self.state.make_cache())
+            {
+                self.local_storage.store_state_cache(&jwt, &cache);
+            }
+        }
     }
 }
@@ -0,0 +1,235 @@
+use calendar_lib::api::{events::types::EventVisibility, utils::TableId};
+use egui::{Button, Color32, DragValue, Pos2, Rect, Sense, Stroke};
+
+use super::super::CalendarApp;
+use crate::{
+    state::{
+        batch::EventBatchAction,
+        capabilities::{Capabilities, CapabilityAction, CapabilityTable},
+    },
+    tables::DbTable,
+    ui::{
+        access_level_picker::AccessLevelPicker, event_selection::EventSelection,
+        event_visibility_picker::EventVisibilityPicker, utils::UiUtils,
+    },
+};
+
+/// Parameters of the batch actions, kept between frames
+pub struct EventBatchInput {
+    days: i64,
+    access_level: i32,
+    visibility: EventVisibility,
+}
+
+impl EventBatchInput {
+    pub fn new() -> Self {
+        Self {
+            days: 1,
+            access_level: 0,
+            visibility: EventVisibility::HideName,
+        }
+    }
+}
+
+impl CalendarApp {
+    pub(super) fn event_batch_bar(
+        &mut self,
+        ui: &mut egui::Ui,
+        user_id: TableId,
+        capabilities: Capabilities,
+    ) {
+        self.event_batch_progress(ui);
+
+        let mut selection = EventSelection::load(ui.ctx(), user_id);
+        selection.retain_existing(self.state.get_user_state(user_id).events.get_table().get());
+        if selection.is_empty() {
+            selection.store(ui.ctx());
+            return;
+        }
+
+        let is_running = self
+            .state
+            .get_event_batch()
+            .is_some_and(|batch| !batch.is_finished());
+        let can_edit = capabilities.table(CapabilityTable::Events, CapabilityAction::Edit);
+        let mut action = None;
+        ui.horizontal_wrapped(|ui| {
+            ui.label(format!("{} selected", selection.get_ids().len()));
+            if ui
+                .add_enabled_permitted(
+                    !is_running,
+                    capabilities.table(CapabilityTable::Events, CapabilityAction::Delete),
+                    Button::new("Delete"),
+                )
+                .clicked()
+            {
+                action = Some(EventBatchAction::Delete);
+            }
+            ui.separator();
+
+            ui.add(DragValue::new(&mut self.batch_input.days).suffix(" days"));
+            if ui
+                .add_enabled_permitted(
+                    !is_running && self.batch_input.days != 0,
+                    can_edit,
+                    Button::new("Move"),
+                )
+                .clicked()
+            {
+                action = Some(EventBatchAction::MoveDays(self.batch_input.days));
+            }
+            ui.separator();
+
+            ui.add(AccessLevelPicker::new(
+                "batch_access_level",
+                &mut self.batch_input.access_level,
+                self.state
+                    .get_user_state(user_id)
+                    .access_levels
+                    .get_table()
+                    .get(),
+            ));
+            if ui
+                .add_enabled_permitted(!is_running, can_edit, Button::new("Set Level"))
+                .clicked()
+            {
+                action = Some(EventBatchAction::SetAccessLevel(
+                    self.batch_input.access_level,
+                ));
+            }
+            ui.separator();
+
+            ui.add(EventVisibilityPicker::new(
+                "batch_visibility",
+                &mut self.batch_input.visibility,
+            ));
+            if ui
+                .add_enabled_permitted(!is_running, can_edit, Button::new("Set Visibility"))
+                .clicked()
+            {
+                action = Some(EventBatchAction::SetVisibility(self.batch_input.visibility));
+            }
+            ui.separator();
+
+            if ui
+                .add_enabled_permitted(
+                    !is_running,
+                    capabilities.table(CapabilityTable::EventTemplates, CapabilityAction::Create),
+                    Button::new("To Templates"),
+                )
+                .clicked()
+            {
+                action = Some(EventBatchAction::ConvertToTemplate);
+            }
+            ui.separator();
+
+            if ui.button("Clear").clicked() {
+                selection.clear();
+            }
+        });
+
+        if let Some(action) = action {
+            self.state
+                .run_event_batch(user_id, &selection.get_ids(), action);
+            selection.clear();
+        }
+        selection.store(ui.ctx());
+        ui.add_space(4.);
+    }
+
+    fn event_batch_progress(&mut self, ui: &mut egui::Ui) {
+        let batch = match self.state.get_event_batch() {
+            Some(batch) => batch,
+            None => return,
+        };
+        let done = batch.succeeded + batch.failed.len();
+        let mut dismiss = false;
+        ui.horizontal(|ui| {
+            if batch.is_finished() {
+                ui.label(format!(
+                    "{}: {} done, {} failed",
+                    batch.action.human_name(),
+                    batch.succeeded,
+                    batch.failed.len()
+                ));
+                dismiss = ui.button("OK").clicked();
+            } else {
+                ui.add(
+                    egui::ProgressBar::new(batch.get_progress())
+                        .desired_width(240.)
+                        .text(format!(
+                            "{} {}/{}",
+                            batch.action.human_name(),
+                            done,
+                            batch.total
+                        )),
+                );
+            }
+        });
+        if batch.is_finished() && !batch.failed.is_empty() {
+            egui::CollapsingHeader::new("Failures")
+                .id_source("event_batch_failures")
+                .show(ui, |ui| {
+                    batch.failed.iter().for_each(|(name, reason)| {
+                        ui.label(format!("{name}: {reason}"));
+                    });
+                });
+        }
+        if dismiss {
+            self.state.dismiss_event_batch();
+        }
+    }
+
+    /// Dragging over the background selects the crossed cards
+    pub(super) fn event_lasso(&self, ui: &mut egui::Ui, rect: Rect, cards: Vec<(TableId, Rect)>) {
+        if !self
+            .get_selected_capabilities()
+            .can_change_any(CapabilityTable::Events)
+        {
+            return;
+        }
+        let id = ui.id().with("event_lasso");
+        let response = ui.interact(rect, id, Sense::drag());
+        if response.drag_started() {
+            if let Some(origin) = ui.input(|input| input.pointer.press_origin()) {
+                ui.data_mut(|data| data.insert_temp(id, origin));
+            }
+        }
+        let origin = match ui.data(|data| data.get_temp::<Pos2>(id)) {
+            Some(origin) => origin,
+            None => return,
+        };
+        let current = ui
+            .input(|input| input.pointer.interact_pos())
+            .unwrap_or(origin);
+        let lasso = Rect::from_two_pos(origin, current);
+
+        if response.dragged() {
+            ui.painter().rect(
+                lasso,
+                0.,
+                Color32::YELLOW.gamma_multiply(0.1),
+                Stroke::new(1., Color32::YELLOW),
+            );
+        }
+        if response.drag_released() {
+            ui.data_mut(|data| data.remove::<Pos2>(id));
+            // Plain clicks are left to the cards
+            if lasso.width().max(lasso.height()) < 8. {
+                return;
+            }
+            let modifiers = ui.input(|input| input.modifiers);
+            let mut selection = EventSelection::load(ui.ctx(), self.selected_user_id);
+            if !modifiers.command && !modifiers.shift {
+                selection.clear();
+            }
+            selection.extend(
+                cards
+                    .into_iter()
+                    .filter(|(_, card)| card.intersects(lasso))
+                    .map(|(id, _)| id),
+            );
+            selection.store(ui.ctx());
+        }
+    }
+}
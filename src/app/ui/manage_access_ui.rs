@@ -1,37 +1,82 @@
-use egui::{Align, Button, Label, Layout, RichText};
+use calendar_lib::api::utils::TableId;
+use chrono::NaiveDate;
+use egui::{Align, Button, Color32, Label, Layout, RichText};
 use itertools::Itertools;
 
 use crate::{
-    app::{CalendarApp, ManageAccessView},
-    tables::{DbTable, DbTableGetById},
+    app::{preview::Preview, CalendarApp, ManageAccessView},
+    state::{
+        audit_log::{AuditEntry, AuditLog, AuditTable},
+        capabilities::{CapabilityAction, CapabilityTable},
+    },
+    tables::{share_validities::ShareValidity, DbTable, DbTableGetById},
     ui::{
         popups::{popup::PopupType, popup_manager::PopupManager},
         utils::UiUtils,
     },
+    utils::access_levels_human_name,
 };
 
+fn audit_entry_details(entry: &AuditEntry) -> String {
+    let format = |value: &Option<serde_json::Value>| {
+        value
+            .as_ref()
+            .and_then(|value| serde_json::to_string_pretty(value).ok())
+            .unwrap_or_else(|| "-".to_owned())
+    };
+    format!(
+        "Before:\n{}\n\nAfter:\n{}",
+        format(&entry.before),
+        format(&entry.after)
+    )
+}
+
+fn share_expiry_text(validity: Option<&ShareValidity>, today: NaiveDate) -> RichText {
+    let validity = match validity {
+        Some(validity) => validity,
+        None => return RichText::new("No expiry"),
+    };
+    if validity.is_expired(today) {
+        return RichText::new("Expired").color(Color32::RED);
+    }
+    if let Some(start) = validity.start.filter(|&start| today < start) {
+        return RichText::new(format!("Starts {}", start.format("%d.%m.%Y")));
+    }
+    match validity.days_left(today) {
+        Some(1) => RichText::new("Last day").color(Color32::YELLOW),
+        Some(days_left) => RichText::new(format!("{days_left} days left")),
+        None => RichText::new("No expiry"),
+    }
+}
+
 impl CalendarApp {
     pub(super) fn manage_access_view(&mut self, ui: &mut egui::Ui, view: ManageAccessView) {
-        let permissions = self.get_selected_user_permissions();
+        let capabilities = self.get_selected_capabilities();
         ui.with_layout(Layout::left_to_right(Align::TOP), |ui| {
             let height = ui
                 .horizontal(|ui| {
-                    ui.enabled_selectable_header(
+                    ui.permitted_selectable_header(
                         "Sharing",
-                        permissions.allow_share,
+                        capabilities.share(),
                         view.is_sharing(),
                         || {
                             self.set_view(ManageAccessView::Sharing);
                         },
                     );
-                    ui.enabled_selectable_header(
+                    ui.permitted_selectable_header(
                         "Access Levels",
-                        permissions.access_levels.view,
+                        capabilities.table(CapabilityTable::AccessLevels, CapabilityAction::View),
                         view.is_access_levels(),
                         || {
                             self.set_view(ManageAccessView::AccessLevels);
                         },
                     );
+                    ui.selectable_header("Activity", view.is_activity(), || {
+                        self.set_view(ManageAccessView::Activity {
+                            actor_id: None,
+                            table: None,
+                        });
+                    });
                 })
                 .response
                 .rect
@@ -43,8 +88,19 @@ impl CalendarApp {
                 |ui| match view {
                     ManageAccessView::Sharing => {
                         if ui
-                            .add_enabled(
+                            .add_enabled_permitted(
+                                !PopupManager::get().is_open(PopupType::is_new_share_link),
+                                capabilities.share(),
+                                egui::Button::new("Create Link"),
+                            )
+                            .clicked()
+                        {
+                            PopupManager::get().open_new_share_link(self.selected_user_id);
+                        }
+                        if ui
+                            .add_enabled_permitted(
                                 !PopupManager::get().is_open(PopupType::is_new_permission),
+                                capabilities.share(),
                                 egui::Button::new("Share"),
                             )
                             .clicked()
@@ -53,22 +109,22 @@ impl CalendarApp {
                         }
                     }
                     ManageAccessView::AccessLevels => {
-                        if permissions.access_levels.edit {
-                            if ui
-                                .add_enabled(
-                                    !PopupManager::get()
-                                        .is_open(PopupType::is_change_access_levels),
-                                    egui::Button::new("Edit"),
-                                )
-                                .clicked()
-                            {
-                                PopupManager::get().open_change_access_levels(
-                                    self.selected_user_id,
-                                    &self.get_selected_user_state().access_levels,
-                                );
-                            }
+                        if ui
+                            .add_enabled_permitted(
+                                !PopupManager::get().is_open(PopupType::is_change_access_levels),
+                                capabilities
+                                    .table(CapabilityTable::AccessLevels, CapabilityAction::Edit),
+                                egui::Button::new("Edit"),
+                            )
+                            .clicked()
+                        {
+                            PopupManager::get().open_change_access_levels(
+                                self.selected_user_id,
+                                &self.get_selected_user_state().access_levels,
+                            );
                         }
                     }
+                    ManageAccessView::Activity { .. } => {}
                 },
             );
         });
@@ -76,6 +132,8 @@ impl CalendarApp {
     }
 
     pub(super) fn manage_access_sharing_view(&mut self, ui: &mut egui::Ui) {
+        let today = chrono::Local::now().naive_local().date();
+        let can_share = self.get_selected_capabilities().share();
         ui.vertical(|ui| {
             egui::Grid::new("access_grid").show(ui, |ui| {
                 let permissions = self
@@ -97,20 +155,34 @@ impl CalendarApp {
                             .map(|u| (*gp, u))
                     })
                     .for_each(|(gp, user)| {
+                        let validity = self.state.get_share_validity(self.selected_user_id, gp.id);
                         ui.label(&user.name);
+                        ui.label(share_expiry_text(validity, today));
                         if ui
-                            .add_enabled(
+                            .add_enabled_permitted(
                                 !PopupManager::get().is_open(PopupType::is_update_permission),
+                                can_share,
                                 Button::new("MANAGE"),
                             )
                             .clicked()
                         {
-                            PopupManager::get().open_update_permission(&gp, user);
+                            PopupManager::get().open_update_permission(&gp, user, validity);
+                        }
+                        if ui.button("PREVIEW").clicked() {
+                            CalendarApp::request_preview(
+                                ui.ctx(),
+                                Preview {
+                                    user_id: self.selected_user_id,
+                                    recipient_name: user.name.clone(),
+                                    permissions: gp.permissions,
+                                },
+                            );
                         }
                         // Can't revoke your own access
                         if ui
-                            .add_enabled(
+                            .add_enabled_permitted(
                                 gp.receiver_user_id != self.state.get_me().id,
+                                can_share,
                                 Button::new("REVOKE"),
                             )
                             .clicked()
@@ -122,6 +194,44 @@ impl CalendarApp {
                         ui.end_row();
                     });
             });
+
+            let links = self.get_selected_user_state().share_links.get_table().get();
+            if !links.is_empty() {
+                let access_levels = self
+                    .get_selected_user_state()
+                    .access_levels
+                    .get_table()
+                    .get();
+                ui.add_space(8.);
+                ui.heading("Links");
+                ui.separator();
+                egui::Grid::new("share_links_grid").show(ui, |ui| {
+                    links.iter().for_each(|link| {
+                        let url = self.config.make_share_link(&link.token);
+                        ui.label(access_levels_human_name(access_levels, link.access_level));
+                        match url.as_ref() {
+                            Some(url) => ui.label(url),
+                            None => ui.weak("Link address is unknown, APP_URL isn't set"),
+                        };
+                        if ui.add_enabled(url.is_some(), Button::new("COPY")).clicked() {
+                            if let Some(url) = url {
+                                ui.output_mut(|o| o.copied_text = url);
+                            }
+                        }
+                        if ui
+                            .add_enabled_permitted(
+                                !PopupManager::get().is_open(PopupType::is_revoke_share_link),
+                                can_share,
+                                Button::new("REVOKE"),
+                            )
+                            .clicked()
+                        {
+                            PopupManager::get().open_revoke_share_link(link);
+                        }
+                        ui.end_row();
+                    });
+                });
+            }
         });
     }
 
@@ -138,4 +248,98 @@ impl CalendarApp {
                 });
         });
     }
+
+    pub(super) fn manage_access_activity_view(
+        &mut self,
+        ui: &mut egui::Ui,
+        actor_id: Option<TableId>,
+        table: Option<AuditTable>,
+    ) {
+        let capabilities = self.get_selected_capabilities();
+        // Cloned, as reverting locks the log
+        let entries = AuditLog::get().get_entries(self.selected_user_id);
+        ui.vertical(|ui| {
+            ui.horizontal(|ui| {
+                let actors = entries
+                    .iter()
+                    .map(|entry| (entry.actor_id, entry.actor_name.as_str()))
+                    .unique_by(|(id, _)| *id)
+                    .collect_vec();
+                let mut new_actor_id = actor_id;
+                egui::ComboBox::from_id_source("activity_actor")
+                    .selected_text(
+                        actors
+                            .iter()
+                            .find(|(id, _)| Some(*id) == actor_id)
+                            .map_or("Anyone", |(_, name)| *name),
+                    )
+                    .show_ui(ui, |ui| {
+                        ui.selectable_value(&mut new_actor_id, None, "Anyone");
+                        actors.iter().for_each(|&(id, name)| {
+                            ui.selectable_value(&mut new_actor_id, Some(id), name);
+                        });
+                    });
+
+                let mut new_table = table;
+                egui::ComboBox::from_id_source("activity_table")
+                    .selected_text(table.map_or("All tables", |table| table.human_name()))
+                    .show_ui(ui, |ui| {
+                        ui.selectable_value(&mut new_table, None, "All tables");
+                        AuditTable::ALL.iter().for_each(|&table| {
+                            ui.selectable_value(&mut new_table, Some(table), table.human_name());
+                        });
+                    });
+
+                if new_actor_id != actor_id || new_table != table {
+                    self.set_view(ManageAccessView::Activity {
+                        actor_id: new_actor_id,
+                        table: new_table,
+                    });
+                }
+            });
+            ui.add_space(4.);
+
+            let entries = entries
+                .iter()
+                .filter(|entry| actor_id.map_or(true, |id| entry.actor_id == id))
+                .filter(|entry| table.map_or(true, |table| entry.table == table))
+                .collect_vec();
+            if entries.is_empty() {
+                ui.label("No changes were made from this device");
+                return;
+            }
+
+            egui::Grid::new("activity_grid").show(ui, |ui| {
+                entries.into_iter().for_each(|entry| {
+                    ui.label(entry.time.format("%d.%m.%Y %H:%M").to_string());
+                    ui.label(&entry.actor_name);
+                    ui.label(format!(
+                        "{} {}",
+                        entry.action.human_name(),
+                        entry.table.human_name()
+                    ));
+                    ui.label(entry.get_item_name().unwrap_or("-"))
+                        .on_hover_text(audit_entry_details(entry));
+                    if entry.reverted {
+                        ui.label("Reverted");
+                    } else if entry.reverting {
+                        ui.label("Reverting...");
+                    } else if entry.is_revertible() {
+                        if ui
+                            .add_permitted(
+                                entry.revert_capability(&capabilities),
+                                Button::new("REVERT"),
+                            )
+                            .clicked()
+                        {
+                            self.get_selected_user_state().revert_audit_entry(entry);
+                        }
+                    } else {
+                        ui.label("");
+                    }
+                    ui.end_row();
+                });
+            });
+        });
+    }
 }
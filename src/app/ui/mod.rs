@@ -1,5 +1,8 @@
 pub mod admin_ui;
 pub mod app_ui;
+pub mod batch_ui;
 pub mod manage_access_ui;
+pub mod undo_ui;
+pub mod usage_ui;
 pub mod user_ui;
 pub mod utils;
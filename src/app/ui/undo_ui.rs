@@ -0,0 +1,54 @@
+use egui::{Align2, Key, Modifiers};
+
+use crate::{app::CalendarApp, state::undo_history::UndoHistory};
+
+impl CalendarApp {
+    /// Text fields have their own undo
+    pub(super) fn undo_shortcuts(&mut self, ctx: &egui::Context) {
+        if ctx.wants_keyboard_input() || self.state.is_public_view() || self.is_preview() {
+            return;
+        }
+        let (redo, undo) = ctx.input_mut(|input| {
+            (
+                input.consume_key(Modifiers::COMMAND | Modifiers::SHIFT, Key::Z),
+                input.consume_key(Modifiers::COMMAND, Key::Z),
+            )
+        });
+        if redo {
+            self.state.redo();
+        } else if undo {
+            self.state.undo();
+        }
+    }
+
+    pub(super) fn undo_toast(&mut self, ctx: &egui::Context) {
+        let toast = match UndoHistory::get().get_toast() {
+            Some(toast) => toast.clone(),
+            None => return,
+        };
+        egui::Area::new("undo_toast")
+            .anchor(Align2::RIGHT_BOTTOM, [-8., -8.])
+            .show(ctx, |ui| {
+                egui::Frame::popup(ui.style()).show(ui, |ui| {
+                    ui.horizontal(|ui| {
+                        ui.label(&toast.text);
+                        if toast.can_undo
+                            && ui
+                                .add_enabled(
+                                    UndoHistory::get().can_undo(),
+                                    egui::Button::new("Undo"),
+                                )
+                                .clicked()
+                        {
+                            self.state.undo();
+                        }
+                        if ui.small_button("x").clicked() {
+                            UndoHistory::get().hide_toast();
+                        }
+                    });
+                });
+            });
+        // Hide the toast in time, even without input
+        ctx.request_repaint_after(std::time::Duration::from_secs(1));
+    }
+}
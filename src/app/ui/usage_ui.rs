@@ -0,0 +1,199 @@
+use chrono::NaiveDate;
+use itertools::Itertools;
+use serde::{Deserialize, Serialize};
+
+use super::super::{
+    view::{AdminPanelUserDataView, AdminPanelView},
+    CalendarApp,
+};
+use crate::{
+    state::admin_requests::{UsageSnapshot, UserUsage},
+    tables::{DbTable, DbTableGetById},
+    ui::{
+        table_view::{TableView, TableViewActions},
+        tables::usage::UserUsageRow,
+        usage_chart::{UsageBars, UsageChart},
+    },
+    utils::bytes_human_name,
+};
+
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Eq)]
+pub(in super::super) enum UsageMetric {
+    Events,
+    EventTemplates,
+    Schedules,
+    Shares,
+    Storage,
+}
+
+impl UsageMetric {
+    const ALL: [UsageMetric; 5] = [
+        UsageMetric::Events,
+        UsageMetric::EventTemplates,
+        UsageMetric::Schedules,
+        UsageMetric::Shares,
+        UsageMetric::Storage,
+    ];
+
+    fn human_name(&self) -> &'static str {
+        match self {
+            UsageMetric::Events => "Events",
+            UsageMetric::EventTemplates => "Templates",
+            UsageMetric::Schedules => "Schedules",
+            UsageMetric::Shares => "Shares",
+            UsageMetric::Storage => "Storage",
+        }
+    }
+
+    fn of_snapshot(&self, snapshot: &UsageSnapshot) -> u64 {
+        match self {
+            UsageMetric::Events => snapshot.events as u64,
+            UsageMetric::EventTemplates => snapshot.event_templates as u64,
+            UsageMetric::Schedules => snapshot.schedules as u64,
+            UsageMetric::Shares => snapshot.granted_permissions as u64,
+            UsageMetric::Storage => snapshot.storage_bytes,
+        }
+    }
+
+    fn of_user(&self, usage: &UserUsage) -> u64 {
+        match self {
+            UsageMetric::Events => usage.events as u64,
+            UsageMetric::EventTemplates => usage.event_templates as u64,
+            UsageMetric::Schedules => usage.schedules as u64,
+            UsageMetric::Shares => usage.granted_permissions as u64,
+            UsageMetric::Storage => usage.storage_bytes,
+        }
+    }
+
+    fn get_format(&self) -> fn(u64) -> String {
+        match self {
+            UsageMetric::Storage => bytes_human_name,
+            _ => |value| value.to_string(),
+        }
+    }
+}
+
+impl CalendarApp {
+    pub(super) fn admin_panel_usage_view(
+        &mut self,
+        ui: &mut egui::Ui,
+        days: u32,
+        metric: UsageMetric,
+        table: TableView<UserUsageRow>,
+    ) {
+        ui.horizontal(|ui| {
+            let mut new_days = days;
+            egui::ComboBox::from_id_source("admin_usage_days")
+                .selected_text(format!("Last {days} days"))
+                .show_ui(ui, |ui| {
+                    [7, 30, 90, 365].into_iter().for_each(|option| {
+                        ui.selectable_value(&mut new_days, option, format!("Last {option} days"));
+                    });
+                });
+            let mut new_metric = metric;
+            egui::ComboBox::from_id_source("admin_usage_metric")
+                .selected_text(metric.human_name())
+                .show_ui(ui, |ui| {
+                    UsageMetric::ALL.into_iter().for_each(|metric| {
+                        ui.selectable_value(&mut new_metric, metric, metric.human_name());
+                    });
+                });
+            let reload = ui.button("Reload").clicked();
+            if new_days != days || new_metric != metric {
+                self.set_view(AdminPanelView::Usage {
+                    days: new_days,
+                    metric: new_metric,
+                    table,
+                });
+            }
+            if new_days != days || reload {
+                self.state.admin_state.load_usage(new_days);
+            }
+        });
+
+        let usage = match &self.state.admin_state.usage {
+            Some(usage) => usage,
+            None => {
+                ui.spinner();
+                return;
+            }
+        };
+
+        let totals = usage.get_totals();
+        egui::Grid::new("admin_usage_totals")
+            .num_columns(UsageMetric::ALL.len() + 1)
+            .show(ui, |ui| {
+                ui.strong("Users");
+                UsageMetric::ALL.into_iter().for_each(|metric| {
+                    ui.strong(metric.human_name());
+                });
+                ui.end_row();
+                ui.label(totals.users.to_string());
+                UsageMetric::ALL.into_iter().for_each(|metric| {
+                    ui.label(metric.get_format()(metric.of_snapshot(&totals)));
+                });
+                ui.end_row();
+            });
+        ui.add_space(4.);
+
+        ui.label(format!("{} over time", metric.human_name()));
+        let history = usage
+            .history
+            .iter()
+            .map(|snapshot| (snapshot.date, metric.of_snapshot(snapshot)))
+            .collect::<Vec<(NaiveDate, u64)>>();
+        ui.add(UsageChart::new(&history, metric.get_format()));
+        ui.add_space(4.);
+
+        let users = self.state.admin_state.users.get_table();
+        let rows = usage
+            .users
+            .iter()
+            .map(|usage| UserUsageRow {
+                name: users
+                    .get_by_id(usage.user_id)
+                    .map_or_else(|| format!("#{}", usage.user_id), |user| user.name.clone()),
+                usage: usage.clone(),
+            })
+            .collect_vec();
+
+        ui.label(format!(
+            "Top users by {}",
+            metric.human_name().to_lowercase()
+        ));
+        let top = rows
+            .iter()
+            .map(|row| (row.name.clone(), metric.of_user(&row.usage)))
+            .sorted_by_key(|(_, value)| std::cmp::Reverse(*value))
+            .take(10)
+            .collect_vec();
+        ui.add(UsageBars::new(&top, metric.get_format()));
+        ui.add_space(4.);
+
+        let actions = table
+            .show(
+                ui,
+                &rows,
+                Some(TableViewActions::new(
+                    vec![(0, "Data".to_owned())],
+                    |row: &UserUsageRow| row.usage.user_id,
+                )),
+            )
+            .inner;
+        actions
+            .actions
+            .into_iter()
+            .for_each(|(act, user_id)| match act {
+                0 => {
+                    self.set_view(AdminPanelView::UserData {
+                        user_id,
+                        view: AdminPanelUserDataView::Events {
+                            table: TableView::new("admin_events_table"),
+                        },
+                    });
+                    self.state.admin_state.load_user_state(user_id);
+                }
+                _ => {}
+            });
+    }
+}
@@ -1,5 +1,6 @@
 use super::super::{CalendarApp, CalendarView, EventsView};
 use crate::{
+    state::capabilities::{CapabilityAction, CapabilityTable},
     tables::DbTable,
     ui::{
         event_card::EventCard,
@@ -19,29 +20,29 @@ use num_traits::FromPrimitive;
 
 impl CalendarApp {
     pub(super) fn calendar_view_picker(&mut self, ui: &mut egui::Ui, view: CalendarView) {
-        let permissions = self.get_selected_user_permissions();
+        let capabilities = self.get_selected_capabilities();
         ui.with_layout(Layout::left_to_right(Align::TOP), |ui| {
             let height = ui
                 .horizontal(|ui| {
-                    ui.enabled_selectable_header(
+                    ui.permitted_selectable_header(
                         "Events",
-                        permissions.events.view,
+                        capabilities.table(CapabilityTable::Events, CapabilityAction::View),
                         view.is_events(),
                         || {
                             self.set_view(EventsView::Month);
                         },
                     );
-                    ui.enabled_selectable_header(
+                    ui.permitted_selectable_header(
                         "Schedules",
-                        permissions.schedules.view,
+                        capabilities.table(CapabilityTable::Schedules, CapabilityAction::View),
                         view.is_schedules(),
                         || {
                             self.set_view(CalendarView::Schedules);
                         },
                     );
-                    ui.enabled_selectable_header(
+                    ui.permitted_selectable_header(
                         "Templates",
-                        permissions.event_templates.view,
+                        capabilities.table(CapabilityTable::EventTemplates, CapabilityAction::View),
                         view.is_event_templates(),
                         || {
                             self.set_view(CalendarView::EventTemplates);
@@ -56,43 +57,47 @@ impl CalendarApp {
                 egui::Vec2::new(ui.available_width(), height),
                 Layout::right_to_left(Align::Center),
                 |ui| match view {
+                    // Links and previews can't change anything, so there is nothing to explain
+                    _ if self.state.is_public_view() || self.is_preview() => {}
                     CalendarView::Events(_) => {
-                        if permissions.events.create {
-                            if ui
-                                .add_enabled(
-                                    !PopupManager::get().is_open(PopupType::is_new_event),
-                                    egui::Button::new("Create Event"),
-                                )
-                                .clicked()
-                            {
-                                PopupManager::get().open_new_event(self.selected_user_id);
-                            }
+                        if ui
+                            .add_enabled_permitted(
+                                !PopupManager::get().is_open(PopupType::is_new_event),
+                                capabilities
+                                    .table(CapabilityTable::Events, CapabilityAction::Create),
+                                egui::Button::new("Create Event"),
+                            )
+                            .clicked()
+                        {
+                            PopupManager::get().open_new_event(self.selected_user_id);
                         }
                     }
                     CalendarView::Schedules => {
-                        if permissions.schedules.create {
-                            if ui
-                                .add_enabled(
-                                    !PopupManager::get().is_open(PopupType::is_new_schedule),
-                                    egui::Button::new("Create Schedule"),
-                                )
-                                .clicked()
-                            {
-                                PopupManager::get().open_new_schedule(self.selected_user_id);
-                            }
+                        if ui
+                            .add_enabled_permitted(
+                                !PopupManager::get().is_open(PopupType::is_new_schedule),
+                                capabilities
+                                    .table(CapabilityTable::Schedules, CapabilityAction::Create),
+                                egui::Button::new("Create Schedule"),
+                            )
+                            .clicked()
+                        {
+                            PopupManager::get().open_new_schedule(self.selected_user_id);
                         }
                     }
                     CalendarView::EventTemplates => {
-                        if permissions.event_templates.create {
-                            if ui
-                                .add_enabled(
-                                    !PopupManager::get().is_open(PopupType::is_new_event_template),
-                                    egui::Button::new("Create Template"),
-                                )
-                                .clicked()
-                            {
-                                PopupManager::get().open_new_event_template(self.selected_user_id);
-                            }
+                        if ui
+                            .add_enabled_permitted(
+                                !PopupManager::get().is_open(PopupType::is_new_event_template),
+                                capabilities.table(
+                                    CapabilityTable::EventTemplates,
+                                    CapabilityAction::Create,
+                                ),
+                                egui::Button::new("Create Template"),
+                            )
+                            .clicked()
+                        {
+                            PopupManager::get().open_new_event_template(self.selected_user_id);
                         }
                     }
                 },
@@ -216,7 +221,7 @@ impl CalendarApp {
 
         let weekday_height = response.rect.height();
 
-        let level = self.get_selected_access_level();
+        let capabilities = self.get_selected_capabilities();
         let num_of_weeks = if month
             == (first_day + chrono::Days::new(7 * 5))
                 .week(Weekday::Mon)
@@ -275,8 +280,7 @@ impl CalendarApp {
                                             &self,
                                             egui::Vec2::new(column_width - 2., 200.),
                                             event,
-                                            level,
-                                            self.get_selected_user_permissions().events,
+                                            capabilities,
                                         )
                                         .small(),
                                     );
@@ -285,8 +289,7 @@ impl CalendarApp {
                                             &self,
                                             egui::Vec2::new(column_width, 200.),
                                             event,
-                                            level,
-                                            self.get_selected_user_permissions().events,
+                                            capabilities,
                                         ));
                                     });
                                 };
@@ -356,7 +359,8 @@ impl CalendarApp {
                 weekday_human_name
             };
 
-            ui.horizontal_top(|ui| {
+            let mut cards = vec![];
+            let response = ui.horizontal_top(|ui| {
                 (0..7).for_each(|weekday| {
                     let date = monday + chrono::Days::new(weekday);
                     let weekday = chrono::Weekday::from_u64(weekday).unwrap();
@@ -372,26 +376,29 @@ impl CalendarApp {
                         ui.vertical_centered(|ui| ui.heading(text));
                         ui.add_space(4.);
 
-                        let level = self.get_selected_access_level();
+                        let capabilities = self.get_selected_capabilities();
                         self.prepare_date(date);
                         self.state
                             .get_events_for_date(date)
                             .iter()
                             .for_each(|event| {
-                                ui.add(
+                                let response = ui.add(
                                     EventCard::new(
                                         &self,
                                         egui::Vec2::new(column_width, 200.),
                                         &event,
-                                        level,
-                                        self.get_selected_user_permissions().events,
+                                        capabilities,
                                     )
                                     .hide_date(),
                                 );
+                                if event.id != -1 {
+                                    cards.push((event.id, response.rect));
+                                }
                             });
                     });
                 });
             });
+            self.event_lasso(ui, response.response.rect, cards);
         });
     }
 
@@ -400,7 +407,7 @@ impl CalendarApp {
             let column_width = 200.;
             let num_of_columns = get_columns_from_width(ui, column_width);
 
-            let level = self.get_selected_access_level();
+            let capabilities = self.get_selected_capabilities();
             self.prepare_date(date);
             // TODO: Use array_chunks, once it becomes stable
             // https://github.com/rust-lang/rust/issues/100450
@@ -423,8 +430,7 @@ impl CalendarApp {
                                 &self,
                                 egui::Vec2::new(column_width, 200.),
                                 &event,
-                                level,
-                                self.get_selected_user_permissions().events,
+                                capabilities,
                             ));
                         });
                     });
@@ -452,7 +458,7 @@ impl CalendarApp {
                 egui::CollapsingHeader::new(RichText::new(header_text).heading())
                     .default_open(day >= 0)
                     .show_unindented(ui, |ui| {
-                        let level = self.get_selected_access_level();
+                        let capabilities = self.get_selected_capabilities();
                         self.prepare_date(date);
                         // TODO: Use array_chunks, once it becomes stable
                         // https://github.com/rust-lang/rust/issues/100450
@@ -476,8 +482,7 @@ impl CalendarApp {
                                                 &self,
                                                 egui::Vec2::new(column_width, 200.),
                                                 &event,
-                                                level,
-                                                self.get_selected_user_permissions().events,
+                                                capabilities,
                                             )
                                             .hide_date(),
                                         );
@@ -519,8 +524,7 @@ impl CalendarApp {
                                 &self,
                                 egui::Vec2::new(column_width, 200.),
                                 &schedule,
-                                self.get_selected_access_level(),
-                                self.get_selected_user_permissions().schedules,
+                                self.get_selected_capabilities(),
                             ));
                         });
                     });
@@ -558,8 +562,7 @@ impl CalendarApp {
                                 &self,
                                 egui::Vec2::new(column_width, 200.),
                                 &template,
-                                self.get_selected_access_level(),
-                                self.get_selected_user_permissions().event_templates,
+                                self.get_selected_capabilities(),
                             ));
                         });
                     });
@@ -1,11 +1,18 @@
 use calendar_lib::api::{
     event_templates::types::EventTemplate, events::types::Event, schedules::types::Schedule,
-    utils::User,
+    utils::TableId,
 };
 use derive_is_enum_variant::is_enum_variant;
 use serde::{Deserialize, Serialize};
 
-use crate::ui::table_view::TableView;
+use super::ui::usage_ui::UsageMetric;
+use crate::{
+    state::audit_log::AuditTable,
+    ui::{
+        table_view::TableView,
+        tables::{usage::UserUsageRow, users::AdminUserRow},
+    },
+};
 
 #[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq, is_enum_variant)]
 pub(super) enum AppView {
@@ -24,12 +31,18 @@ pub(super) enum CalendarView {
 #[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq, is_enum_variant)]
 pub(super) enum AdminPanelView {
     Users {
-        table: TableView<User>,
+        table: TableView<AdminUserRow>,
     },
     UserData {
         user_id: i32,
         view: AdminPanelUserDataView,
     },
+    Usage {
+        /// Days of history
+        days: u32,
+        metric: UsageMetric,
+        table: TableView<UserUsageRow>,
+    },
 }
 
 #[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq, is_enum_variant)]
@@ -43,6 +56,10 @@ pub(super) enum AdminPanelUserDataView {
 pub(super) enum ManageAccessView {
     Sharing,
     AccessLevels,
+    Activity {
+        actor_id: Option<TableId>,
+        table: Option<AuditTable>,
+    },
 }
 
 impl Into<AppView> for CalendarView {
@@ -1,4 +1,9 @@
-use crate::local_storage::{LocalStorage, LocalStorageTrait};
+use crate::{
+    encryption::{decrypt, encrypt},
+    local_storage::{LocalStorage, LocalStorageTrait},
+    preferences::Preferences,
+    state::state_cache::CachedState,
+};
 
 pub struct AppLocalStorage {
     local_storage: LocalStorage,
@@ -21,4 +26,32 @@ impl AppLocalStorage {
     pub fn clear_jwt(&mut self) {
         self.local_storage.clear(Self::JWT);
     }
+
+    /// Encrypted with the session jwt, so it is unreadable without the session
+    const STATE_CACHE: &'static str = "state_cache";
+    pub fn get_state_cache(&mut self, jwt: &str) -> Option<CachedState> {
+        self.local_storage
+            .get::<String>(Self::STATE_CACHE)
+            .and_then(|data| decrypt(jwt, &data))
+            .and_then(|data| serde_json::from_slice(&data).ok())
+    }
+    pub fn store_state_cache(&mut self, jwt: &str, cache: &CachedState) {
+        if let Some(data) = serde_json::to_vec(cache)
+            .ok()
+            .and_then(|data| encrypt(jwt, &data))
+        {
+            self.local_storage.put(Self::STATE_CACHE, &data);
+        }
+    }
+    pub fn clear_state_cache(&mut self) {
+        self.local_storage.clear(Self::STATE_CACHE);
+    }
+
+    const PREFERENCES: &'static str = "preferences";
+    pub fn get_preferences(&mut self) -> Option<Preferences> {
+        self.local_storage.get(Self::PREFERENCES)
+    }
+    pub fn store_preferences(&mut self, preferences: &Preferences) {
+        self.local_storage.put(Self::PREFERENCES, preferences);
+    }
 }
@@ -4,14 +4,60 @@ use serde::Deserialize;
 pub struct Config {
     pub is_localhost: bool,
     pub api_url: String,
+    /// Seconds between background refreshes of the loaded states
+    pub refresh_interval: i64,
+    /// Address the app is opened at, share links point to it.
+    /// Empty, when it isn't known
+    pub app_url: String,
+    /// Token of the share link, the app was opened with
+    pub share_token: Option<String>,
 }
 
 impl Config {
+    const DEFAULT_REFRESH_INTERVAL: i64 = 60;
+    const MAX_REFRESH_INTERVAL: i64 = 24 * 60 * 60;
+
+    /// None, when the app url isn't known, so the link can't be opened
+    pub fn make_share_link(&self, token: &str) -> Option<String> {
+        (!self.app_url.is_empty()).then(|| format!("{}?share={}", self.app_url, token))
+    }
+
+    /// Invalid values fall back to the default
+    fn validate_refresh_interval(interval: Option<i64>) -> i64 {
+        match interval {
+            Some(interval) if (1..=Self::MAX_REFRESH_INTERVAL).contains(&interval) => interval,
+            Some(interval) => {
+                println!("Refresh interval {interval} is out of range, using default");
+                Self::DEFAULT_REFRESH_INTERVAL
+            }
+            None => Self::DEFAULT_REFRESH_INTERVAL,
+        }
+    }
+
+    /// Config without a server, for tests
+    #[cfg(test)]
+    pub fn local() -> Self {
+        Self {
+            is_localhost: true,
+            api_url: String::new(),
+            refresh_interval: Self::DEFAULT_REFRESH_INTERVAL,
+            app_url: String::new(),
+            share_token: None,
+        }
+    }
+
     #[cfg(not(target_arch = "wasm32"))]
     pub fn load() -> Self {
         Self {
             is_localhost: false,
             api_url: std::env::var("API_URL").expect("Error loading API_URL for env"),
+            refresh_interval: Self::validate_refresh_interval(
+                std::env::var("REFRESH_INTERVAL")
+                    .ok()
+                    .and_then(|interval| interval.parse().ok()),
+            ),
+            app_url: std::env::var("APP_URL").unwrap_or_default(),
+            share_token: None,
         }
     }
 
@@ -31,9 +77,24 @@ impl Config {
 
         let api_url = format!("{protocol}://{hostname}:{port}/api/");
 
+        let app_url = format!(
+            "{}{}",
+            location.origin().unwrap(),
+            location.pathname().unwrap()
+        );
+        let share_token = location.href().ok().and_then(|href| {
+            url::Url::parse(&href).ok().and_then(|url| {
+                url.query_pairs()
+                    .find_map(|(key, value)| (key == "share").then(|| value.into_owned()))
+            })
+        });
+
         Self {
             is_localhost,
             api_url,
+            refresh_interval: Self::DEFAULT_REFRESH_INTERVAL,
+            app_url,
+            share_token,
         }
     }
 }
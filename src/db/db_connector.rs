@@ -1,5 +1,6 @@
 use std::any::Any;
 use std::cell::Ref;
+use std::collections::HashSet;
 use std::sync::mpsc::{channel, Receiver, Sender};
 use std::sync::{atomic, Arc, RwLock};
 use std::{cell::RefCell, rc::Rc};
@@ -131,6 +132,11 @@ impl DbConnectorData {
     pub fn push_jwt(&self, jwt: String) {
         *self.jwt.write().unwrap() = Some(jwt);
     }
+
+    #[cfg(target_arch = "wasm32")]
+    pub fn make_url(&self, op: &str) -> String {
+        self.server_url.clone() + op
+    }
 }
 
 pub struct DbConnector {
@@ -144,6 +150,10 @@ pub struct DbConnector {
     results: Rc<RefCell<Vec<RequestResult<Bytes>>>>,
     typed_results: Rc<RefCell<Vec<RequestResult<Box<dyn Any>>>>>,
 
+    // Requests that were sent, but whose response wasn't received yet.
+    // Responses with any other id are either duplicates or don't belong to us, so they are dropped
+    pending: HashSet<RequestId>,
+
     pub error_handler: Box<dyn FnMut(reqwest::Error)>,
 }
 
@@ -155,6 +165,7 @@ impl DbConnector {
             reciever,
             results: Rc::new(RefCell::new(Vec::new())),
             typed_results: Rc::new(RefCell::new(Vec::new())),
+            pending: HashSet::new(),
             error_handler: Box::new(|error| println!("ConnectorError: {error:?}")),
         }
     }
@@ -170,6 +181,7 @@ impl DbConnector {
 
         let client = data.client.clone();
         let sender = self.sender.clone();
+        self.pending.insert(request_id);
         easy_spawn(async move {
             let res: Result<reqwest::Response, reqwest::Error> = client.execute(request).await;
 
@@ -194,10 +206,39 @@ impl DbConnector {
     }
 
     pub fn pull_responses(&mut self) {
-        let mut pulled = self.reciever.try_iter().collect::<Vec<_>>();
+        let mut pulled = self
+            .reciever
+            .try_iter()
+            .filter(|result| {
+                let is_pending = self.pending.remove(&result.id);
+                if !is_pending {
+                    println!(
+                        "Dropping response for unknown or already completed request {}",
+                        result.id
+                    );
+                }
+                is_pending
+            })
+            .collect::<Vec<_>>();
         self.results.borrow_mut().append(&mut pulled);
     }
 
+    /// Completes the request with the given result, as if it came from the server
+    #[cfg(test)]
+    pub fn complete_request(
+        &mut self,
+        id: RequestId,
+        result: reqwest::Result<(StatusCode, Bytes)>,
+    ) {
+        self.pending.insert(id);
+        self.sender.send(RequestResult::new(id, result)).unwrap();
+        self.pull_responses();
+    }
+
+    pub fn any_pending_requests(&self) -> bool {
+        !self.pending.is_empty()
+    }
+
     pub fn send_requests(&mut self) {
         let requests = RequestsHolder::get().take();
         requests.into_iter().for_each(|request| {
@@ -338,3 +379,56 @@ impl DbConnector {
             })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn send_response(connector: &DbConnector, id: RequestId) {
+        connector
+            .sender
+            .send(RequestResult::new(
+                id,
+                Ok((StatusCode::OK, Bytes::from_static(b"null"))),
+            ))
+            .unwrap();
+    }
+
+    #[test]
+    fn pending_response_is_pulled() {
+        let mut connector = DbConnector::new();
+        connector.pending.insert(1);
+        send_response(&connector, 1);
+        connector.pull_responses();
+
+        assert!(connector.is_request_completed(1));
+        assert!(!connector.any_pending_requests());
+    }
+
+    #[test]
+    fn unknown_response_is_dropped() {
+        let mut connector = DbConnector::new();
+        connector.pending.insert(1);
+        send_response(&connector, 2);
+        connector.pull_responses();
+
+        assert!(!connector.is_request_completed(2));
+        assert!(connector.any_pending_requests());
+    }
+
+    #[test]
+    fn consumed_response_is_dropped() {
+        let mut connector = DbConnector::new();
+        connector.pending.insert(1);
+        send_response(&connector, 1);
+        connector.pull_responses();
+        connector.convert_response::<(), ()>(1);
+        assert!(connector.take_response::<(), ()>(1).is_some());
+
+        // Duplicate of the response, that was already taken
+        send_response(&connector, 1);
+        connector.pull_responses();
+        assert!(!connector.is_request_completed(1));
+        assert!(connector.take_response::<(), ()>(1).is_none());
+    }
+}
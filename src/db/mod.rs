@@ -1,5 +1,6 @@
 pub mod aliases;
 pub mod db_connector;
+pub mod push_channel;
 pub mod request;
 pub mod requests_holder;
 pub mod table;
@@ -0,0 +1,409 @@
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    mpsc::{channel, Receiver, Sender},
+    Arc,
+};
+#[cfg(target_arch = "wasm32")]
+use std::{cell::RefCell, rc::Rc};
+
+use calendar_lib::api::utils::TableId;
+use itertools::Itertools;
+use serde::Deserialize;
+
+use super::db_connector::DbConnectorData;
+
+// TODO: move to lib
+pub const SUBSCRIBE_PATH: &'static str = "sync/subscribe";
+// TODO: move to lib
+/// EventSource can't send headers, so it is authorized by a short-lived single use ticket
+#[cfg(target_arch = "wasm32")]
+pub const TICKET_PATH: &'static str = "sync/ticket";
+
+// TODO: move to lib
+#[cfg(target_arch = "wasm32")]
+#[derive(Debug, Clone, Deserialize)]
+pub struct StreamTicket {
+    pub ticket: String,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize, PartialEq)]
+pub enum ChangedTable {
+    AccessLevels,
+    Events,
+    EventTemplates,
+    Schedules,
+    GrantedPermissions,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize, PartialEq)]
+pub enum ChangeKind {
+    /// Item was inserted or updated
+    Upserted,
+    Deleted,
+}
+
+/// Single change notification, as sent by the server
+#[derive(Debug, Clone, Deserialize)]
+pub struct TableChange {
+    /// Owner of the changed table
+    pub user_id: TableId,
+    pub table: ChangedTable,
+    pub id: TableId,
+    pub kind: ChangeKind,
+}
+
+/// Server-Sent Events subscription, that delivers changes made by other clients
+pub struct PushChannel {
+    receiver: Receiver<TableChange>,
+    connected: Arc<AtomicBool>,
+    closed: Arc<AtomicBool>,
+
+    /// Opened once the ticket is received
+    #[cfg(target_arch = "wasm32")]
+    event_source: Rc<RefCell<Option<web_sys::EventSource>>>,
+}
+
+impl PushChannel {
+    #[cfg(not(target_arch = "wasm32"))]
+    const RECONNECT_DELAY: std::time::Duration = std::time::Duration::from_secs(5);
+
+    fn open() -> (Self, Sender<TableChange>) {
+        let (sender, receiver) = channel();
+        let push_channel = Self {
+            receiver,
+            connected: Arc::new(AtomicBool::new(false)),
+            closed: Arc::new(AtomicBool::new(false)),
+            #[cfg(target_arch = "wasm32")]
+            event_source: Rc::new(RefCell::new(None)),
+        };
+        (push_channel, sender)
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn connect() -> Self {
+        let (push_channel, sender) = Self::open();
+        push_channel.subscribe(
+            sender,
+            || {
+                DbConnectorData::get()
+                    .make_request2(reqwest::Method::GET, SUBSCRIBE_PATH, true)
+                    .header(reqwest::header::ACCEPT, "text/event-stream")
+            },
+            Self::RECONNECT_DELAY,
+        );
+        push_channel
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    pub fn connect() -> Self {
+        let (push_channel, sender) = Self::open();
+        push_channel.subscribe(sender);
+        push_channel
+    }
+
+    pub fn is_connected(&self) -> bool {
+        self.connected.load(Ordering::SeqCst)
+    }
+
+    pub fn pull(&self) -> Vec<TableChange> {
+        self.receiver.try_iter().collect_vec()
+    }
+
+    /// Same as dropping the channel
+    pub fn close(self) {}
+}
+
+/// Reconnect loop lives on its own, so it has to be stopped explicitly
+impl Drop for PushChannel {
+    fn drop(&mut self) {
+        self.closed.store(true, Ordering::SeqCst);
+        #[cfg(target_arch = "wasm32")]
+        if let Some(event_source) = self.event_source.borrow_mut().take() {
+            event_source.close();
+        }
+    }
+}
+
+impl PushChannel {
+    #[cfg(not(target_arch = "wasm32"))]
+    fn subscribe<F>(
+        &self,
+        sender: Sender<TableChange>,
+        make_request: F,
+        reconnect_delay: std::time::Duration,
+    ) where
+        F: Fn() -> reqwest::RequestBuilder + Send + 'static,
+    {
+        use crate::utils::easy_spawn;
+
+        let connected = self.connected.clone();
+        let closed = self.closed.clone();
+        easy_spawn(async move {
+            while !closed.load(Ordering::SeqCst) {
+                match make_request().send().await {
+                    Ok(mut response) if response.status().is_success() => {
+                        connected.store(true, Ordering::SeqCst);
+                        let mut buffer = String::new();
+                        while let Ok(Some(chunk)) = response.chunk().await {
+                            if closed.load(Ordering::SeqCst) {
+                                break;
+                            }
+                            buffer.push_str(&String::from_utf8_lossy(&chunk));
+                            for change in take_events(&mut buffer) {
+                                // Receiver is dropped together with the channel
+                                if sender.send(change).is_err() {
+                                    return;
+                                }
+                            }
+                        }
+                        connected.store(false, Ordering::SeqCst);
+                    }
+                    Ok(response) => {
+                        println!("Push channel refused with status '{}'", response.status());
+                    }
+                    Err(error) => {
+                        println!("Push channel error: {error:?}");
+                    }
+                }
+                tokio::time::sleep(reconnect_delay).await;
+            }
+        });
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    fn subscribe(&self, sender: Sender<TableChange>) {
+        Self::open_with_ticket(
+            sender,
+            self.connected.clone(),
+            self.closed.clone(),
+            self.event_source.clone(),
+        );
+    }
+
+    /// Tickets are single use, so a new one is needed, once the browser stops reconnecting
+    #[cfg(target_arch = "wasm32")]
+    fn open_with_ticket(
+        sender: Sender<TableChange>,
+        connected: Arc<AtomicBool>,
+        closed: Arc<AtomicBool>,
+        event_source: Rc<RefCell<Option<web_sys::EventSource>>>,
+    ) {
+        use crate::utils::easy_spawn;
+
+        easy_spawn(async move {
+            let ticket = DbConnectorData::get()
+                .make_request2(reqwest::Method::POST, TICKET_PATH, true)
+                .send()
+                .await;
+            let ticket = match ticket {
+                Ok(response) => response.json::<StreamTicket>().await,
+                Err(error) => Err(error),
+            };
+            match ticket {
+                // Channel could be dropped while the ticket was requested
+                Ok(_) if closed.load(Ordering::SeqCst) => {}
+                Ok(ticket) => {
+                    let opened = Self::open_event_source(
+                        sender,
+                        connected,
+                        closed,
+                        event_source.clone(),
+                        ticket,
+                    );
+                    *event_source.borrow_mut() = opened;
+                }
+                Err(error) => println!("Unable to get push channel ticket: {error:?}"),
+            }
+        });
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    fn open_event_source(
+        sender: Sender<TableChange>,
+        connected: Arc<AtomicBool>,
+        closed: Arc<AtomicBool>,
+        event_source_cell: Rc<RefCell<Option<web_sys::EventSource>>>,
+        ticket: StreamTicket,
+    ) -> Option<web_sys::EventSource> {
+        use wasm_bindgen::{closure::Closure, JsCast};
+
+        let url = format!(
+            "{}?ticket={}",
+            DbConnectorData::get().make_url(SUBSCRIBE_PATH),
+            ticket.ticket
+        );
+        let event_source = match web_sys::EventSource::new(&url) {
+            Ok(event_source) => event_source,
+            Err(error) => {
+                println!("Unable to open push channel: {error:?}");
+                return None;
+            }
+        };
+
+        let on_message = Closure::<dyn FnMut(web_sys::MessageEvent)>::new({
+            let sender = sender.clone();
+            move |event: web_sys::MessageEvent| {
+                if let Some(data) = event.data().as_string() {
+                    match serde_json::from_str::<TableChange>(&data) {
+                        Ok(change) => {
+                            let _ = sender.send(change);
+                        }
+                        Err(error) => println!("Unknown push notification: {error:?}"),
+                    }
+                }
+            }
+        });
+        event_source.set_onmessage(Some(on_message.as_ref().unchecked_ref()));
+        on_message.forget();
+
+        // Browser reconnects by itself, we only have to track the status
+        let on_open = Closure::<dyn FnMut()>::new({
+            let connected = connected.clone();
+            move || {
+                connected.store(true, Ordering::SeqCst);
+            }
+        });
+        event_source.set_onopen(Some(on_open.as_ref().unchecked_ref()));
+        on_open.forget();
+
+        let on_error = Closure::<dyn FnMut()>::new(move || {
+            connected.store(false, Ordering::SeqCst);
+            let gave_up = event_source_cell
+                .borrow()
+                .as_ref()
+                .is_some_and(|event_source| {
+                    event_source.ready_state() == web_sys::EventSource::CLOSED
+                });
+            if gave_up && !closed.load(Ordering::SeqCst) {
+                Self::open_with_ticket(
+                    sender.clone(),
+                    connected.clone(),
+                    closed.clone(),
+                    event_source_cell.clone(),
+                );
+            }
+        });
+        event_source.set_onerror(Some(on_error.as_ref().unchecked_ref()));
+        on_error.forget();
+
+        Some(event_source)
+    }
+}
+
+/// Removes all complete events from the buffer and parses their data
+#[cfg(not(target_arch = "wasm32"))]
+fn take_events(buffer: &mut String) -> Vec<TableChange> {
+    let mut changes = vec![];
+    while let Some(end) = buffer.find("\n\n") {
+        let event = buffer.drain(..end + 2).collect::<String>();
+        let data = event
+            .lines()
+            .filter_map(|line| line.strip_prefix("data:"))
+            .map(|data| data.trim_start())
+            .join("\n");
+        if data.is_empty() {
+            // Comments and keep-alive messages
+            continue;
+        }
+        match serde_json::from_str::<TableChange>(&data) {
+            Ok(change) => changes.push(change),
+            Err(error) => println!("Unknown push notification: {error:?}"),
+        }
+    }
+    changes
+}
+
+#[cfg(test)]
+#[cfg(not(target_arch = "wasm32"))]
+mod tests {
+    use super::*;
+    use std::{sync::atomic::AtomicUsize, time::Duration};
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    const EVENTS: &str = ": keep-alive\n\n\
+        data: {\"user_id\":1,\"table\":\"Events\",\"id\":5,\"kind\":\"Upserted\"}\n\n\
+        data: {\"user_id\":1,\"table\":\"Schedules\",\"id\":7,\"kind\":\"Deleted\"}\n\n";
+
+    /// Stand-in for the server, that sends the events to each connection and closes it
+    async fn serve(events: &'static str) -> (String, Arc<AtomicUsize>) {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!(
+            "http://{}/{}",
+            listener.local_addr().unwrap(),
+            SUBSCRIBE_PATH
+        );
+        let connections = Arc::new(AtomicUsize::new(0));
+        tokio::spawn({
+            let connections = connections.clone();
+            async move {
+                loop {
+                    let (mut stream, _) = listener.accept().await.unwrap();
+                    connections.fetch_add(1, Ordering::SeqCst);
+                    let mut request = [0; 1024];
+                    let _ = stream.read(&mut request).await;
+                    let response = format!(
+                        "HTTP/1.1 200 OK\r\n\
+                        Content-Type: text/event-stream\r\n\
+                        Content-Length: {}\r\n\r\n{}",
+                        events.len(),
+                        events
+                    );
+                    let _ = stream.write_all(response.as_bytes()).await;
+                }
+            }
+        });
+        (url, connections)
+    }
+
+    fn connect_to(url: String, reconnect_delay: Duration) -> PushChannel {
+        let (push_channel, sender) = PushChannel::open();
+        push_channel.subscribe(
+            sender,
+            move || reqwest::Client::new().get(url.clone()),
+            reconnect_delay,
+        );
+        push_channel
+    }
+
+    #[test]
+    fn events_are_taken_only_when_complete() {
+        let mut buffer = EVENTS[..EVENTS.len() - 1].to_owned();
+        let changes = take_events(&mut buffer);
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].table, ChangedTable::Events);
+        assert_eq!(changes[0].kind, ChangeKind::Upserted);
+
+        buffer.push('\n');
+        let changes = take_events(&mut buffer);
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].table, ChangedTable::Schedules);
+        assert_eq!(changes[0].kind, ChangeKind::Deleted);
+        assert!(buffer.is_empty());
+    }
+
+    #[tokio::test]
+    async fn changes_are_received() {
+        let (url, _) = serve(EVENTS).await;
+        let push_channel = connect_to(url, Duration::from_secs(60));
+        tokio::time::sleep(Duration::from_millis(200)).await;
+
+        let changes = push_channel.pull();
+        assert_eq!(changes.len(), 2);
+        assert_eq!((changes[0].user_id, changes[0].id), (1, 5));
+        assert_eq!((changes[1].user_id, changes[1].id), (1, 7));
+    }
+
+    #[tokio::test]
+    async fn dropped_channel_stops_reconnecting() {
+        let (url, connections) = serve(EVENTS).await;
+        let push_channel = connect_to(url, Duration::from_millis(20));
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        assert!(connections.load(Ordering::SeqCst) > 1);
+
+        drop(push_channel);
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        let after_drop = connections.load(Ordering::SeqCst);
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        assert_eq!(connections.load(Ordering::SeqCst), after_drop);
+    }
+}
@@ -1,4 +1,4 @@
-use std::{fmt::Debug, marker::PhantomData, sync::atomic::AtomicU16};
+use std::{fmt::Debug, marker::PhantomData, sync::atomic::AtomicU64};
 
 use serde::de::DeserializeOwned;
 
@@ -7,8 +7,9 @@ use super::{
     requests_holder::{RequestData, RequestsHolder},
 };
 
-pub type RequestId = u16;
-pub type RequestIdAtomic = AtomicU16;
+/// Ids are never reused within a session, so a late response can't be mistaken for a new request
+pub type RequestId = u64;
+pub type RequestIdAtomic = AtomicU64;
 
 // TODO: move to lib
 pub trait RequestType
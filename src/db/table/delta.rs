@@ -0,0 +1,13 @@
+use serde::Deserialize;
+
+use super::item::TableId;
+
+// TODO: move to lib
+/// Changes of a single table since the requested version
+#[derive(Deserialize)]
+pub struct TableDelta<T> {
+    pub version: u64,
+    /// Inserted and updated items
+    pub updated: Vec<T>,
+    pub deleted: Vec<TableId>,
+}
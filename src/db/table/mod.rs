@@ -1,7 +1,9 @@
 pub mod basic;
+pub mod delta;
 pub mod item;
 pub mod table;
 pub mod utils;
 
 pub use basic::*;
+pub use delta::*;
 pub use item::*;
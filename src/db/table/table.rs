@@ -1,5 +1,6 @@
 use super::{
     basic::DbTable,
+    delta::TableDelta,
     item::{DbTableItem, TableId},
 };
 
@@ -51,6 +52,14 @@ impl<T: DbTableItem> Table<T> {
     pub fn replace_all(&mut self, new_data: Vec<T>) {
         self.items = new_data;
     }
+    pub fn apply_delta(&mut self, delta: TableDelta<T>) {
+        delta.deleted.into_iter().for_each(|id| {
+            self.remove_one(id);
+        });
+        delta.updated.into_iter().for_each(|item| {
+            self.push_one(item);
+        });
+    }
 }
 
 impl<T: DbTableItem> DbTable<T> for Table<T> {
@@ -0,0 +1,43 @@
+use base64::{engine::general_purpose::STANDARD, Engine};
+use chacha20poly1305::{
+    aead::{Aead, AeadCore, KeyInit, OsRng},
+    ChaCha20Poly1305, Key, Nonce,
+};
+use sha2::{Digest, Sha256};
+
+const NONCE_SIZE: usize = 12;
+
+/// Key is bound to the secret, so data can't be read after the secret is gone
+fn derive_key(secret: &str) -> Key {
+    let mut hasher = Sha256::new();
+    hasher.update(b"calendar-local-data");
+    hasher.update(secret.as_bytes());
+    Key::clone_from_slice(&hasher.finalize())
+}
+
+/// Returns base64 of nonce followed by the ciphertext
+pub fn encrypt(secret: &str, data: &[u8]) -> Option<String> {
+    let cipher = ChaCha20Poly1305::new(&derive_key(secret));
+    let nonce = ChaCha20Poly1305::generate_nonce(&mut OsRng);
+    match cipher.encrypt(&nonce, data) {
+        Ok(ciphertext) => {
+            let mut result = nonce.to_vec();
+            result.extend(ciphertext);
+            Some(STANDARD.encode(result))
+        }
+        Err(error) => {
+            println!("Error while encrypting data: {error:?}");
+            None
+        }
+    }
+}
+
+pub fn decrypt(secret: &str, data: &str) -> Option<Vec<u8>> {
+    let data = STANDARD.decode(data).ok()?;
+    if data.len() < NONCE_SIZE {
+        return None;
+    }
+    let (nonce, ciphertext) = data.split_at(NONCE_SIZE);
+    let cipher = ChaCha20Poly1305::new(&derive_key(secret));
+    cipher.decrypt(Nonce::from_slice(nonce), ciphertext).ok()
+}
@@ -7,7 +7,10 @@ mod app;
 mod app_local_storage;
 mod config;
 mod db;
+mod encryption;
 mod local_storage;
+mod permission_presets;
+mod preferences;
 mod state;
 mod tables;
 mod ui;
@@ -0,0 +1,147 @@
+use calendar_lib::api::permissions::types::{Permissions, TablePermissions};
+use serde::{Deserialize, Serialize};
+
+/// Permissions, as they are chosen by the share giver
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct PermissionFlags {
+    pub events_view: bool,
+    pub events_edit: bool,
+    pub event_templates_view: bool,
+    pub event_templates_edit: bool,
+    pub schedules_view: bool,
+    pub schedules_edit: bool,
+    pub sharing: bool,
+    pub access_levels_edit: bool,
+}
+
+impl PermissionFlags {
+    pub const VIEWER: Self = Self {
+        events_view: true,
+        events_edit: false,
+        event_templates_view: true,
+        event_templates_edit: false,
+        schedules_view: true,
+        schedules_edit: false,
+        sharing: false,
+        access_levels_edit: false,
+    };
+    pub const ASSISTANT: Self = Self {
+        events_view: true,
+        events_edit: true,
+        event_templates_view: true,
+        event_templates_edit: true,
+        schedules_view: true,
+        schedules_edit: true,
+        sharing: false,
+        access_levels_edit: false,
+    };
+    pub const CO_OWNER: Self = Self {
+        events_view: true,
+        events_edit: true,
+        event_templates_view: true,
+        event_templates_edit: true,
+        schedules_view: true,
+        schedules_edit: true,
+        sharing: true,
+        access_levels_edit: true,
+    };
+
+    /// Names and values of all flags, in the order they are shown
+    pub fn describe(&self) -> [(&'static str, bool); 8] {
+        [
+            ("View Events", self.events_view),
+            ("Edit Events", self.events_edit),
+            ("View Event Templates", self.event_templates_view),
+            ("Edit Event Templates", self.event_templates_edit),
+            ("View Schedules", self.schedules_view),
+            ("Edit Schedules", self.schedules_edit),
+            ("Manage Sharing", self.sharing),
+            ("Edit Access Levels", self.access_levels_edit),
+        ]
+    }
+
+    pub fn from_permissions(permissions: &Permissions) -> Self {
+        Self {
+            events_view: permissions.events.view,
+            events_edit: permissions.events.edit,
+            event_templates_view: permissions.event_templates.view,
+            event_templates_edit: permissions.event_templates.edit,
+            schedules_view: permissions.schedules.view,
+            schedules_edit: permissions.schedules.edit,
+            sharing: permissions.allow_share,
+            access_levels_edit: permissions.access_levels.edit,
+        }
+    }
+
+    pub fn make_permissions(&self, access_level: i32) -> Permissions {
+        Permissions {
+            access_level,
+            access_levels: TablePermissions {
+                view: self.access_levels_edit || self.sharing,
+                edit: self.access_levels_edit,
+                create: self.access_levels_edit,
+                delete: self.access_levels_edit,
+            },
+            events: TablePermissions {
+                view: self.events_view || self.events_edit,
+                edit: self.events_edit,
+                create: self.events_edit,
+                delete: self.events_edit,
+            },
+            event_templates: TablePermissions {
+                view: self.event_templates_view
+                    || self.event_templates_edit
+                    || self.schedules_view
+                    || self.schedules_edit,
+                edit: self.event_templates_edit,
+                create: self.event_templates_edit,
+                delete: self.event_templates_edit,
+            },
+            schedules: TablePermissions {
+                view: self.schedules_view || self.schedules_edit,
+                edit: self.schedules_edit,
+                create: self.schedules_edit,
+                delete: self.schedules_edit,
+            },
+            allow_share: self.sharing,
+        }
+    }
+
+    /// Combinations, that can't be granted as they are
+    pub fn find_inconsistency(&self) -> Option<&'static str> {
+        if self.events_edit && !self.events_view {
+            Some("Events can't be edited without viewing them")
+        } else if self.event_templates_edit && !self.event_templates_view {
+            Some("Event templates can't be edited without viewing them")
+        } else if self.schedules_edit && !self.schedules_view {
+            Some("Schedules can't be edited without viewing them")
+        } else if self.schedules_view && !self.event_templates_view {
+            Some("Schedules can't be viewed without their event templates")
+        } else {
+            None
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PermissionPreset {
+    pub name: String,
+    pub flags: PermissionFlags,
+}
+
+impl PermissionPreset {
+    pub fn new(name: impl Into<String>, flags: PermissionFlags) -> Self {
+        Self {
+            name: name.into(),
+            flags,
+        }
+    }
+
+    pub fn builtin() -> Vec<Self> {
+        vec![
+            Self::new("Viewer", PermissionFlags::VIEWER),
+            Self::new("Assistant", PermissionFlags::ASSISTANT),
+            Self::new("Co-owner", PermissionFlags::CO_OWNER),
+        ]
+    }
+}
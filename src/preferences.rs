@@ -0,0 +1,81 @@
+use std::{
+    collections::HashSet,
+    sync::{Mutex, MutexGuard},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{permission_presets::PermissionPreset, tables::TableId};
+
+/// Settings of this device, that are kept between sessions
+#[derive(Default, Serialize, Deserialize)]
+pub struct Preferences {
+    #[serde(default)]
+    permission_presets: Vec<PermissionPreset>,
+    /// Shares, that the expiry reminder was already shown for
+    #[serde(default)]
+    reminded_shares: HashSet<TableId>,
+
+    #[serde(skip)]
+    changed: bool,
+}
+
+impl Preferences {
+    pub fn get() -> MutexGuard<'static, Self> {
+        use std::sync::OnceLock;
+
+        static DATA: OnceLock<Mutex<Preferences>> = OnceLock::new();
+        DATA.get_or_init(|| Mutex::new(Preferences::default()))
+            .lock()
+            .unwrap()
+    }
+
+    /// Builtin presets come first and can't be changed
+    pub fn get_permission_presets(&self) -> Vec<PermissionPreset> {
+        PermissionPreset::builtin()
+            .into_iter()
+            .chain(self.permission_presets.iter().cloned())
+            .collect()
+    }
+
+    pub fn get_user_permission_presets(&self) -> &[PermissionPreset] {
+        &self.permission_presets
+    }
+
+    /// Preset with the same name is replaced
+    pub fn save_permission_preset(&mut self, preset: PermissionPreset) {
+        match self
+            .permission_presets
+            .iter_mut()
+            .find(|p| p.name == preset.name)
+        {
+            Some(p) => *p = preset,
+            None => self.permission_presets.push(preset),
+        }
+        self.changed = true;
+    }
+
+    pub fn remove_permission_preset(&mut self, name: &str) {
+        self.permission_presets.retain(|p| p.name != name);
+        self.changed = true;
+    }
+
+    pub fn is_share_reminded(&self, id: TableId) -> bool {
+        self.reminded_shares.contains(&id)
+    }
+
+    pub fn mark_shares_reminded(&mut self, ids: impl IntoIterator<Item = TableId>) {
+        self.reminded_shares.extend(ids);
+        self.changed = true;
+    }
+
+    pub fn clear_reminded_shares(&mut self) {
+        self.reminded_shares.clear();
+        self.changed = true;
+    }
+
+    /// Returns true once per change
+    pub fn take_changed(&mut self) -> bool {
+        std::mem::take(&mut self.changed)
+    }
+}
@@ -0,0 +1,323 @@
+use calendar_lib::api::{
+    roles::types::Role,
+    utils::{EmptyResponse, User},
+};
+use chrono::{NaiveDate, NaiveDateTime};
+use serde::{Deserialize, Serialize};
+
+use crate::{db::request::RequestIdentifier, tables::TableId};
+
+use super::{admin_state::AdminState, main_state::State, request::*};
+
+// TODO: move to lib
+#[derive(Debug, Clone, Serialize)]
+pub struct AdminCreateUserBody {
+    pub name: String,
+    pub email: String,
+    pub password: String,
+    pub roles: Vec<Role>,
+}
+
+#[derive(Clone, Copy)]
+pub struct AdminCreateUserRequest {}
+impl RequestType for AdminCreateUserRequest {
+    const URL: &'static str = "admin/users/create";
+    const IS_AUTHORIZED: bool = true;
+    const METHOD: reqwest::Method = reqwest::Method::POST;
+
+    type Query = ();
+    type Body = AdminCreateUserBody;
+    type Response = User;
+
+    type Info = ();
+}
+#[allow(unused_variables)]
+impl StateRequestType for AdminCreateUserRequest {
+    fn push_to_state(response: Self::Response, info: Self::Info, state: &mut State) {
+        state.admin_state.load_state();
+    }
+
+    fn push_bad_to_state(response: Self::BadResponse, info: Self::Info, state: &mut State) {}
+}
+
+// TODO: move to lib
+/// Fields, that are None, are not changed
+#[derive(Debug, Clone, Serialize)]
+pub struct AdminUpdateUserBody {
+    pub user_id: TableId,
+    pub name: Option<String>,
+    pub email: Option<String>,
+    pub roles: Option<Vec<Role>>,
+    pub disabled: Option<bool>,
+}
+
+#[derive(Clone, Copy)]
+pub struct AdminUpdateUserRequest {}
+impl RequestType for AdminUpdateUserRequest {
+    const URL: &'static str = "admin/users/update";
+    const IS_AUTHORIZED: bool = true;
+    const METHOD: reqwest::Method = reqwest::Method::POST;
+
+    type Query = ();
+    type Body = AdminUpdateUserBody;
+    type Response = EmptyResponse;
+
+    /// user_id
+    type Info = TableId;
+}
+#[allow(unused_variables)]
+impl StateRequestType for AdminUpdateUserRequest {
+    fn push_to_state(response: Self::Response, info: Self::Info, state: &mut State) {
+        state.admin_state.load_state();
+    }
+
+    fn push_bad_to_state(response: Self::BadResponse, info: Self::Info, state: &mut State) {
+        state.admin_state.load_state();
+    }
+}
+
+// TODO: move to lib
+#[derive(Debug, Clone, Serialize)]
+pub struct AdminResetPasswordBody {
+    pub user_id: TableId,
+    pub new_password: String,
+}
+
+#[derive(Clone, Copy)]
+pub struct AdminResetPasswordRequest {}
+impl RequestType for AdminResetPasswordRequest {
+    const URL: &'static str = "admin/users/reset_password";
+    const IS_AUTHORIZED: bool = true;
+    const METHOD: reqwest::Method = reqwest::Method::POST;
+
+    type Query = ();
+    type Body = AdminResetPasswordBody;
+    type Response = EmptyResponse;
+
+    /// user_id
+    type Info = TableId;
+}
+#[allow(unused_variables)]
+impl StateRequestType for AdminResetPasswordRequest {
+    fn push_to_state(response: Self::Response, info: Self::Info, state: &mut State) {}
+
+    fn push_bad_to_state(response: Self::BadResponse, info: Self::Info, state: &mut State) {}
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AdminDeleteUserArgs {
+    pub user_id: TableId,
+}
+
+#[derive(Clone, Copy)]
+pub struct AdminDeleteUserRequest {}
+impl RequestType for AdminDeleteUserRequest {
+    const URL: &'static str = "admin/users/delete";
+    const IS_AUTHORIZED: bool = true;
+    const METHOD: reqwest::Method = reqwest::Method::DELETE;
+
+    type Query = AdminDeleteUserArgs;
+    type Response = EmptyResponse;
+
+    /// user_id
+    type Info = TableId;
+}
+#[allow(unused_variables)]
+impl StateRequestType for AdminDeleteUserRequest {
+    fn push_to_state(response: Self::Response, info: Self::Info, state: &mut State) {
+        let user_id = info;
+        state.admin_state.users.get_table_mut().remove_one(user_id);
+        state.admin_state.users_data.remove(&user_id);
+        state.admin_state.users_info.remove(&user_id);
+    }
+
+    fn push_bad_to_state(response: Self::BadResponse, info: Self::Info, state: &mut State) {
+        state.admin_state.load_state();
+    }
+}
+
+// TODO: move to lib
+/// Account details, that are only visible to admins
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AdminUserInfo {
+    pub user_id: TableId,
+    pub disabled: bool,
+    pub last_login: Option<NaiveDateTime>,
+    pub events: usize,
+    pub event_templates: usize,
+    pub schedules: usize,
+}
+
+#[derive(Clone, Copy)]
+pub struct AdminLoadUsersInfoRequest {}
+impl RequestType for AdminLoadUsersInfoRequest {
+    const URL: &'static str = "admin/users/info";
+    const IS_AUTHORIZED: bool = true;
+    const METHOD: reqwest::Method = reqwest::Method::GET;
+
+    type Query = ();
+    type Response = Vec<AdminUserInfo>;
+
+    type Info = ();
+}
+#[allow(unused_variables)]
+impl StateRequestType for AdminLoadUsersInfoRequest {
+    fn push_to_state(response: Self::Response, info: Self::Info, state: &mut State) {
+        state.admin_state.users_info = response
+            .into_iter()
+            .map(|info| (info.user_id, info))
+            .collect();
+    }
+
+    fn push_bad_to_state(response: Self::BadResponse, info: Self::Info, state: &mut State) {
+        state.admin_state.users_info.clear();
+    }
+}
+
+// TODO: move to lib
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UserUsage {
+    pub user_id: TableId,
+    pub events: usize,
+    pub event_templates: usize,
+    pub schedules: usize,
+    pub granted_permissions: usize,
+    /// Size of the user data in the database
+    pub storage_bytes: u64,
+}
+
+// TODO: move to lib
+/// System-wide totals at the end of a day
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UsageSnapshot {
+    pub date: NaiveDate,
+    pub users: usize,
+    pub events: usize,
+    pub event_templates: usize,
+    pub schedules: usize,
+    pub granted_permissions: usize,
+    pub storage_bytes: u64,
+}
+
+// TODO: move to lib
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AdminUsage {
+    pub users: Vec<UserUsage>,
+    /// Ordered by date
+    pub history: Vec<UsageSnapshot>,
+}
+
+impl AdminUsage {
+    /// Current totals, history can be a day behind
+    pub fn get_totals(&self) -> UsageSnapshot {
+        UsageSnapshot {
+            date: chrono::Local::now().naive_local().date(),
+            users: self.users.len(),
+            events: self.users.iter().map(|usage| usage.events).sum(),
+            event_templates: self.users.iter().map(|usage| usage.event_templates).sum(),
+            schedules: self.users.iter().map(|usage| usage.schedules).sum(),
+            granted_permissions: self
+                .users
+                .iter()
+                .map(|usage| usage.granted_permissions)
+                .sum(),
+            storage_bytes: self.users.iter().map(|usage| usage.storage_bytes).sum(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AdminLoadUsageArgs {
+    /// Days of history
+    pub days: u32,
+}
+
+#[derive(Clone, Copy)]
+pub struct AdminLoadUsageRequest {}
+impl RequestType for AdminLoadUsageRequest {
+    const URL: &'static str = "admin/usage";
+    const IS_AUTHORIZED: bool = true;
+    const METHOD: reqwest::Method = reqwest::Method::GET;
+
+    type Query = AdminLoadUsageArgs;
+    type Response = AdminUsage;
+
+    type Info = ();
+}
+#[allow(unused_variables)]
+impl StateRequestType for AdminLoadUsageRequest {
+    fn push_to_state(response: Self::Response, info: Self::Info, state: &mut State) {
+        state.admin_state.usage = Some(response);
+    }
+
+    fn push_bad_to_state(response: Self::BadResponse, info: Self::Info, state: &mut State) {}
+}
+
+impl AdminState {
+    pub fn create_user(
+        &self,
+        name: String,
+        email: String,
+        password: String,
+        roles: Vec<Role>,
+    ) -> RequestIdentifier<AdminCreateUserRequest> {
+        make_state_request((), |connector| {
+            connector
+                .make_request::<AdminCreateUserRequest>()
+                .json(&AdminCreateUserBody {
+                    name,
+                    email,
+                    password,
+                    roles,
+                })
+        })
+    }
+
+    pub fn update_user(
+        &self,
+        body: AdminUpdateUserBody,
+    ) -> RequestIdentifier<AdminUpdateUserRequest> {
+        make_state_request(body.user_id, |connector| {
+            connector
+                .make_request::<AdminUpdateUserRequest>()
+                .json(&body)
+        })
+    }
+
+    pub fn reset_password(
+        &self,
+        user_id: TableId,
+        new_password: String,
+    ) -> RequestIdentifier<AdminResetPasswordRequest> {
+        make_state_request(user_id, |connector| {
+            connector
+                .make_request::<AdminResetPasswordRequest>()
+                .json(&AdminResetPasswordBody {
+                    user_id,
+                    new_password,
+                })
+        })
+    }
+
+    pub fn delete_user(&self, user_id: TableId) -> RequestIdentifier<AdminDeleteUserRequest> {
+        make_state_request(user_id, |connector| {
+            connector
+                .make_request::<AdminDeleteUserRequest>()
+                .query(&AdminDeleteUserArgs { user_id })
+        })
+    }
+
+    pub fn load_users_info(&self) -> RequestIdentifier<AdminLoadUsersInfoRequest> {
+        make_state_request((), |connector| {
+            connector.make_request::<AdminLoadUsersInfoRequest>()
+        })
+    }
+
+    pub fn load_usage(&self, days: u32) -> RequestIdentifier<AdminLoadUsageRequest> {
+        make_state_request((), |connector| {
+            connector
+                .make_request::<AdminLoadUsageRequest>()
+                .query(&AdminLoadUsageArgs { days })
+        })
+    }
+}
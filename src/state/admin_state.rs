@@ -2,11 +2,18 @@ use std::collections::HashMap;
 
 use calendar_lib::api::utils::User;
 
-use super::{state_table::StateTable, user_state::UserState};
+use super::{
+    admin_requests::{AdminUsage, AdminUserInfo},
+    state_table::StateTable,
+    user_state::UserState,
+};
 
 pub struct AdminState {
     pub users: StateTable<User>,
     pub users_data: HashMap<i32, UserState>,
+    pub users_info: HashMap<i32, AdminUserInfo>,
+    /// Loaded on demand, it is expensive for the server
+    pub usage: Option<AdminUsage>,
 }
 
 impl AdminState {
@@ -14,6 +21,8 @@ impl AdminState {
         Self {
             users: StateTable::new(),
             users_data: HashMap::default(),
+            users_info: HashMap::default(),
+            usage: None,
         }
     }
 }
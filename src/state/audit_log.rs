@@ -0,0 +1,387 @@
+use std::sync::{Mutex, MutexGuard};
+
+use calendar_lib::api::{
+    event_templates::types::EventTemplate,
+    events::types::Event,
+    permissions::types::GrantedPermission,
+    schedules::types::Schedule,
+    utils::{TableId, User},
+};
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+
+use crate::tables::{plan_exceptions::PlanException, share_links::ShareLink, DbTableItem};
+
+use super::{
+    capabilities::{Capabilities, Capability, CapabilityAction, CapabilityTable},
+    main_state::UserState,
+    undo_history::UndoHistory,
+};
+
+/// Tables, which changes are recorded
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AuditTable {
+    Events,
+    EventTemplates,
+    Schedules,
+    PlanExceptions,
+    Permissions,
+    ShareLinks,
+}
+
+impl AuditTable {
+    pub const ALL: [AuditTable; 6] = [
+        AuditTable::Events,
+        AuditTable::EventTemplates,
+        AuditTable::Schedules,
+        AuditTable::PlanExceptions,
+        AuditTable::Permissions,
+        AuditTable::ShareLinks,
+    ];
+
+    pub fn human_name(&self) -> &'static str {
+        match self {
+            AuditTable::Events => "Events",
+            AuditTable::EventTemplates => "Event Templates",
+            AuditTable::Schedules => "Schedules",
+            AuditTable::PlanExceptions => "Schedule Exceptions",
+            AuditTable::Permissions => "Sharing",
+            AuditTable::ShareLinks => "Share Links",
+        }
+    }
+
+    pub fn item_human_name(&self) -> &'static str {
+        match self {
+            AuditTable::Events => "Event",
+            AuditTable::EventTemplates => "Event template",
+            AuditTable::Schedules => "Schedule",
+            AuditTable::PlanExceptions => "Schedule exception",
+            AuditTable::Permissions => "Share",
+            AuditTable::ShareLinks => "Share link",
+        }
+    }
+
+    fn capability_table(&self) -> Option<CapabilityTable> {
+        match self {
+            AuditTable::Events => Some(CapabilityTable::Events),
+            AuditTable::EventTemplates => Some(CapabilityTable::EventTemplates),
+            AuditTable::Schedules | AuditTable::PlanExceptions => Some(CapabilityTable::Schedules),
+            AuditTable::Permissions | AuditTable::ShareLinks => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AuditAction {
+    Insert,
+    Update,
+    Delete,
+}
+
+impl AuditAction {
+    pub fn human_name(&self) -> &'static str {
+        match self {
+            AuditAction::Insert => "Created",
+            AuditAction::Update => "Updated",
+            AuditAction::Delete => "Deleted",
+        }
+    }
+}
+
+pub trait TableItemAudit
+where
+    Self: DbTableItem,
+{
+    const AUDIT_TABLE: AuditTable;
+}
+
+impl TableItemAudit for Event {
+    const AUDIT_TABLE: AuditTable = AuditTable::Events;
+}
+
+impl TableItemAudit for EventTemplate {
+    const AUDIT_TABLE: AuditTable = AuditTable::EventTemplates;
+}
+
+impl TableItemAudit for Schedule {
+    const AUDIT_TABLE: AuditTable = AuditTable::Schedules;
+}
+
+impl TableItemAudit for PlanException {
+    const AUDIT_TABLE: AuditTable = AuditTable::PlanExceptions;
+}
+
+impl TableItemAudit for GrantedPermission {
+    const AUDIT_TABLE: AuditTable = AuditTable::Permissions;
+}
+
+impl TableItemAudit for ShareLink {
+    const AUDIT_TABLE: AuditTable = AuditTable::ShareLinks;
+}
+
+/// Mutation, issued by this client
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuditEntry {
+    pub id: u64,
+    /// Owner of the changed data
+    pub user_id: TableId,
+    pub actor_id: TableId,
+    pub actor_name: String,
+    pub time: NaiveDateTime,
+    pub table: AuditTable,
+    pub action: AuditAction,
+    /// Inserted items get their id from the server response
+    pub item_id: Option<TableId>,
+    pub before: Option<serde_json::Value>,
+    /// Updates only contain the changed fields
+    pub after: Option<serde_json::Value>,
+    pub reverted: bool,
+    /// Revert was sent, but not yet accepted by the server
+    #[serde(skip)]
+    pub reverting: bool,
+}
+
+impl AuditEntry {
+    pub fn get_item_name(&self) -> Option<&str> {
+        self.before
+            .iter()
+            .chain(self.after.iter())
+            .find_map(|value| value.get("name").and_then(|name| name.as_str()))
+    }
+
+    fn get_access_level(&self) -> Option<i32> {
+        self.before
+            .as_ref()
+            .or(self.after.as_ref())?
+            .get("access_level")?
+            .as_i64()
+            .map(|level| level as i32)
+    }
+
+    /// Moves the entry to the item, recreated under `new_id`
+    pub(super) fn replace_item_id(
+        &mut self,
+        user_id: TableId,
+        table: AuditTable,
+        old_id: TableId,
+        new_id: TableId,
+    ) {
+        if self.user_id != user_id || self.table != table || self.item_id != Some(old_id) {
+            return;
+        }
+        self.item_id = Some(new_id);
+        [&mut self.before, &mut self.after]
+            .into_iter()
+            .flatten()
+            .filter_map(|value| value.get_mut("id"))
+            .for_each(|id| *id = new_id.into());
+    }
+
+    pub fn is_revertible(&self) -> bool {
+        !self.reverted && !self.reverting && self.table.is_undoable()
+    }
+
+    pub fn revert_capability(&self, capabilities: &Capabilities) -> Capability {
+        let action = match self.action {
+            AuditAction::Insert => CapabilityAction::Delete,
+            AuditAction::Update => CapabilityAction::Edit,
+            AuditAction::Delete => CapabilityAction::Create,
+        };
+        match (self.table.capability_table(), self.get_access_level()) {
+            (Some(table), Some(access_level)) => capabilities.item(table, action, access_level),
+            (Some(table), None) => capabilities.table(table, action),
+            (None, _) => capabilities.share(),
+        }
+    }
+}
+
+/// Change, sent to the server.
+/// Recorded in the audit log and the undo history once the server accepts it
+#[derive(Debug, Clone)]
+pub struct TrackedChange {
+    table: AuditTable,
+    action: AuditAction,
+    item_id: Option<TableId>,
+    before: Option<serde_json::Value>,
+    after: Option<serde_json::Value>,
+    /// Undo step the change belongs to, None if it can't be undone
+    undo_step: Option<u64>,
+    /// Audit entry, which is reverted by this change
+    reverts: Option<u64>,
+    /// Id of the deleted item, this insert brings back
+    recreates: Option<TableId>,
+}
+
+impl TrackedChange {
+    pub(super) fn new(
+        table: AuditTable,
+        action: AuditAction,
+        item_id: Option<TableId>,
+        before: Option<serde_json::Value>,
+        after: Option<serde_json::Value>,
+    ) -> Self {
+        Self {
+            table,
+            action,
+            item_id,
+            before,
+            after,
+            undo_step: UndoHistory::get().reserve_step(table),
+            reverts: AuditLog::get().reverting,
+            recreates: None,
+        }
+    }
+
+    pub(super) fn recreating(self, old_id: TableId) -> Self {
+        Self {
+            recreates: Some(old_id),
+            ..self
+        }
+    }
+
+    pub(super) fn accept(self, user_id: TableId, inserted_id: Option<TableId>) {
+        let recreated = self.recreates.zip(inserted_id);
+        let entry = {
+            let mut audit_log = AuditLog::get();
+            if let Some((old_id, new_id)) = recreated {
+                audit_log
+                    .entries
+                    .iter_mut()
+                    .for_each(|entry| entry.replace_item_id(user_id, self.table, old_id, new_id));
+            }
+            if let Some(id) = self.reverts {
+                audit_log.mark_reverted(id);
+            }
+            audit_log.record(
+                user_id,
+                self.table,
+                self.action,
+                inserted_id.or(self.item_id),
+                self.before,
+                self.after,
+            )
+        };
+        let mut undo_history = UndoHistory::get();
+        if let Some((old_id, new_id)) = recreated {
+            undo_history.replace_item_id(user_id, self.table, old_id, new_id);
+        }
+        if let Some(step) = self.undo_step {
+            undo_history.record(step, entry);
+        }
+    }
+
+    pub(super) fn reject(self) {
+        if let Some(id) = self.reverts {
+            AuditLog::get().set_reverting(id, false);
+        }
+    }
+}
+
+/// Local log of the changes, made from this device
+#[derive(Default)]
+pub struct AuditLog {
+    actor: Option<(TableId, String)>,
+    next_id: u64,
+    entries: Vec<AuditEntry>,
+    /// Entry, which revert requests are being issued
+    reverting: Option<u64>,
+}
+
+impl AuditLog {
+    const MAX_ENTRIES: usize = 500;
+
+    pub fn get() -> MutexGuard<'static, Self> {
+        use std::sync::OnceLock;
+
+        static DATA: OnceLock<Mutex<AuditLog>> = OnceLock::new();
+        DATA.get_or_init(|| Mutex::new(AuditLog::default()))
+            .lock()
+            .unwrap()
+    }
+
+    pub(super) fn set_actor(&mut self, actor: &User) {
+        self.actor = Some((actor.id, actor.name.clone()));
+    }
+
+    pub(super) fn clear(&mut self) {
+        *self = Self::default();
+    }
+
+    fn record(
+        &mut self,
+        user_id: TableId,
+        table: AuditTable,
+        action: AuditAction,
+        item_id: Option<TableId>,
+        before: Option<serde_json::Value>,
+        after: Option<serde_json::Value>,
+    ) -> AuditEntry {
+        let (actor_id, actor_name) = self
+            .actor
+            .clone()
+            .unwrap_or_else(|| (-1, "Unknown".to_owned()));
+        self.entries.push(AuditEntry {
+            id: self.next_id,
+            user_id,
+            actor_id,
+            actor_name,
+            time: chrono::Local::now().naive_local(),
+            table,
+            action,
+            item_id,
+            before,
+            after,
+            reverted: false,
+            reverting: false,
+        });
+        self.next_id += 1;
+        if self.entries.len() > Self::MAX_ENTRIES {
+            self.entries.remove(0);
+        }
+        self.entries.last().unwrap().clone()
+    }
+
+    pub(super) fn get_all(&self) -> &[AuditEntry] {
+        &self.entries
+    }
+
+    pub(super) fn restore(&mut self, entries: Vec<AuditEntry>) {
+        self.next_id = entries.iter().map(|entry| entry.id + 1).max().unwrap_or(0);
+        self.entries = entries;
+    }
+
+    /// Newest first
+    pub fn get_entries(&self, user_id: TableId) -> Vec<AuditEntry> {
+        self.entries
+            .iter()
+            .rev()
+            .filter(|entry| entry.user_id == user_id)
+            .cloned()
+            .collect()
+    }
+
+    fn mark_reverted(&mut self, id: u64) {
+        if let Some(entry) = self.entries.iter_mut().find(|entry| entry.id == id) {
+            entry.reverted = true;
+            entry.reverting = false;
+        }
+    }
+
+    fn set_reverting(&mut self, id: u64, reverting: bool) {
+        if let Some(entry) = self.entries.iter_mut().find(|entry| entry.id == id) {
+            entry.reverting = reverting;
+        }
+    }
+}
+
+impl UserState {
+    /// Reverting is a change itself, so it gets its own entry.
+    /// Entry is marked reverted once the server accepts the revert
+    pub fn revert_audit_entry(&self, entry: &AuditEntry) {
+        AuditLog::get().reverting = Some(entry.id);
+        let sent = self.apply_audit_entry(entry, true).is_some();
+        let mut audit_log = AuditLog::get();
+        audit_log.reverting = None;
+        audit_log.set_reverting(entry.id, sent);
+    }
+}
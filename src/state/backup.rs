@@ -0,0 +1,697 @@
+use std::collections::HashMap;
+
+use calendar_lib::api::{
+    auth::types::AccessLevel,
+    event_templates::types::{EventTemplate, NewEventTemplate},
+    events::types::{Event, NewEvent},
+    permissions::types::{GrantedPermission, NewGrantedPermission},
+    schedules::types::{EventPlan, NewEventPlan, NewSchedule, Schedule},
+    utils::{TableId, User},
+};
+use chrono::{NaiveDate, NaiveDateTime};
+use itertools::Itertools;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    db::request::RequestIdentifier,
+    tables::{
+        share_validities::{ShareValidity, ShareWithValidity},
+        DbTable, DbTableGetById,
+    },
+};
+
+use super::{
+    main_state::State,
+    table_requests::{InsertResponse, TableInsertRequest, TableItemInsert},
+    undo_history::UndoHistory,
+    user_state::UserState,
+};
+
+/// Complete calendar of a user. Ids are those of the original account
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CalendarBackup {
+    pub version: u32,
+    pub created: NaiveDateTime,
+    pub user_id: TableId,
+    pub access_levels: Vec<AccessLevel>,
+    pub events: Vec<Event>,
+    pub event_templates: Vec<EventTemplate>,
+    pub schedules: Vec<Schedule>,
+    pub granted_permissions: Vec<GrantedPermission>,
+    /// Receivers of the granted permissions, as they are shared by email
+    pub users: Vec<User>,
+    /// Validity windows of the granted permissions. Missing before version 2
+    #[serde(default)]
+    pub share_validities: Vec<ShareValidity>,
+}
+
+impl CalendarBackup {
+    pub const VERSION: u32 = 2;
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap_or_default()
+    }
+
+    pub fn from_json(text: &str) -> Result<Self, String> {
+        #[derive(Deserialize)]
+        struct Versioned {
+            version: u32,
+        }
+
+        let versioned =
+            serde_json::from_str::<Versioned>(text).map_err(|_| "Not a backup".to_owned())?;
+        if versioned.version > Self::VERSION {
+            return Err(format!(
+                "Backup version {} is newer than supported {}",
+                versioned.version,
+                Self::VERSION
+            ));
+        }
+        serde_json::from_str(text).map_err(|err| err.to_string())
+    }
+
+    /// Levels, that don't exist in the target account, are lowered to the closest one,
+    /// so that nothing becomes more visible than it was
+    pub fn remap_access_level(level: i32, target: &[AccessLevel]) -> i32 {
+        target
+            .iter()
+            .map(|target| target.level)
+            .filter(|&target| target <= level)
+            .max()
+            .or_else(|| target.iter().map(|target| target.level).min())
+            .unwrap_or(level)
+    }
+
+    fn get_receiver_email(&self, permission: &GrantedPermission) -> Option<String> {
+        self.users
+            .iter()
+            .find(|user| user.id == permission.receiver_user_id)
+            .map(|user| user.email.clone())
+    }
+
+    /// Shares without a stored validity are always valid
+    fn get_share_validity(&self, permission_id: TableId) -> (Option<NaiveDate>, Option<NaiveDate>) {
+        self.share_validities
+            .iter()
+            .find(|validity| validity.permission_id == permission_id)
+            .map_or((None, None), |validity| (validity.start, validity.end))
+    }
+}
+
+impl UserState {
+    pub fn make_backup(&self) -> CalendarBackup {
+        let granted_permissions = self
+            .granted_permissions
+            .get_table()
+            .get()
+            .iter()
+            .filter(|permission| permission.giver_user_id == self.user_id)
+            .cloned()
+            .collect_vec();
+        let share_validities = self
+            .share_validities
+            .get_table()
+            .get()
+            .iter()
+            .filter(|validity| {
+                granted_permissions
+                    .iter()
+                    .any(|permission| permission.id == validity.permission_id)
+            })
+            .copied()
+            .collect();
+        CalendarBackup {
+            version: CalendarBackup::VERSION,
+            created: chrono::Local::now().naive_local(),
+            user_id: self.user_id,
+            access_levels: self.access_levels.get_table().get().clone(),
+            events: self.events.get_table().get().clone(),
+            event_templates: self.event_templates.get_table().get().clone(),
+            schedules: self.schedules.get_table().get().clone(),
+            granted_permissions,
+            users: self.users.get_table().get().clone(),
+            share_validities,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RestoreStage {
+    Templates,
+    Schedules,
+    Events,
+    Permissions,
+    Finished,
+}
+
+impl RestoreStage {
+    pub fn human_name(&self) -> &'static str {
+        match self {
+            RestoreStage::Templates => "Restoring templates",
+            RestoreStage::Schedules => "Restoring schedules",
+            RestoreStage::Events => "Restoring events",
+            RestoreStage::Permissions => "Restoring sharing",
+            RestoreStage::Finished => "Restored",
+        }
+    }
+}
+
+/// Id, given to the inserted item by the server, once the request is done.
+/// Has to be checked before the state takes the response
+type InsertCheck = Box<dyn Fn(&State) -> Option<Result<TableId, ()>> + Send>;
+
+fn make_insert_check<T: TableItemInsert>(
+    identifier: RequestIdentifier<TableInsertRequest<T>>,
+) -> InsertCheck {
+    Box::new(move |state| {
+        state
+            .db_connector
+            .convert_response::<InsertResponse, T::BadResponse>(identifier.id);
+        state
+            .db_connector
+            .get_response::<InsertResponse, T::BadResponse>(identifier.id)
+            .map(|response| match response {
+                Ok(Ok(response)) => Ok(response.id),
+                _ => Err(()),
+            })
+    })
+}
+
+/// Plans of a restored schedule are matched by their weekday and time
+fn match_plans(old: &[EventPlan], new: &[EventPlan]) -> Vec<(TableId, TableId)> {
+    old.iter()
+        .filter_map(|old_plan| {
+            new.iter()
+                .find(|plan| plan.weekday == old_plan.weekday && plan.time == old_plan.time)
+                .map(|plan| (old_plan.id, plan.id))
+        })
+        .collect()
+}
+
+/// Restore goes table by table, as the new ids are needed by the next one
+pub struct BackupRestore {
+    pub user_id: TableId,
+    pub stage: RestoreStage,
+    pub failed: Vec<String>,
+    backup: CalendarBackup,
+    /// Inserts of the stage with the backup ids of their items
+    pending: Vec<(TableId, InsertCheck)>,
+    /// Schedules are inserted, waiting for the reloaded table to find the new plan ids
+    awaiting_plans: bool,
+    template_ids: HashMap<TableId, TableId>,
+    schedule_ids: HashMap<TableId, TableId>,
+    plan_ids: HashMap<TableId, TableId>,
+}
+
+impl BackupRestore {
+    pub fn is_finished(&self) -> bool {
+        self.stage == RestoreStage::Finished
+    }
+
+    fn new(user_id: TableId, backup: CalendarBackup) -> Self {
+        Self {
+            user_id,
+            stage: RestoreStage::Templates,
+            failed: vec![],
+            backup,
+            pending: vec![],
+            awaiting_plans: false,
+            template_ids: HashMap::new(),
+            schedule_ids: HashMap::new(),
+            plan_ids: HashMap::new(),
+        }
+    }
+
+    fn new_template(&self, template: &EventTemplate, levels: &[AccessLevel]) -> NewEventTemplate {
+        NewEventTemplate {
+            user_id: self.user_id,
+            name: template.name.clone(),
+            event_name: template.event_name.clone(),
+            event_description: template.event_description.clone(),
+            duration: template.duration,
+            access_level: CalendarBackup::remap_access_level(template.access_level, levels),
+        }
+    }
+
+    fn new_schedule(&self, schedule: &Schedule, levels: &[AccessLevel]) -> Option<NewSchedule> {
+        Some(NewSchedule {
+            user_id: self.user_id,
+            template_id: *self.template_ids.get(&schedule.template_id)?,
+            name: schedule.name.clone(),
+            description: schedule.description.clone(),
+            first_day: schedule.first_day,
+            last_day: schedule.last_day,
+            access_level: CalendarBackup::remap_access_level(schedule.access_level, levels),
+            events: schedule
+                .event_plans
+                .iter()
+                .map(|plan| NewEventPlan {
+                    weekday: plan.weekday,
+                    time: plan.time,
+                })
+                .collect(),
+        })
+    }
+
+    fn new_event(&self, event: &Event, levels: &[AccessLevel]) -> NewEvent {
+        NewEvent {
+            user_id: self.user_id,
+            name: event.name.clone(),
+            description: event.description.clone(),
+            start: event.start,
+            end: event.end,
+            access_level: CalendarBackup::remap_access_level(event.access_level, levels),
+            visibility: event.visibility,
+            plan_id: event
+                .plan_id
+                .and_then(|plan_id| self.plan_ids.get(&plan_id).copied()),
+        }
+    }
+
+    /// Sends the requests of the current stage
+    fn send(&mut self, state: &UserState) {
+        let backup = &self.backup;
+        let levels = state.access_levels.get_table().get();
+        let mut failed = vec![];
+        UndoHistory::get().begin_group();
+        self.pending = match self.stage {
+            RestoreStage::Templates => backup
+                .event_templates
+                .iter()
+                .map(|template| {
+                    (
+                        template.id,
+                        make_insert_check(
+                            state
+                                .event_templates
+                                .insert(self.new_template(template, levels)),
+                        ),
+                    )
+                })
+                .collect(),
+            RestoreStage::Schedules => backup
+                .schedules
+                .iter()
+                .filter_map(|schedule| match self.new_schedule(schedule, levels) {
+                    Some(new_schedule) => Some((
+                        schedule.id,
+                        make_insert_check(state.schedules.insert(new_schedule)),
+                    )),
+                    None => {
+                        failed.push(format!("Schedule '{}': template is missing", schedule.name));
+                        None
+                    }
+                })
+                .collect(),
+            RestoreStage::Events => backup
+                .events
+                .iter()
+                .map(|event| {
+                    (
+                        event.id,
+                        make_insert_check(state.events.insert(self.new_event(event, levels))),
+                    )
+                })
+                .collect(),
+            RestoreStage::Permissions => backup
+                .granted_permissions
+                .iter()
+                .filter_map(|permission| {
+                    let email = backup.get_receiver_email(permission)?;
+                    // Restoring into the same account keeps the existing shares
+                    let is_shared = state
+                        .granted_permissions
+                        .get_table()
+                        .get()
+                        .iter()
+                        .filter(|current| current.giver_user_id == self.user_id)
+                        .filter_map(|current| {
+                            state.users.get_table().get_by_id(current.receiver_user_id)
+                        })
+                        .any(|user| user.email == email);
+                    if is_shared {
+                        return None;
+                    }
+                    let mut permissions = permission.permissions;
+                    permissions.access_level =
+                        CalendarBackup::remap_access_level(permissions.access_level, levels);
+                    Some((
+                        permission.id,
+                        make_insert_check(state.granted_permissions.insert_with_info(
+                            ShareWithValidity::new(
+                                NewGrantedPermission {
+                                    giver_user_id: self.user_id,
+                                    receiver_email: email.clone(),
+                                    permissions,
+                                },
+                                backup.get_share_validity(permission.id),
+                            ),
+                            email,
+                        )),
+                    ))
+                })
+                .collect(),
+            RestoreStage::Finished => vec![],
+        };
+        UndoHistory::get().end_group();
+        self.failed.extend(failed);
+        self.awaiting_plans = self.stage == RestoreStage::Schedules;
+    }
+
+    /// Inserted item got its id
+    fn store_id(&mut self, old_id: TableId, new_id: TableId) {
+        match self.stage {
+            RestoreStage::Templates => {
+                self.template_ids.insert(old_id, new_id);
+            }
+            RestoreStage::Schedules => {
+                self.schedule_ids.insert(old_id, new_id);
+            }
+            _ => {}
+        }
+    }
+
+    /// Plans get their ids with the schedule, so they are taken from the reloaded table.
+    /// False, while some of the restored schedules are not loaded yet
+    fn map_plan_ids(&mut self, state: &UserState) -> bool {
+        let schedules = state.schedules.get_table();
+        let pairs = self
+            .backup
+            .schedules
+            .iter()
+            .filter_map(|old| {
+                let new_id = *self.schedule_ids.get(&old.id)?;
+                Some(schedules.get_by_id(new_id).map(|new| (old, new)))
+            })
+            .collect::<Option<Vec<_>>>();
+        match pairs {
+            Some(pairs) => {
+                self.plan_ids = pairs
+                    .into_iter()
+                    .flat_map(|(old, new)| match_plans(&old.event_plans, &new.event_plans))
+                    .collect();
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn next_stage(&mut self) {
+        self.stage = match self.stage {
+            RestoreStage::Templates => RestoreStage::Schedules,
+            RestoreStage::Schedules => RestoreStage::Events,
+            RestoreStage::Events => RestoreStage::Permissions,
+            RestoreStage::Permissions | RestoreStage::Finished => RestoreStage::Finished,
+        };
+    }
+}
+
+impl State {
+    /// Recreates the backup on top of the current data of the user
+    pub fn restore_backup(&mut self, user_id: TableId, backup: CalendarBackup) {
+        if self
+            .restore
+            .as_ref()
+            .is_some_and(|restore| !restore.is_finished())
+        {
+            return;
+        }
+        let mut restore = BackupRestore::new(user_id, backup);
+        restore.send(self.get_user_state(user_id));
+        self.restore = Some(restore);
+    }
+
+    pub fn get_backup_restore(&self) -> Option<&BackupRestore> {
+        self.restore.as_ref()
+    }
+
+    pub(super) fn update_backup_restore(&mut self) {
+        let mut restore = match self.restore.take() {
+            Some(restore) if !restore.is_finished() => restore,
+            restore => {
+                self.restore = restore;
+                return;
+            }
+        };
+        let results = restore
+            .pending
+            .iter()
+            .map(|(_, check)| check(self))
+            .collect_vec();
+        let rejected = results
+            .iter()
+            .filter(|result| matches!(result, Some(Err(_))))
+            .count();
+        if rejected > 0 {
+            restore.failed.push(format!(
+                "{}: {} rejected by the server",
+                restore.stage.human_name(),
+                rejected
+            ));
+        }
+        let pending = std::mem::take(&mut restore.pending);
+        restore.pending = pending
+            .into_iter()
+            .zip(results)
+            .filter_map(|((old_id, check), result)| match result {
+                Some(Ok(new_id)) => {
+                    restore.store_id(old_id, new_id);
+                    None
+                }
+                Some(Err(())) => None,
+                None => Some((old_id, check)),
+            })
+            .collect();
+
+        if restore.pending.is_empty() {
+            match self.try_get_user_state(restore.user_id) {
+                Some(user_state) => {
+                    if restore.awaiting_plans && restore.map_plan_ids(user_state) {
+                        restore.awaiting_plans = false;
+                    }
+                    if !restore.awaiting_plans {
+                        restore.next_stage();
+                        restore.send(user_state);
+                    }
+                }
+                None => {
+                    restore
+                        .failed
+                        .push("Calendar is no longer available".to_owned());
+                    restore.stage = RestoreStage::Finished;
+                }
+            }
+        }
+        self.restore = Some(restore);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use calendar_lib::api::events::types::EventVisibility;
+    use chrono::{NaiveDate, NaiveTime, Weekday};
+
+    fn level(level: i32) -> AccessLevel {
+        AccessLevel {
+            id: level,
+            user_id: 1,
+            level,
+            name: format!("Level {level}"),
+        }
+    }
+
+    fn date(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2024, 1, day).unwrap()
+    }
+
+    fn time(hour: u32) -> NaiveTime {
+        NaiveTime::from_hms_opt(hour, 0, 0).unwrap()
+    }
+
+    fn backup() -> CalendarBackup {
+        CalendarBackup {
+            version: CalendarBackup::VERSION,
+            created: date(1).and_time(time(12)),
+            user_id: 1,
+            access_levels: vec![level(0), level(10)],
+            events: vec![],
+            event_templates: vec![],
+            schedules: vec![],
+            granted_permissions: vec![],
+            users: vec![],
+            share_validities: vec![],
+        }
+    }
+
+    fn plan(id: TableId, weekday: Weekday, hour: u32) -> EventPlan {
+        EventPlan {
+            id,
+            weekday,
+            time: time(hour),
+        }
+    }
+
+    fn schedule(id: TableId, template_id: TableId, event_plans: Vec<EventPlan>) -> Schedule {
+        Schedule {
+            id,
+            user_id: 1,
+            template_id,
+            name: format!("Schedule {id}"),
+            description: None,
+            first_day: date(1),
+            last_day: None,
+            access_level: 10,
+            event_plans,
+        }
+    }
+
+    fn event(plan_id: Option<TableId>) -> Event {
+        Event {
+            id: 1,
+            user_id: 1,
+            name: "Event".to_owned(),
+            description: None,
+            start: date(1).and_time(time(9)),
+            end: date(1).and_time(time(10)),
+            access_level: 10,
+            visibility: EventVisibility::HideName,
+            plan_id,
+        }
+    }
+
+    #[test]
+    fn json_round_trip() {
+        let restored = CalendarBackup::from_json(&backup().to_json()).unwrap();
+        assert_eq!(restored.version, CalendarBackup::VERSION);
+        assert_eq!(restored.user_id, 1);
+        assert_eq!(restored.access_levels.len(), 2);
+    }
+
+    #[test]
+    fn share_validities_round_trip() {
+        let mut original = backup();
+        original.share_validities = vec![
+            ShareValidity {
+                permission_id: 3,
+                start: Some(date(2)),
+                end: Some(date(20)),
+            },
+            ShareValidity {
+                permission_id: 4,
+                start: None,
+                end: Some(date(10)),
+            },
+        ];
+        let restored = CalendarBackup::from_json(&original.to_json()).unwrap();
+        assert_eq!(restored.share_validities, original.share_validities);
+        assert_eq!(
+            restored.get_share_validity(3),
+            (Some(date(2)), Some(date(20)))
+        );
+        assert_eq!(restored.get_share_validity(4), (None, Some(date(10))));
+        assert_eq!(restored.get_share_validity(5), (None, None));
+    }
+
+    #[test]
+    fn shares_of_version_1_are_always_valid() {
+        let mut json = serde_json::to_value(backup()).unwrap();
+        json["version"] = 1.into();
+        json.as_object_mut().unwrap().remove("share_validities");
+        let restored = CalendarBackup::from_json(&json.to_string()).unwrap();
+        assert!(restored.share_validities.is_empty());
+        assert_eq!(restored.get_share_validity(3), (None, None));
+    }
+
+    #[test]
+    fn older_versions_are_read() {
+        let mut old = backup();
+        old.version = CalendarBackup::VERSION - 1;
+        assert!(CalendarBackup::from_json(&old.to_json()).is_ok());
+    }
+
+    #[test]
+    fn newer_versions_are_rejected() {
+        let mut new = backup();
+        new.version = CalendarBackup::VERSION + 1;
+        let error = CalendarBackup::from_json(&new.to_json()).unwrap_err();
+        assert!(error.contains("newer"), "{error}");
+    }
+
+    #[test]
+    fn other_json_is_not_a_backup() {
+        assert_eq!(CalendarBackup::from_json("{}").unwrap_err(), "Not a backup");
+        assert_eq!(
+            CalendarBackup::from_json("[1]").unwrap_err(),
+            "Not a backup"
+        );
+        assert!(CalendarBackup::from_json(r#"{"version": 1}"#).is_err());
+    }
+
+    #[test]
+    fn existing_access_levels_are_kept() {
+        let target = [level(0), level(10), level(20)];
+        assert_eq!(CalendarBackup::remap_access_level(0, &target), 0);
+        assert_eq!(CalendarBackup::remap_access_level(10, &target), 10);
+    }
+
+    #[test]
+    fn missing_access_levels_are_lowered() {
+        let target = [level(0), level(10), level(20)];
+        assert_eq!(CalendarBackup::remap_access_level(15, &target), 10);
+        assert_eq!(CalendarBackup::remap_access_level(100, &target), 20);
+        // Nothing lower exists, so the lowest one is the closest
+        assert_eq!(
+            CalendarBackup::remap_access_level(3, &[level(5), level(10)]),
+            5
+        );
+        assert_eq!(CalendarBackup::remap_access_level(3, &[]), 3);
+    }
+
+    #[test]
+    fn schedules_use_restored_templates() {
+        let mut restore = BackupRestore::new(2, backup());
+        restore.template_ids.insert(5, 50);
+        let levels = [level(0), level(5)];
+
+        let new = restore
+            .new_schedule(&schedule(7, 5, vec![]), &levels)
+            .unwrap();
+        assert_eq!(new.user_id, 2);
+        assert_eq!(new.template_id, 50);
+        assert_eq!(new.access_level, 5);
+        assert!(restore
+            .new_schedule(&schedule(8, 6, vec![]), &levels)
+            .is_none());
+    }
+
+    #[test]
+    fn plans_are_matched_by_weekday_and_time() {
+        let old = [
+            plan(1, Weekday::Mon, 9),
+            plan(2, Weekday::Tue, 10),
+            plan(3, Weekday::Wed, 11),
+        ];
+        let new = [
+            plan(12, Weekday::Tue, 10),
+            plan(11, Weekday::Mon, 9),
+            plan(13, Weekday::Wed, 12),
+        ];
+        assert_eq!(match_plans(&old, &new), vec![(1, 11), (2, 12)]);
+    }
+
+    #[test]
+    fn events_use_restored_plans() {
+        let mut restore = BackupRestore::new(2, backup());
+        restore.plan_ids.insert(1, 11);
+        let levels = [level(0), level(10)];
+
+        assert_eq!(
+            restore.new_event(&event(Some(1)), &levels).plan_id,
+            Some(11)
+        );
+        // Plan wasn't restored, so the event is kept on its own
+        assert_eq!(restore.new_event(&event(Some(2)), &levels).plan_id, None);
+        assert_eq!(restore.new_event(&event(None), &levels).plan_id, None);
+    }
+}
@@ -0,0 +1,269 @@
+use calendar_lib::api::{
+    event_templates::types::NewEventTemplate,
+    events::types::{EventVisibility, UpdateEvent},
+    utils::*,
+};
+use chrono::Duration;
+use itertools::Itertools;
+
+use crate::tables::{DbTable, DbTableGetById};
+
+use super::{
+    capabilities::{CapabilityAction, CapabilityTable},
+    main_state::State,
+    request::{make_request_check, RequestCheck},
+    undo_history::UndoHistory,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum EventBatchAction {
+    Delete,
+    MoveDays(i64),
+    SetAccessLevel(i32),
+    SetVisibility(EventVisibility),
+    ConvertToTemplate,
+}
+
+impl EventBatchAction {
+    pub fn human_name(&self) -> String {
+        match self {
+            EventBatchAction::Delete => "Deleting".to_owned(),
+            EventBatchAction::MoveDays(days) => format!("Moving by {days} days"),
+            EventBatchAction::SetAccessLevel(_) => "Changing access level".to_owned(),
+            EventBatchAction::SetVisibility(_) => "Changing visibility".to_owned(),
+            EventBatchAction::ConvertToTemplate => "Converting to templates".to_owned(),
+        }
+    }
+
+    fn capability(&self) -> (CapabilityTable, CapabilityAction) {
+        match self {
+            EventBatchAction::Delete => (CapabilityTable::Events, CapabilityAction::Delete),
+            EventBatchAction::MoveDays(_)
+            | EventBatchAction::SetAccessLevel(_)
+            | EventBatchAction::SetVisibility(_) => {
+                (CapabilityTable::Events, CapabilityAction::Edit)
+            }
+            EventBatchAction::ConvertToTemplate => {
+                (CapabilityTable::EventTemplates, CapabilityAction::Create)
+            }
+        }
+    }
+}
+
+pub struct EventBatch {
+    pub action: EventBatchAction,
+    pub total: usize,
+    pub succeeded: usize,
+    /// Event name and the reason
+    pub failed: Vec<(String, String)>,
+    pending: Vec<(String, RequestCheck)>,
+}
+
+impl EventBatch {
+    pub fn is_finished(&self) -> bool {
+        self.pending.is_empty()
+    }
+
+    pub fn get_progress(&self) -> f32 {
+        if self.total == 0 {
+            1.
+        } else {
+            (self.succeeded + self.failed.len()) as f32 / self.total as f32
+        }
+    }
+}
+
+impl State {
+    /// Events are changed as one step of the undo history.
+    /// Events, that can't be changed, are reported as failed without a request
+    pub fn run_event_batch(
+        &mut self,
+        user_id: TableId,
+        event_ids: &[TableId],
+        action: EventBatchAction,
+    ) {
+        if self
+            .batch
+            .as_ref()
+            .is_some_and(|batch| !batch.is_finished())
+        {
+            return;
+        }
+        let capabilities = self.get_capabilities(user_id);
+        let (table, capability_action) = action.capability();
+        let mut batch = EventBatch {
+            action,
+            total: event_ids.len(),
+            succeeded: 0,
+            failed: vec![],
+            pending: vec![],
+        };
+
+        UndoHistory::get().begin_group();
+        let user_state = self.get_user_state(user_id);
+        event_ids.iter().for_each(|&id| {
+            let event = match user_state.events.get_table().get_by_id(id) {
+                Some(event) => event.clone(),
+                None => {
+                    batch
+                        .failed
+                        .push((format!("#{id}"), "Event no longer exists".to_owned()));
+                    return;
+                }
+            };
+            if let Err(denied) = capabilities.item(table, capability_action, event.access_level) {
+                batch.failed.push((event.name, denied.explain()));
+                return;
+            }
+
+            let check = match action {
+                EventBatchAction::Delete => make_request_check(user_state.events.delete(id)),
+                EventBatchAction::MoveDays(days) => {
+                    let offset = Duration::try_days(days).unwrap_or_default();
+                    make_request_check(user_state.events.update(UpdateEvent {
+                        id,
+                        name: UNone,
+                        description: UNone,
+                        start: USome(event.start + offset),
+                        end: USome(event.end + offset),
+                        access_level: UNone,
+                        visibility: UNone,
+                        plan_id: UNone,
+                    }))
+                }
+                EventBatchAction::SetAccessLevel(access_level) => {
+                    make_request_check(user_state.events.update(UpdateEvent {
+                        id,
+                        name: UNone,
+                        description: UNone,
+                        start: UNone,
+                        end: UNone,
+                        access_level: USome(access_level),
+                        visibility: UNone,
+                        plan_id: UNone,
+                    }))
+                }
+                EventBatchAction::SetVisibility(visibility) => {
+                    make_request_check(user_state.events.update(UpdateEvent {
+                        id,
+                        name: UNone,
+                        description: UNone,
+                        start: UNone,
+                        end: UNone,
+                        access_level: UNone,
+                        visibility: USome(visibility),
+                        plan_id: UNone,
+                    }))
+                }
+                EventBatchAction::ConvertToTemplate => {
+                    make_request_check(user_state.event_templates.insert(NewEventTemplate {
+                        user_id,
+                        name: event.name.clone(),
+                        event_name: event.name.clone(),
+                        event_description: event.description.clone(),
+                        duration: (event.end - event.start).to_std().unwrap_or_default(),
+                        access_level: event.access_level,
+                    }))
+                }
+            };
+            batch.pending.push((event.name, check));
+        });
+        UndoHistory::get().end_group();
+
+        self.batch = Some(batch);
+    }
+
+    pub fn get_event_batch(&self) -> Option<&EventBatch> {
+        self.batch.as_ref()
+    }
+
+    pub fn dismiss_event_batch(&mut self) {
+        if self.batch.as_ref().is_some_and(|batch| batch.is_finished()) {
+            self.batch = None;
+        }
+    }
+
+    pub(super) fn update_event_batch(&mut self) {
+        let batch = match self.batch.as_ref() {
+            Some(batch) if !batch.is_finished() => batch,
+            _ => return,
+        };
+        let results = batch
+            .pending
+            .iter()
+            .map(|(_, check)| check(self))
+            .collect_vec();
+
+        let batch = self.batch.as_mut().unwrap();
+        let pending = std::mem::take(&mut batch.pending);
+        pending
+            .into_iter()
+            .zip(results)
+            .for_each(|((name, check), result)| match result {
+                Some(true) => batch.succeeded += 1,
+                Some(false) => batch
+                    .failed
+                    .push((name, "Request failed or was rejected".to_owned())),
+                None => batch.pending.push((name, check)),
+            });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bytes::Bytes;
+    use calendar_lib::api::events::types::Event;
+    use reqwest::StatusCode;
+
+    use super::*;
+    use crate::{
+        config::Config,
+        db::request::RequestIdentifier,
+        state::table_requests::{StateRequestInfo, TableDeleteRequest},
+    };
+
+    fn pending_check(id: u64) -> RequestCheck {
+        make_request_check(RequestIdentifier::<TableDeleteRequest<Event>>::new(
+            id,
+            StateRequestInfo::new(1, 1),
+        ))
+    }
+
+    #[test]
+    fn failed_requests_finish_the_batch() {
+        let mut state = State::new(&Config::local());
+        state.batch = Some(EventBatch {
+            action: EventBatchAction::Delete,
+            total: 2,
+            succeeded: 0,
+            failed: vec![],
+            pending: vec![
+                ("first".to_owned(), pending_check(1)),
+                ("second".to_owned(), pending_check(2)),
+            ],
+        });
+
+        state.db_connector.complete_request(
+            1,
+            Err(reqwest::Client::new().get("not a url").build().unwrap_err()),
+        );
+        state.update_event_batch();
+        let batch = state.get_event_batch().unwrap();
+        assert!(!batch.is_finished());
+        assert_eq!(batch.failed.len(), 1);
+
+        state.db_connector.complete_request(
+            2,
+            Ok((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Bytes::from_static(b"oops"),
+            )),
+        );
+        state.update_event_batch();
+        let batch = state.get_event_batch().unwrap();
+        assert!(batch.is_finished());
+        assert_eq!(batch.succeeded, 0);
+        assert_eq!(batch.failed.len(), 2);
+        assert_eq!(batch.get_progress(), 1.);
+    }
+}
@@ -0,0 +1,308 @@
+use calendar_lib::api::{
+    permissions::types::{Permissions, TablePermissions},
+    utils::TableId,
+};
+
+use super::main_state::State;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CapabilityTable {
+    Events,
+    EventTemplates,
+    Schedules,
+    AccessLevels,
+}
+
+impl CapabilityTable {
+    fn human_name(&self) -> &'static str {
+        match self {
+            CapabilityTable::Events => "Events",
+            CapabilityTable::EventTemplates => "Event Templates",
+            CapabilityTable::Schedules => "Schedules",
+            CapabilityTable::AccessLevels => "Access Levels",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CapabilityAction {
+    View,
+    Create,
+    Edit,
+    Delete,
+}
+
+impl CapabilityAction {
+    fn human_name(&self) -> &'static str {
+        match self {
+            CapabilityAction::View => "View",
+            CapabilityAction::Create => "Create",
+            CapabilityAction::Edit => "Edit",
+            CapabilityAction::Delete => "Delete",
+        }
+    }
+}
+
+/// Reason, why an action is unavailable
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Denied {
+    MissingPermission(CapabilityTable, CapabilityAction),
+    AccessLevelTooLow,
+    SharingNotAllowed,
+}
+
+impl Denied {
+    pub fn explain(&self) -> String {
+        match self {
+            Denied::MissingPermission(table, action) => format!(
+                "Missing permission: {} {}",
+                action.human_name(),
+                table.human_name()
+            ),
+            Denied::AccessLevelTooLow => "Item access level is above yours".to_owned(),
+            Denied::SharingNotAllowed => "Missing permission: Manage Sharing".to_owned(),
+        }
+    }
+}
+
+pub type Capability = Result<(), Denied>;
+
+/// What the current user can do with the data of some user
+#[derive(Debug, Clone, Copy)]
+pub struct Capabilities {
+    permissions: Permissions,
+}
+
+impl Capabilities {
+    pub fn new(permissions: Permissions) -> Self {
+        Self { permissions }
+    }
+
+    /// Same visibility, but no changes
+    pub fn read_only(self) -> Self {
+        let read_only = |table: TablePermissions| TablePermissions {
+            view: table.view,
+            edit: false,
+            create: false,
+            delete: false,
+        };
+        Self {
+            permissions: Permissions {
+                access_levels: read_only(self.permissions.access_levels),
+                events: read_only(self.permissions.events),
+                event_templates: read_only(self.permissions.event_templates),
+                schedules: read_only(self.permissions.schedules),
+                allow_share: false,
+                ..self.permissions
+            },
+        }
+    }
+
+    pub fn get_access_level(&self) -> i32 {
+        self.permissions.access_level
+    }
+
+    fn get_table_permissions(&self, table: CapabilityTable) -> TablePermissions {
+        match table {
+            CapabilityTable::Events => self.permissions.events,
+            CapabilityTable::EventTemplates => self.permissions.event_templates,
+            CapabilityTable::Schedules => self.permissions.schedules,
+            CapabilityTable::AccessLevels => self.permissions.access_levels,
+        }
+    }
+
+    pub fn table(&self, table: CapabilityTable, action: CapabilityAction) -> Capability {
+        let permissions = self.get_table_permissions(table);
+        let allowed = match action {
+            CapabilityAction::View => permissions.view,
+            CapabilityAction::Create => permissions.create,
+            CapabilityAction::Edit => permissions.edit,
+            CapabilityAction::Delete => permissions.delete,
+        };
+        if allowed {
+            Ok(())
+        } else {
+            Err(Denied::MissingPermission(table, action))
+        }
+    }
+
+    /// Items above our access level can't be changed, even with the table permission
+    pub fn item(
+        &self,
+        table: CapabilityTable,
+        action: CapabilityAction,
+        item_access_level: i32,
+    ) -> Capability {
+        self.table(table, action)?;
+        if item_access_level > self.permissions.access_level {
+            Err(Denied::AccessLevelTooLow)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Controls for changing items are only shown to those, who can change some of them
+    pub fn can_change_any(&self, table: CapabilityTable) -> bool {
+        self.table(table, CapabilityAction::Edit).is_ok()
+            || self.table(table, CapabilityAction::Delete).is_ok()
+    }
+
+    pub fn share(&self) -> Capability {
+        if self.permissions.allow_share {
+            Ok(())
+        } else {
+            Err(Denied::SharingNotAllowed)
+        }
+    }
+}
+
+impl State {
+    /// Unknown users have no capabilities
+    pub fn get_capabilities(&self, user_id: TableId) -> Capabilities {
+        Capabilities::new(self.get_user_permissions(user_id))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::permission_presets::PermissionFlags;
+
+    const LEVEL: i32 = 10;
+
+    const TABLES: [CapabilityTable; 4] = [
+        CapabilityTable::Events,
+        CapabilityTable::EventTemplates,
+        CapabilityTable::Schedules,
+        CapabilityTable::AccessLevels,
+    ];
+    const ACTIONS: [CapabilityAction; 4] = [
+        CapabilityAction::View,
+        CapabilityAction::Create,
+        CapabilityAction::Edit,
+        CapabilityAction::Delete,
+    ];
+
+    /// Flag set and whether it allows each action on each table, in TABLES x ACTIONS order
+    fn presets() -> [(&'static str, PermissionFlags, [[bool; 4]; 4], bool); 3] {
+        const NONE: [bool; 4] = [false; 4];
+        const VIEW: [bool; 4] = [true, false, false, false];
+        const ALL: [bool; 4] = [true; 4];
+        [
+            (
+                "Viewer",
+                PermissionFlags::VIEWER,
+                [VIEW, VIEW, VIEW, NONE],
+                false,
+            ),
+            (
+                "Assistant",
+                PermissionFlags::ASSISTANT,
+                [ALL, ALL, ALL, NONE],
+                false,
+            ),
+            (
+                "Co-owner",
+                PermissionFlags::CO_OWNER,
+                [ALL, ALL, ALL, ALL],
+                true,
+            ),
+        ]
+    }
+
+    fn capabilities(flags: PermissionFlags) -> Capabilities {
+        Capabilities::new(flags.make_permissions(LEVEL))
+    }
+
+    #[test]
+    fn table_follows_flags() {
+        presets().into_iter().for_each(|(name, flags, allowed, _)| {
+            let capabilities = capabilities(flags);
+            TABLES.iter().zip(allowed).for_each(|(&table, allowed)| {
+                ACTIONS.iter().zip(allowed).for_each(|(&action, allowed)| {
+                    let expected = if allowed {
+                        Ok(())
+                    } else {
+                        Err(Denied::MissingPermission(table, action))
+                    };
+                    assert_eq!(
+                        capabilities.table(table, action),
+                        expected,
+                        "{name}: {action:?} {table:?}"
+                    );
+                });
+            });
+        });
+    }
+
+    #[test]
+    fn item_checks_table_then_level() {
+        presets().into_iter().for_each(|(name, flags, allowed, _)| {
+            let capabilities = capabilities(flags);
+            TABLES.iter().zip(allowed).for_each(|(&table, allowed)| {
+                ACTIONS.iter().zip(allowed).for_each(|(&action, allowed)| {
+                    let missing = Err(Denied::MissingPermission(table, action));
+                    let (own_level, above_level) = if allowed {
+                        (Ok(()), Err(Denied::AccessLevelTooLow))
+                    } else {
+                        (missing, missing)
+                    };
+                    assert_eq!(
+                        capabilities.item(table, action, LEVEL),
+                        own_level,
+                        "{name}: {action:?} {table:?} on own level"
+                    );
+                    assert_eq!(
+                        capabilities.item(table, action, LEVEL + 1),
+                        above_level,
+                        "{name}: {action:?} {table:?} above own level"
+                    );
+                });
+            });
+        });
+    }
+
+    #[test]
+    fn share_follows_flags() {
+        presets()
+            .into_iter()
+            .for_each(|(name, flags, _, can_share)| {
+                let expected = if can_share {
+                    Ok(())
+                } else {
+                    Err(Denied::SharingNotAllowed)
+                };
+                assert_eq!(capabilities(flags).share(), expected, "{name}");
+            });
+    }
+
+    #[test]
+    fn can_change_any_needs_edit_or_delete() {
+        presets().into_iter().for_each(|(name, flags, allowed, _)| {
+            let capabilities = capabilities(flags);
+            TABLES.iter().zip(allowed).for_each(|(&table, allowed)| {
+                assert_eq!(
+                    capabilities.can_change_any(table),
+                    allowed[2] || allowed[3],
+                    "{name}: {table:?}"
+                );
+            });
+        });
+    }
+
+    #[test]
+    fn read_only_keeps_only_view() {
+        presets().into_iter().for_each(|(name, flags, allowed, _)| {
+            let capabilities = capabilities(flags).read_only();
+            TABLES.iter().zip(allowed).for_each(|(&table, allowed)| {
+                assert_eq!(
+                    capabilities.table(table, CapabilityAction::View).is_ok(),
+                    allowed[0],
+                    "{name}: {table:?}"
+                );
+                assert!(!capabilities.can_change_any(table), "{name}: {table:?}");
+            });
+            assert_eq!(capabilities.share(), Err(Denied::SharingNotAllowed));
+        });
+    }
+}
@@ -1,15 +1,27 @@
-use calendar_lib::api::{auth::*, user_state};
+use calendar_lib::api::{
+    auth::{
+        types::{AccessLevel, AccessLevelChange},
+        *,
+    },
+    event_templates::types::EventTemplate,
+    events::types::Event,
+    permissions::types::GrantedPermission,
+    schedules::types::Schedule,
+    user_state,
+    utils::{EmptyResponse, User},
+};
+use serde::{Deserialize, Serialize};
 
-use crate::{db::aliases::UserUtils, tables::TableId};
+use crate::{
+    db::aliases::UserUtils,
+    tables::{
+        plan_exceptions::PlanException, plan_overrides::PlanOverride,
+        schedule_policies::SchedulePolicy, share_validities::ShareValidity, TableDelta, TableId,
+    },
+};
 
 use super::{main_state::State, request::*};
 
-/* TODO:
-    admin requests:
-        load_user_memory_usage
-        user_roles
-*/
-
 #[derive(Clone, Copy)]
 pub struct LogoutRequest {}
 impl RequestType for LogoutRequest {
@@ -79,7 +91,9 @@ impl StateRequestType for LoginByKeyRequest {
         state.on_logged_in(response.user, response.jwt);
     }
 
-    fn push_bad_to_state(response: Self::BadResponse, info: Self::Info, state: &mut State) {}
+    fn push_bad_to_state(response: Self::BadResponse, info: Self::Info, state: &mut State) {
+        state.discard_stale_state();
+    }
 }
 
 #[derive(Clone, Copy)]
@@ -120,7 +134,17 @@ impl StateRequestType for LoadStateRequest {
     fn push_to_state(response: Self::Response, info: Self::Info, state: &mut State) {
         let user_id = info;
         state.get_user_state_mut(user_id).replace_data(response);
+        state.get_user_state(user_id).share_validities.load_all();
+        state.get_user_state(user_id).plan_overrides.load_all();
+        state.get_user_state(user_id).plan_exceptions.load_all();
+        state.get_user_state(user_id).schedule_policies.load_all();
+        if state.get_user_permissions(user_id).allow_share {
+            state.get_user_state(user_id).share_links.load_all();
+        }
         state.populate_granted_user_states(user_id);
+        if user_id == state.me.id {
+            state.mark_fresh();
+        }
         state.clear_events(user_id);
     }
 
@@ -140,6 +164,96 @@ impl StateRequestType for LoadStateRequest {
     }
 }
 
+// TODO: move to lib
+/// Versions of UserState tables, that are already known to the client
+#[derive(Debug, Clone, Copy, Default, Serialize)]
+pub struct TableVersions {
+    pub users: Option<u64>,
+    pub access_levels: Option<u64>,
+    pub events: Option<u64>,
+    pub event_templates: Option<u64>,
+    pub schedules: Option<u64>,
+    pub granted_permissions: Option<u64>,
+    pub share_validities: Option<u64>,
+    pub plan_overrides: Option<u64>,
+    pub plan_exceptions: Option<u64>,
+    pub schedule_policies: Option<u64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LoadStateChangesBody {
+    pub user_id: TableId,
+    pub versions: TableVersions,
+}
+
+#[derive(Deserialize)]
+pub struct StateChanges {
+    pub users: TableDelta<User>,
+    pub access_levels: TableDelta<AccessLevel>,
+    pub events: TableDelta<Event>,
+    pub event_templates: TableDelta<EventTemplate>,
+    pub schedules: TableDelta<Schedule>,
+    pub granted_permissions: TableDelta<GrantedPermission>,
+    pub share_validities: TableDelta<ShareValidity>,
+    pub plan_overrides: TableDelta<PlanOverride>,
+    pub plan_exceptions: TableDelta<PlanException>,
+    pub schedule_policies: TableDelta<SchedulePolicy>,
+}
+
+#[derive(Debug, Clone)]
+pub struct LoadStateChangesInfo {
+    pub user_id: TableId,
+    pub versions: TableVersions,
+}
+
+#[derive(Clone, Copy)]
+pub struct LoadStateChangesRequest {}
+impl RequestType for LoadStateChangesRequest {
+    const URL: &'static str = "user_state/load_changes";
+    const IS_AUTHORIZED: bool = true;
+    const METHOD: reqwest::Method = reqwest::Method::POST;
+
+    type Query = ();
+    type Body = LoadStateChangesBody;
+    type Response = StateChanges;
+    type BadResponse = user_state::load::BadRequestResponse;
+
+    type Info = LoadStateChangesInfo;
+}
+impl StateRequestType for LoadStateChangesRequest {
+    fn push_to_state(response: Self::Response, info: Self::Info, state: &mut State) {
+        let user_id = info.user_id;
+        // State could have been dropped while the request was in flight (e.g. logout)
+        if state.try_get_user_state(user_id).is_none() {
+            return;
+        }
+        state
+            .get_user_state_mut(user_id)
+            .push_changes(info.versions, response);
+        state.populate_granted_user_states(user_id);
+        state.clear_events(user_id);
+    }
+
+    fn push_bad_to_state(response: Self::BadResponse, info: Self::Info, state: &mut State) {
+        LoadStateRequest::push_bad_to_state(response, info.user_id, state);
+    }
+}
+
+// TODO: move to lib
+/// Items of a deleted level are moved to another level, instead of being dropped
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct AccessLevelReassign {
+    pub old_level: i32,
+    pub new_level: i32,
+}
+
+// TODO: move to lib
+#[derive(Debug, Clone, Serialize)]
+pub struct ChangeAccessLevelsBody {
+    pub array: Vec<AccessLevelChange>,
+    pub reassign: Vec<AccessLevelReassign>,
+}
+
 #[derive(Clone, Copy)]
 pub struct ChangeAccessLevelsRequest {}
 impl RequestType for ChangeAccessLevelsRequest {
@@ -148,7 +262,7 @@ impl RequestType for ChangeAccessLevelsRequest {
     const METHOD: reqwest::Method = change_access_levels::METHOD;
 
     type Query = change_access_levels::Args;
-    type Body = change_access_levels::Body;
+    type Body = ChangeAccessLevelsBody;
     type Response = change_access_levels::Response;
     type BadResponse = change_access_levels::BadRequestResponse;
 
@@ -166,3 +280,140 @@ impl StateRequestType for ChangeAccessLevelsRequest {
         state.get_user_state(user_id).load_state();
     }
 }
+
+// TODO: move to lib
+/// Data of the calendar, that is visible by a share link.
+/// Server only sends items up to the link access level, with hidden event details already removed
+#[derive(Deserialize)]
+pub struct SharedCalendar {
+    pub user: User,
+    pub events: Vec<Event>,
+    pub event_templates: Vec<EventTemplate>,
+    pub schedules: Vec<Schedule>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LoadSharedCalendarArgs {
+    pub token: String,
+}
+
+#[derive(Clone, Copy)]
+pub struct LoadSharedCalendarRequest {}
+impl RequestType for LoadSharedCalendarRequest {
+    const URL: &'static str = "share_links/view";
+    const IS_AUTHORIZED: bool = false;
+    const METHOD: reqwest::Method = reqwest::Method::GET;
+
+    type Query = LoadSharedCalendarArgs;
+    type Response = SharedCalendar;
+
+    type Info = ();
+}
+#[allow(unused_variables)]
+impl StateRequestType for LoadSharedCalendarRequest {
+    fn push_to_state(response: Self::Response, info: Self::Info, state: &mut State) {
+        state.open_public_view(response);
+    }
+
+    fn push_bad_to_state(response: Self::BadResponse, info: Self::Info, state: &mut State) {
+        println!("Share link is invalid or revoked");
+    }
+}
+
+// TODO: move to lib
+/// Replaces all overrides of the schedule event plans
+#[derive(Debug, Clone, Serialize)]
+pub struct SetPlanOverridesBody {
+    pub schedule_id: TableId,
+    pub overrides: Vec<PlanOverride>,
+}
+
+#[derive(Clone, Copy)]
+pub struct SetPlanOverridesRequest {}
+impl RequestType for SetPlanOverridesRequest {
+    const URL: &'static str = "schedules/set_plan_overrides";
+    const IS_AUTHORIZED: bool = true;
+    const METHOD: reqwest::Method = reqwest::Method::POST;
+
+    type Query = ();
+    type Body = SetPlanOverridesBody;
+    type Response = EmptyResponse;
+
+    /// user_id
+    type Info = TableId;
+}
+#[allow(unused_variables)]
+impl StateRequestType for SetPlanOverridesRequest {
+    fn push_to_state(response: Self::Response, info: Self::Info, state: &mut State) {
+        let user_id = info;
+        state.get_user_state(user_id).plan_overrides.load_all();
+    }
+
+    fn push_bad_to_state(response: Self::BadResponse, info: Self::Info, state: &mut State) {
+        println!("Failed setting plan overrides");
+    }
+}
+
+#[derive(Clone, Copy)]
+pub struct SetSchedulePolicyRequest {}
+impl RequestType for SetSchedulePolicyRequest {
+    const URL: &'static str = "schedules/set_policy";
+    const IS_AUTHORIZED: bool = true;
+    const METHOD: reqwest::Method = reqwest::Method::POST;
+
+    type Query = ();
+    type Body = SchedulePolicy;
+    type Response = EmptyResponse;
+
+    /// user_id
+    type Info = TableId;
+}
+#[allow(unused_variables)]
+impl StateRequestType for SetSchedulePolicyRequest {
+    fn push_to_state(response: Self::Response, info: Self::Info, state: &mut State) {
+        let user_id = info;
+        state.get_user_state(user_id).schedule_policies.load_all();
+    }
+
+    fn push_bad_to_state(response: Self::BadResponse, info: Self::Info, state: &mut State) {
+        println!("Failed setting schedule policy");
+    }
+}
+
+// TODO: move to lib
+/// UpdateSchedule can't change the template
+#[derive(Debug, Clone, Serialize)]
+pub struct SetScheduleTemplateBody {
+    pub schedule_id: TableId,
+    pub template_id: TableId,
+}
+
+#[derive(Clone, Copy)]
+pub struct SetScheduleTemplateRequest {}
+impl RequestType for SetScheduleTemplateRequest {
+    const URL: &'static str = "schedules/set_template";
+    const IS_AUTHORIZED: bool = true;
+    const METHOD: reqwest::Method = reqwest::Method::POST;
+
+    type Query = ();
+    type Body = SetScheduleTemplateBody;
+    type Response = EmptyResponse;
+
+    /// user_id, schedule_id
+    type Info = (TableId, TableId);
+}
+#[allow(unused_variables)]
+impl StateRequestType for SetScheduleTemplateRequest {
+    fn push_to_state(response: Self::Response, info: Self::Info, state: &mut State) {
+        let (user_id, schedule_id) = info;
+        state.clear_events(user_id);
+        state
+            .get_user_state(user_id)
+            .schedules
+            .load_by_id(schedule_id);
+    }
+
+    fn push_bad_to_state(response: Self::BadResponse, info: Self::Info, state: &mut State) {
+        println!("Failed changing schedule template");
+    }
+}
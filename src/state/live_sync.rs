@@ -0,0 +1,94 @@
+use calendar_lib::api::{
+    event_templates::types::EventTemplate, events::types::Event,
+    permissions::types::GrantedPermission, schedules::types::Schedule,
+};
+
+use crate::db::push_channel::{ChangeKind, ChangedTable, PushChannel, TableChange};
+
+use super::{main_state::State, table_requests::TableItemDelete};
+
+/// While push channel is unavailable, changes are picked up by the refresher
+pub struct LiveSync {
+    push_channel: Option<PushChannel>,
+}
+
+impl LiveSync {
+    pub(super) fn new() -> Self {
+        Self { push_channel: None }
+    }
+
+    pub fn is_connected(&self) -> bool {
+        self.push_channel
+            .as_ref()
+            .is_some_and(|push_channel| push_channel.is_connected())
+    }
+}
+
+impl State {
+    pub(super) fn start_live_sync(&mut self) {
+        self.stop_live_sync();
+        self.live_sync.push_channel = Some(PushChannel::connect());
+    }
+
+    pub(super) fn stop_live_sync(&mut self) {
+        if let Some(push_channel) = self.live_sync.push_channel.take() {
+            push_channel.close();
+        }
+    }
+
+    pub(super) fn update_live_sync(&mut self) {
+        let changes = match self.live_sync.push_channel.as_ref() {
+            Some(push_channel) => push_channel.pull(),
+            None => return,
+        };
+        changes
+            .into_iter()
+            .for_each(|change| self.apply_table_change(change));
+    }
+
+    fn apply_table_change(&mut self, change: TableChange) {
+        let TableChange {
+            user_id,
+            table,
+            id,
+            kind,
+        } = change;
+
+        // Changes of states, that we don't have, are of no interest
+        if self.try_get_user_state(user_id).is_none() {
+            return;
+        }
+
+        match (table, kind) {
+            (ChangedTable::AccessLevels, _) => {
+                self.get_user_state(user_id).access_levels.load_all();
+            }
+            (ChangedTable::Events, ChangeKind::Upserted) => {
+                self.get_user_state(user_id).events.load_by_id(id);
+            }
+            (ChangedTable::Events, ChangeKind::Deleted) => {
+                Event::push_from_delete(self, user_id, id);
+            }
+            (ChangedTable::EventTemplates, ChangeKind::Upserted) => {
+                self.get_user_state(user_id).event_templates.load_by_id(id);
+            }
+            (ChangedTable::EventTemplates, ChangeKind::Deleted) => {
+                EventTemplate::push_from_delete(self, user_id, id);
+            }
+            (ChangedTable::Schedules, ChangeKind::Upserted) => {
+                self.get_user_state(user_id).schedules.load_by_id(id);
+            }
+            (ChangedTable::Schedules, ChangeKind::Deleted) => {
+                Schedule::push_from_delete(self, user_id, id);
+            }
+            (ChangedTable::GrantedPermissions, ChangeKind::Upserted) => {
+                self.get_user_state(user_id)
+                    .granted_permissions
+                    .load_by_id(id);
+            }
+            (ChangedTable::GrantedPermissions, ChangeKind::Deleted) => {
+                GrantedPermission::push_from_delete(self, user_id, id);
+            }
+        }
+    }
+}
@@ -3,6 +3,7 @@ use std::{cell::Ref, collections::HashMap};
 use calendar_lib::api::{
     events::types::{Event, EventVisibility},
     permissions::types::Permissions,
+    schedules::types::Schedule,
     utils::{TableId, User},
 };
 
@@ -10,15 +11,20 @@ use chrono::{Datelike, NaiveDate, NaiveDateTime};
 use itertools::Itertools;
 
 use crate::{
+    config::Config,
     db::{
         aliases::UserUtils,
         db_connector::{DbConnector, DbConnectorData},
         request::RequestIdentifier,
     },
-    tables::DbTable,
+    tables::{plan_exceptions::PlanExceptionKind, DbTable},
 };
 
-use super::{request::RequestType, shared_state::GrantedUserState, state_updater::StateUpdater};
+use super::{
+    audit_log::AuditLog, backup::BackupRestore, batch::EventBatch, live_sync::LiveSync,
+    materializer::Materializer, refresher::Refresher, request::RequestType,
+    shared_state::GrantedUserState, state_cache::CacheStatus, state_updater::StateUpdater,
+};
 
 pub use super::{admin_state::AdminState, user_state::UserState};
 
@@ -31,13 +37,24 @@ pub struct State {
     pub granted_states: Vec<GrantedUserState>,
     pub admin_state: AdminState,
 
+    pub(super) live_sync: LiveSync,
+    pub(super) refresher: Refresher,
+    pub(super) cache_status: CacheStatus,
+    /// Owner of the calendar, opened by a share link
+    pub(super) public_user_id: Option<TableId>,
+    /// Receives responses for users, whose state no longer exists
+    pub(super) detached_state: UserState,
+    pub(super) batch: Option<EventBatch>,
+    pub(super) restore: Option<BackupRestore>,
+    pub(super) materializer: Materializer,
+
     /// Has both server and phantom events
     pub(super) events_per_day: HashMap<NaiveDate, Vec<Event>>,
     pub(super) events_per_day_user_id: TableId,
 }
 
 impl State {
-    pub fn new() -> Self {
+    pub fn new(config: &Config) -> Self {
         State {
             db_connector: DbConnector::new(),
             me: User::default(),
@@ -45,14 +62,22 @@ impl State {
             granted_states: Vec::new(),
             admin_state: AdminState::new(),
 
+            live_sync: LiveSync::new(),
+            refresher: Refresher::new(config),
+            cache_status: CacheStatus::new(),
+            public_user_id: None,
+            detached_state: UserState::new(-1),
+            batch: None,
+            restore: None,
+            materializer: Materializer::new(),
+
             events_per_day: HashMap::new(),
             events_per_day_user_id: -1,
         }
     }
 
     pub fn any_pending_requests(&self) -> bool {
-        // This is not quite correct
-        StateUpdater::get().any_checkers()
+        self.db_connector.any_pending_requests() || StateUpdater::get().any_checkers()
     }
 
     pub fn try_get_me(&self) -> Option<&User> {
@@ -68,17 +93,18 @@ impl State {
     }
 
     /// Can return this user state instead of requested
+    /// Unknown users get the detached state, so their data is never mixed with ours
     pub fn get_user_state(&self, user_id: i32) -> &UserState {
         match self.try_get_user_state(user_id) {
             Some(user_state) => user_state,
             None => {
                 println!("get_user_state state {} not found", user_id);
-                &self.user_state
+                &self.detached_state
             }
         }
     }
 
-    /// Can return this user state instead of requested
+    /// Changes to unknown users are made on a detached state, so they are never shown
     pub fn get_user_state_mut<'a>(&'a mut self, user_id: i32) -> &'a mut UserState {
         if self.me.is_admin() {
             self.admin_state
@@ -92,10 +118,18 @@ impl State {
             if user_id == self.me.id {
                 &mut self.user_state
             } else {
-                self.granted_states
-                    .iter_mut()
-                    .find_map(|state| (state.user.id == user_id).then_some(&mut state.state))
-                    .unwrap_or(&mut self.user_state)
+                match self
+                    .granted_states
+                    .iter()
+                    .position(|state| state.user.id == user_id)
+                {
+                    Some(index) => &mut self.granted_states[index].state,
+                    None => {
+                        println!("get_user_state_mut state {} not found", user_id);
+                        self.detached_state = UserState::new(user_id);
+                        &mut self.detached_state
+                    }
+                }
             }
         }
     }
@@ -140,6 +174,19 @@ impl State {
             .and_then(|r| r.ok())
     }
 
+    /// Whether the request succeeded, once it is completed.
+    /// Transport errors and unexpected responses are failures too
+    pub fn get_request_result<T: RequestType>(
+        &self,
+        identifier: &RequestIdentifier<T>,
+    ) -> Option<bool> {
+        self.db_connector
+            .convert_response::<T::Response, T::BadResponse>(identifier.id);
+        self.db_connector
+            .get_response::<T::Response, T::BadResponse>(identifier.id)
+            .map(|response| matches!(response, Ok(Ok(_))))
+    }
+
     pub fn find_response_by_type<'a, T: RequestType>(
         &'a self,
     ) -> Option<Result<Ref<'a, T::Response>, Ref<'a, T::BadResponse>>> {
@@ -147,18 +194,14 @@ impl State {
             .find_response_by_type::<T::Response, T::BadResponse>()
     }
 
-    pub fn take_response<T: RequestType>(
-        &mut self,
-        identifier: &RequestIdentifier<T>,
-    ) -> Option<Result<Box<T::Response>, Box<T::BadResponse>>> {
-        self.db_connector
-            .convert_response::<T::Response, T::BadResponse>(identifier.id);
-        self.db_connector
-            .take_response::<T::Response, T::BadResponse>(identifier.id)
-            .and_then(|r| r.ok())
-    }
-
     pub fn update(&mut self) {
+        self.update_live_sync();
+        self.update_refresher();
+        // Before the executors take the responses of tracked requests
+        self.update_undo_history();
+        self.update_event_batch();
+        self.update_backup_restore();
+        self.update_materializer();
         StateUpdater::get().update(self);
         self.db_connector.pull_responses();
         self.db_connector.send_requests();
@@ -168,6 +211,7 @@ impl State {
 impl State {
     pub fn clear_events(&mut self, _user_id: TableId) {
         self.events_per_day.clear();
+        self.mark_cache_outdated();
     }
 
     pub(super) fn generate_phantom_events(
@@ -178,55 +222,96 @@ impl State {
     ) -> Vec<Event> {
         let user_state = self.get_user_state(user_id);
 
-        let event_exists = |plan_id: i32| {
-            user_state
-                .events
-                .get_table()
-                .get()
-                .iter()
-                .any(|e| e.plan_id == Some(plan_id) && e.start.date() == date)
+        let is_active = |schedule: &Schedule, date: NaiveDate| {
+            schedule.first_day <= date
+                && (schedule.last_day.is_none()
+                    || schedule.last_day.is_some_and(|last_day| date <= last_day))
+        };
+        let make_event = |schedule: &Schedule,
+                          plan_id: TableId,
+                          start: NaiveDateTime,
+                          duration: Option<std::time::Duration>|
+         -> Option<Event> {
+            let (template, plan_override) = user_state.get_plan_template(schedule, plan_id)?;
+            let level = plan_override.access_level.unwrap_or(schedule.access_level);
+            if level > access_level {
+                return None;
+            }
+            let duration = duration
+                .or(plan_override.duration)
+                .unwrap_or(template.duration);
+            Some(Event {
+                id: -1,
+                user_id: schedule.user_id,
+                name: template.event_name.clone(),
+                description: template.event_description.clone(),
+                start,
+                end: start + chrono::Duration::from_std(duration).unwrap(),
+                access_level: level,
+                visibility: EventVisibility::HideName,
+                plan_id: Some(plan_id),
+            })
         };
 
-        user_state
+        let planned = user_state
             .schedules
             .get_table()
             .get()
             .iter()
-            .filter(|s| s.first_day <= date)
-            .filter(|s| s.last_day.is_none() || s.last_day.is_some_and(|last_day| date <= last_day))
-            .filter(|s| s.access_level <= access_level)
+            .filter(|s| is_active(s, date))
             .flat_map(|schedule| {
-                match user_state
-                    .event_templates
+                schedule
+                    .event_plans
+                    .iter()
+                    .filter(|event_plan| {
+                        event_plan.weekday == date.weekday()
+                            && !user_state.occurrence_exists(event_plan.id, date)
+                            && user_state.get_plan_exception(event_plan.id, date).is_none()
+                    })
+                    .filter_map(|event_plan| {
+                        make_event(
+                            schedule,
+                            event_plan.id,
+                            NaiveDateTime::new(date, event_plan.time),
+                            None,
+                        )
+                    })
+                    .collect::<Vec<_>>()
+            });
+
+        // Occurrences, that were moved to this date, possibly from another one
+        let moved = user_state
+            .plan_exceptions
+            .get_table()
+            .get()
+            .iter()
+            .filter_map(|exception| match exception.kind {
+                PlanExceptionKind::Change { start, duration } if start.date() == date => {
+                    Some((exception, start, duration))
+                }
+                _ => None,
+            })
+            // Matched by the exception, as another occurrence of the plan can be accepted on this date
+            .filter(|(exception, ..)| {
+                !user_state.occurrence_exists(exception.plan_id, exception.date)
+            })
+            .filter_map(|(exception, start, duration)| {
+                let schedule = user_state
+                    .schedules
                     .get_table()
                     .get()
                     .iter()
-                    .find(|template| template.id == schedule.template_id)
-                {
-                    Some(template) => schedule
-                        .event_plans
-                        .iter()
-                        .filter_map(|event_plan| {
-                            let start = NaiveDateTime::new(date, event_plan.time);
-                            (event_plan.weekday == date.weekday() && !event_exists(event_plan.id))
-                                .then(|| Event {
-                                    id: -1,
-                                    user_id: schedule.user_id,
-                                    name: template.event_name.clone(),
-                                    description: template.event_description.clone(),
-                                    start,
-                                    end: start
-                                        + chrono::Duration::from_std(template.duration).unwrap(),
-                                    access_level: schedule.access_level,
-                                    visibility: EventVisibility::HideName,
-                                    plan_id: Some(event_plan.id),
-                                })
+                    .filter(|s| is_active(s, exception.date))
+                    .find(|s| {
+                        s.event_plans.iter().any(|event_plan| {
+                            event_plan.id == exception.plan_id
+                                && event_plan.weekday == exception.date.weekday()
                         })
-                        .collect(),
-                    None => vec![],
-                }
-            })
-            .collect()
+                    })?;
+                make_event(schedule, exception.plan_id, start, duration)
+            });
+
+        planned.chain(moved).collect()
     }
 
     pub fn prepare_date(&mut self, user_id: TableId, access_level: i32, date: NaiveDate) {
@@ -279,7 +364,15 @@ impl State {
         DbConnectorData::get().push_jwt(jwt);
         self.me = user;
         self.user_state.set_user_id(self.me.id);
+        AuditLog::get().set_actor(&self.me);
         self.load_state();
+        if self.is_stale() {
+            // Granted states restored from cache are not reloaded by populate_granted_user_states
+            self.granted_states.iter().for_each(|gs| {
+                gs.state.load_state();
+            });
+        }
+        self.start_live_sync();
     }
 
     pub(super) fn populate_granted_user_states(&mut self, user_id: TableId) {
@@ -311,6 +404,7 @@ impl State {
             }
         });
 
+        let today = chrono::Local::now().naive_local().date();
         let new_given_permissions = self
             .get_user_state(user_id)
             .granted_permissions
@@ -318,6 +412,7 @@ impl State {
             .get()
             .iter()
             .filter(|gp| gp.receiver_user_id == user_id)
+            .filter(|gp| self.is_share_active(gp.id, today))
             .filter(|gp| {
                 !self
                     .granted_states
@@ -341,6 +436,9 @@ impl State {
             state.state.load_state();
         });
         self.granted_states.append(&mut new_states);
+        if user_id == self.me.id {
+            self.drop_inactive_granted_states();
+        }
         self.populate_granted_user_states_users(user_id);
     }
 
@@ -0,0 +1,259 @@
+use calendar_lib::api::utils::TableId;
+use chrono::{Datelike, Days, Duration, NaiveDate, NaiveDateTime};
+use itertools::Itertools;
+
+use crate::{
+    db::aliases::UserUtils,
+    tables::{
+        plan_exceptions::PlanExceptionKind, schedule_policies::MaterializePolicy, DbTable,
+        DbTableGetById,
+    },
+};
+
+use super::{
+    main_state::{State, UserState},
+    request::{make_request_check, RequestCheck},
+};
+
+/// Event, that was created from a schedule without the user accepting it
+#[derive(Debug, Clone)]
+pub struct MaterializedEvent {
+    pub name: String,
+    pub start: NaiveDateTime,
+}
+
+struct DueOccurrence {
+    plan_id: TableId,
+    /// Date, the occurrence was planned on
+    date: NaiveDate,
+    event: MaterializedEvent,
+}
+
+/// Periodically creates events from schedule occurrences, as the schedule policies say
+pub struct Materializer {
+    last_run: Option<NaiveDateTime>,
+    pending: Vec<(DueOccurrence, RequestCheck)>,
+    /// Reported until the user dismisses them
+    created: Vec<MaterializedEvent>,
+    failed: usize,
+}
+
+impl Materializer {
+    const INTERVAL_SECONDS: i64 = 60;
+    /// Old schedules can have a lot of passed occurrences, the rest is left for the next runs
+    const MAX_PER_RUN: usize = 50;
+
+    pub(super) fn new() -> Self {
+        Self {
+            last_run: None,
+            pending: vec![],
+            created: vec![],
+            failed: 0,
+        }
+    }
+
+    pub fn get_created(&self) -> &[MaterializedEvent] {
+        &self.created
+    }
+
+    pub fn get_failed(&self) -> usize {
+        self.failed
+    }
+
+    fn is_pending(&self, plan_id: TableId, date: NaiveDate) -> bool {
+        self.pending
+            .iter()
+            .any(|(occurrence, _)| occurrence.plan_id == plan_id && occurrence.date == date)
+    }
+}
+
+impl UserState {
+    /// Occurrence can be an event on the date it was moved to
+    pub(super) fn occurrence_exists(&self, plan_id: TableId, date: NaiveDate) -> bool {
+        let moved_date =
+            self.get_plan_exception(plan_id, date)
+                .and_then(|exception| match exception.kind {
+                    PlanExceptionKind::Change { start, .. } => Some(start.date()),
+                    PlanExceptionKind::Skip => None,
+                });
+        self.events.get_table().get().iter().any(|event| {
+            event.plan_id == Some(plan_id)
+                && (event.start.date() == date || Some(event.start.date()) == moved_date)
+        })
+    }
+
+    fn find_due_occurrences(&self, now: NaiveDateTime) -> Vec<DueOccurrence> {
+        let today = now.date();
+        self.schedules
+            .get_table()
+            .get()
+            .iter()
+            .filter_map(|schedule| {
+                let policy = self.schedule_policies.get_table().get_by_id(schedule.id)?;
+                let (first, last) = match policy.policy {
+                    MaterializePolicy::Manual => return None,
+                    MaterializePolicy::DaysAhead(days) => {
+                        (today, today.checked_add_days(Days::new(days as u64))?)
+                    }
+                    MaterializePolicy::WhenPassed => (policy.since.date(), today),
+                };
+                let first = first.max(policy.since.date()).max(schedule.first_day);
+                let last = schedule
+                    .last_day
+                    .map_or(last, |last_day| last.min(last_day));
+                Some((schedule, policy, first, last))
+            })
+            .flat_map(|(schedule, policy, first, last)| {
+                first
+                    .iter_days()
+                    .take_while(move |date| *date <= last)
+                    .flat_map(move |date| {
+                        schedule
+                            .event_plans
+                            .iter()
+                            .filter(move |plan| plan.weekday == date.weekday())
+                            .map(move |plan| (schedule, policy, plan, date))
+                    })
+            })
+            .filter_map(|(schedule, policy, plan, date)| {
+                let start = match self.get_plan_exception(plan.id, date).map(|e| e.kind) {
+                    Some(PlanExceptionKind::Skip) => return None,
+                    Some(PlanExceptionKind::Change { start, .. }) => start,
+                    None => NaiveDateTime::new(date, plan.time),
+                };
+                let is_due = policy.policy != MaterializePolicy::WhenPassed || start <= now;
+                if !is_due || start < policy.since || self.occurrence_exists(plan.id, date) {
+                    return None;
+                }
+                let (template, _) = self.get_plan_template(schedule, plan.id)?;
+                Some(DueOccurrence {
+                    plan_id: plan.id,
+                    date,
+                    event: MaterializedEvent {
+                        name: template.event_name.clone(),
+                        start,
+                    },
+                })
+            })
+            .collect()
+    }
+}
+
+impl State {
+    /// Only runs with no requests in flight, so the events table is up to date
+    /// and occurrences are not created twice
+    pub(super) fn update_materializer(&mut self) {
+        let results = self
+            .materializer
+            .pending
+            .iter()
+            .map(|(_, check)| check(self))
+            .collect_vec();
+        let pending = std::mem::take(&mut self.materializer.pending);
+        pending
+            .into_iter()
+            .zip(results)
+            .for_each(|((occurrence, check), result)| match result {
+                Some(true) => self.materializer.created.push(occurrence.event),
+                Some(false) => self.materializer.failed += 1,
+                None => self.materializer.pending.push((occurrence, check)),
+            });
+
+        if self.try_get_me().is_none()
+            || self.me.is_admin()
+            || self.is_public_view()
+            || self.is_stale()
+            || self.any_pending_requests()
+        {
+            return;
+        }
+        let now = chrono::Local::now().naive_local();
+        if self.materializer.last_run.is_some_and(|last_run| {
+            now - last_run < Duration::try_seconds(Materializer::INTERVAL_SECONDS).unwrap()
+        }) {
+            return;
+        }
+        self.materializer.last_run = Some(now);
+
+        let occurrences = self
+            .user_state
+            .find_due_occurrences(now)
+            .into_iter()
+            .filter(|occurrence| {
+                !self
+                    .materializer
+                    .is_pending(occurrence.plan_id, occurrence.date)
+            })
+            .take(Materializer::MAX_PER_RUN)
+            .collect_vec();
+        if occurrences.is_empty() {
+            return;
+        }
+
+        // Created on its own, so the user has nothing to undo or revert
+        let pending = occurrences
+            .into_iter()
+            .filter_map(|occurrence| {
+                self.user_state
+                    .make_scheduled_event(occurrence.plan_id, occurrence.date)
+                    .map(|event| {
+                        let identifier = self.user_state.events.insert_untracked(event);
+                        (occurrence, make_request_check(identifier))
+                    })
+            })
+            .collect_vec();
+        self.materializer.pending.extend(pending);
+    }
+
+    pub fn get_materializer(&self) -> &Materializer {
+        &self.materializer
+    }
+
+    pub fn dismiss_materialized(&mut self) {
+        self.materializer.created.clear();
+        self.materializer.failed = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use calendar_lib::api::events::types::Event;
+
+    use super::*;
+    use crate::{
+        config::Config,
+        db::request::RequestIdentifier,
+        state::table_requests::{StateRequestInfo, TableInsertRequest},
+    };
+
+    #[test]
+    fn failed_insert_is_not_left_pending() {
+        let mut state = State::new(&Config::local());
+        let date = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap();
+        let identifier =
+            RequestIdentifier::<TableInsertRequest<Event>>::new(1, StateRequestInfo::new(1, ()));
+        state.materializer.pending.push((
+            DueOccurrence {
+                plan_id: 1,
+                date,
+                event: MaterializedEvent {
+                    name: "Event".to_owned(),
+                    start: date.and_hms_opt(10, 0, 0).unwrap(),
+                },
+            },
+            make_request_check(identifier),
+        ));
+
+        state.update_materializer();
+        assert!(state.materializer.is_pending(1, date));
+
+        state.db_connector.complete_request(
+            1,
+            Err(reqwest::Client::new().get("not a url").build().unwrap_err()),
+        );
+        state.update_materializer();
+        assert!(!state.materializer.is_pending(1, date));
+        assert!(state.materializer.get_created().is_empty());
+        assert_eq!(state.materializer.get_failed(), 1);
+    }
+}
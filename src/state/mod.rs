@@ -1,14 +1,26 @@
+pub mod admin_requests;
 pub mod admin_state;
+pub mod audit_log;
+pub mod backup;
+pub mod batch;
+pub mod capabilities;
 pub mod custom_requests;
+pub mod live_sync;
 pub mod main_state;
+pub mod materializer;
+pub mod public_view;
+pub mod refresher;
 pub mod request;
 pub mod shared_state;
+pub mod state_cache;
 pub mod state_requests;
 pub mod state_table;
 pub mod state_table_requests;
 pub mod state_updater;
+pub mod table_import;
 pub mod table_requests;
 pub mod table_requests_impl;
+pub mod undo_history;
 pub mod user_state;
 
 pub use main_state::State;
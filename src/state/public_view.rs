@@ -0,0 +1,78 @@
+use calendar_lib::api::{
+    auth::types::AccessLevel,
+    permissions::types::{Permissions, TablePermissions},
+    utils::TableId,
+};
+
+use crate::{db::request::RequestIdentifier, tables::DbTable};
+
+use super::{
+    custom_requests::{LoadSharedCalendarArgs, LoadSharedCalendarRequest, SharedCalendar},
+    main_state::State,
+    request::make_state_request,
+    shared_state::GrantedUserState,
+};
+
+impl State {
+    pub fn load_shared_calendar(
+        &self,
+        token: String,
+    ) -> RequestIdentifier<LoadSharedCalendarRequest> {
+        make_state_request((), |connector| {
+            connector
+                .make_request::<LoadSharedCalendarRequest>()
+                .query(&LoadSharedCalendarArgs { token })
+        })
+    }
+
+    /// Shared calendar is shown as a granted state, that can only be viewed.
+    /// Everything received is shown, nothing is hidden on this side
+    pub(super) fn open_public_view(&mut self, calendar: SharedCalendar) {
+        let permissions = Permissions {
+            access_level: AccessLevel::MAX_LEVEL,
+            events: TablePermissions {
+                view: true,
+                edit: false,
+                create: false,
+                delete: false,
+            },
+            event_templates: TablePermissions {
+                view: true,
+                edit: false,
+                create: false,
+                delete: false,
+            },
+            schedules: TablePermissions {
+                view: true,
+                edit: false,
+                create: false,
+                delete: false,
+            },
+            ..Permissions::NONE
+        };
+
+        let user_id = calendar.user.id;
+        let mut gs = GrantedUserState::new(calendar.user, permissions);
+        gs.state.events.get_table_mut().replace_all(calendar.events);
+        gs.state
+            .event_templates
+            .get_table_mut()
+            .replace_all(calendar.event_templates);
+        gs.state
+            .schedules
+            .get_table_mut()
+            .replace_all(calendar.schedules);
+
+        self.granted_states = vec![gs];
+        self.public_user_id = Some(user_id);
+        self.clear_events(user_id);
+    }
+
+    pub fn is_public_view(&self) -> bool {
+        self.public_user_id.is_some()
+    }
+
+    pub fn get_public_user_id(&self) -> Option<TableId> {
+        self.public_user_id
+    }
+}
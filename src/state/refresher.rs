@@ -0,0 +1,63 @@
+use chrono::{Duration, NaiveDateTime};
+
+use crate::{config::Config, db::aliases::UserUtils};
+
+use super::main_state::State;
+
+/// While push channel is connected, changes arrive on their own, so refresh is needed less often
+const CONNECTED_INTERVAL_MULTIPLIER: i32 = 10;
+
+/// Periodically loads changes of all loaded states since their last sync
+pub struct Refresher {
+    interval: Duration,
+    last_refresh: NaiveDateTime,
+}
+
+impl Refresher {
+    pub(super) fn new(config: &Config) -> Self {
+        Self {
+            interval: Duration::try_seconds(config.refresh_interval).unwrap(),
+            last_refresh: chrono::Local::now().naive_local(),
+        }
+    }
+}
+
+impl State {
+    pub(super) fn update_refresher(&mut self) {
+        if self.try_get_me().is_none() {
+            return;
+        }
+
+        let interval = if self.live_sync.is_connected() {
+            self.refresher.interval * CONNECTED_INTERVAL_MULTIPLIER
+        } else {
+            self.refresher.interval
+        };
+        let now = chrono::Local::now().naive_local();
+        if now - self.refresher.last_refresh >= interval {
+            self.refresher.last_refresh = now;
+            self.refresh_states();
+            // Shares can expire while the app is open
+            self.drop_inactive_granted_states();
+        }
+    }
+
+    /// Only changes are loaded, so open popups keep their input
+    pub fn refresh_states(&self) {
+        if self.me.is_admin() {
+            self.admin_state.users.load_all();
+            // Admin's own state is kept with the others, once it is loaded
+            if !self.admin_state.users_data.contains_key(&self.me.id) {
+                self.admin_state.load_user_state(self.me.id);
+            }
+            self.admin_state.users_data.values().for_each(|user_state| {
+                user_state.load_changes();
+            });
+        } else {
+            self.user_state.load_changes();
+            self.granted_states.iter().for_each(|gs| {
+                gs.state.load_changes();
+            });
+        }
+    }
+}
@@ -9,6 +9,14 @@ use crate::db::{
     request::{make_request_custom, RequestIdentifier},
 };
 
+/// Returns whether the request succeeded, once it is known.
+/// Has to be checked before the state takes the response
+pub(super) type RequestCheck = Box<dyn Fn(&State) -> Option<bool> + Send>;
+
+pub(super) fn make_request_check<T: RequestType>(identifier: RequestIdentifier<T>) -> RequestCheck {
+    Box::new(move |state| state.get_request_result(&identifier))
+}
+
 pub trait StateRequestType
 where
     Self: RequestType,
@@ -16,6 +24,9 @@ where
     fn push_to_state(response: Self::Response, info: Self::Info, state: &mut State);
     #[allow(unused_variables)]
     fn push_bad_to_state(response: Self::BadResponse, info: Self::Info, state: &mut State);
+    /// Request failed to reach the server, or the response was not understood
+    #[allow(unused_variables)]
+    fn push_error_to_state(error: String, info: Self::Info, state: &mut State) {}
 }
 
 pub fn make_state_request<T, F>(info: T::Info, make_request: F) -> RequestIdentifier<T>
@@ -35,12 +46,17 @@ where
                 let identifier: RequestIdentifier<T> =
                     RequestIdentifier::new(request_id, info.clone());
                 let executor: StateExecutor = Box::new(move |state: &mut State| {
-                    let response = state.take_response(&identifier);
-                    if let Some(response) = response {
-                        match response {
-                            Ok(response) => T::push_to_state(*response, info, state),
-                            Err(response) => T::push_bad_to_state(*response, info, state),
-                        }
+                    state
+                        .db_connector
+                        .convert_response::<T::Response, T::BadResponse>(identifier.id);
+                    let response = state
+                        .db_connector
+                        .take_response::<T::Response, T::BadResponse>(identifier.id);
+                    match response {
+                        Some(Ok(Ok(response))) => T::push_to_state(*response, info, state),
+                        Some(Ok(Err(response))) => T::push_bad_to_state(*response, info, state),
+                        Some(Err(error)) => T::push_error_to_state(error, info, state),
+                        None => {}
                     }
                 });
                 Some(executor)
@@ -1,6 +1,16 @@
-use calendar_lib::api::{permissions::types::Permissions, utils::User};
+use calendar_lib::api::{
+    permissions::types::{GrantedPermission, Permissions},
+    utils::{TableId, User},
+};
+use chrono::NaiveDate;
+use itertools::Itertools;
 
-use super::main_state::UserState;
+use crate::tables::{share_validities::ShareValidity, DbTable, DbTableGetById};
+
+use super::main_state::{State, UserState};
+
+/// How many days before the end of a share its giver is reminded
+pub const SHARE_EXPIRY_REMINDER_DAYS: i64 = 3;
 
 pub struct GrantedUserState {
     pub user: User, // no roles
@@ -17,3 +27,60 @@ impl GrantedUserState {
         }
     }
 }
+
+impl State {
+    /// Shares without validity are never limited
+    pub fn get_share_validity(
+        &self,
+        user_id: TableId,
+        permission_id: TableId,
+    ) -> Option<&ShareValidity> {
+        self.try_get_user_state(user_id).and_then(|user_state| {
+            user_state
+                .share_validities
+                .get_table()
+                .get_by_id(permission_id)
+        })
+    }
+
+    /// Validities of received shares are loaded together with our own
+    pub(super) fn is_share_active(&self, permission_id: TableId, today: NaiveDate) -> bool {
+        self.get_share_validity(self.me.id, permission_id)
+            .map_or(true, |validity| validity.is_active(today))
+    }
+
+    pub(super) fn drop_inactive_granted_states(&mut self) {
+        let today = chrono::Local::now().naive_local().date();
+        let active_givers = self
+            .user_state
+            .granted_permissions
+            .get_table()
+            .get()
+            .iter()
+            .filter(|gp| gp.receiver_user_id == self.me.id)
+            .filter(|gp| self.is_share_active(gp.id, today))
+            .map(|gp| gp.giver_user_id)
+            .collect_vec();
+        self.granted_states
+            .retain(|gs| active_givers.contains(&gs.user.id));
+    }
+
+    /// Our shares, that will end soon, with days left
+    pub fn get_expiring_shares(&self) -> Vec<(&GrantedPermission, i64)> {
+        let today = chrono::Local::now().naive_local().date();
+        self.user_state
+            .granted_permissions
+            .get_table()
+            .get()
+            .iter()
+            .filter(|gp| gp.giver_user_id == self.me.id && gp.receiver_user_id != self.me.id)
+            .filter_map(|gp| {
+                self.get_share_validity(self.me.id, gp.id)
+                    .filter(|validity| validity.is_active(today))
+                    .and_then(|validity| validity.days_left(today))
+                    .filter(|&days_left| days_left <= SHARE_EXPIRY_REMINDER_DAYS)
+                    .map(|days_left| (gp, days_left))
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,220 @@
+use calendar_lib::api::{
+    auth::types::AccessLevel,
+    event_templates::types::EventTemplate,
+    events::types::Event,
+    permissions::types::{GrantedPermission, Permissions},
+    schedules::types::Schedule,
+    utils::User,
+};
+use chrono::{Duration, NaiveDateTime};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    db::aliases::UserUtils,
+    tables::{
+        plan_exceptions::PlanException, plan_overrides::PlanOverride,
+        schedule_policies::SchedulePolicy, share_validities::ShareValidity, DbTable,
+    },
+};
+
+use super::{
+    audit_log::{AuditEntry, AuditLog},
+    main_state::{State, UserState},
+    shared_state::GrantedUserState,
+    undo_history::UndoHistory,
+};
+
+/// Snapshot of the last loaded data, that is shown until fresh data arrives
+#[derive(Serialize, Deserialize)]
+pub struct CachedState {
+    pub me: User,
+    pub user_state: CachedUserState,
+    pub granted_states: Vec<CachedGrantedState>,
+    #[serde(default)]
+    pub audit_log: Vec<AuditEntry>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct CachedUserState {
+    pub users: Vec<User>,
+    pub access_levels: Vec<AccessLevel>,
+    pub events: Vec<Event>,
+    pub event_templates: Vec<EventTemplate>,
+    pub schedules: Vec<Schedule>,
+    pub granted_permissions: Vec<GrantedPermission>,
+    #[serde(default)]
+    pub share_validities: Vec<ShareValidity>,
+    #[serde(default)]
+    pub plan_overrides: Vec<PlanOverride>,
+    #[serde(default)]
+    pub plan_exceptions: Vec<PlanException>,
+    #[serde(default)]
+    pub schedule_policies: Vec<SchedulePolicy>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct CachedGrantedState {
+    pub user: User,
+    pub permissions: Permissions,
+    pub state: CachedUserState,
+}
+
+pub struct CacheStatus {
+    /// Data was restored from cache and wasn't reloaded yet
+    stale: bool,
+    /// Data has changed since the cache was made
+    outdated: bool,
+    last_write: Option<NaiveDateTime>,
+    /// Stored cache belongs to an invalid session and has to be deleted
+    discarded: bool,
+}
+
+impl CacheStatus {
+    /// Every change marks the cache outdated, so writes are limited to one per interval
+    const WRITE_INTERVAL_SECONDS: i64 = 10;
+
+    pub(super) fn new() -> Self {
+        Self {
+            stale: false,
+            outdated: false,
+            last_write: None,
+            discarded: false,
+        }
+    }
+}
+
+impl UserState {
+    fn make_cache(&self) -> CachedUserState {
+        CachedUserState {
+            users: self.users.get_table().get().clone(),
+            access_levels: self.access_levels.get_table().get().clone(),
+            events: self.events.get_table().get().clone(),
+            event_templates: self.event_templates.get_table().get().clone(),
+            schedules: self.schedules.get_table().get().clone(),
+            granted_permissions: self.granted_permissions.get_table().get().clone(),
+            share_validities: self.share_validities.get_table().get().clone(),
+            plan_overrides: self.plan_overrides.get_table().get().clone(),
+            plan_exceptions: self.plan_exceptions.get_table().get().clone(),
+            schedule_policies: self.schedule_policies.get_table().get().clone(),
+        }
+    }
+
+    fn restore_cache(&mut self, cache: CachedUserState) {
+        self.users.get_table_mut().replace_all(cache.users);
+        self.access_levels
+            .get_table_mut()
+            .replace_all(cache.access_levels);
+        self.events.get_table_mut().replace_all(cache.events);
+        self.event_templates
+            .get_table_mut()
+            .replace_all(cache.event_templates);
+        self.schedules.get_table_mut().replace_all(cache.schedules);
+        self.granted_permissions
+            .get_table_mut()
+            .replace_all(cache.granted_permissions);
+        self.share_validities
+            .get_table_mut()
+            .replace_all(cache.share_validities);
+        self.plan_overrides
+            .get_table_mut()
+            .replace_all(cache.plan_overrides);
+        self.plan_exceptions
+            .get_table_mut()
+            .replace_all(cache.plan_exceptions);
+        self.schedule_policies
+            .get_table_mut()
+            .replace_all(cache.schedule_policies);
+    }
+}
+
+impl State {
+    pub fn is_stale(&self) -> bool {
+        self.cache_status.stale
+    }
+
+    pub(super) fn mark_cache_outdated(&mut self) {
+        self.cache_status.outdated = true;
+    }
+
+    pub(super) fn mark_fresh(&mut self) {
+        self.cache_status.stale = false;
+    }
+
+    /// Returns true once per changes in the write interval, stale data is never reported
+    pub fn take_cache_outdated(&mut self) -> bool {
+        let status = &mut self.cache_status;
+        let now = chrono::Local::now().naive_local();
+        let interval = Duration::try_seconds(CacheStatus::WRITE_INTERVAL_SECONDS).unwrap();
+        if status.stale
+            || !status.outdated
+            || status
+                .last_write
+                .is_some_and(|last_write| now - last_write < interval)
+        {
+            return false;
+        }
+        status.outdated = false;
+        status.last_write = Some(now);
+        true
+    }
+
+    /// Returns true once after the cached session was found invalid
+    pub fn take_cache_discarded(&mut self) -> bool {
+        std::mem::take(&mut self.cache_status.discarded)
+    }
+
+    /// Admin data is always loaded on demand, so it is not cached
+    pub fn make_cache(&self) -> Option<CachedState> {
+        let me = self.try_get_me()?;
+        if me.is_admin() {
+            return None;
+        }
+        Some(CachedState {
+            me: me.clone(),
+            user_state: self.user_state.make_cache(),
+            granted_states: self
+                .granted_states
+                .iter()
+                .map(|gs| CachedGrantedState {
+                    user: gs.user.clone(),
+                    permissions: gs.permissions,
+                    state: gs.state.make_cache(),
+                })
+                .collect(),
+            audit_log: AuditLog::get().get_all().to_vec(),
+        })
+    }
+
+    pub fn restore_cache(&mut self, cache: CachedState) {
+        self.me = cache.me;
+        self.user_state.set_user_id(self.me.id);
+        self.user_state.restore_cache(cache.user_state);
+        AuditLog::get().set_actor(&self.me);
+        AuditLog::get().restore(cache.audit_log);
+        self.granted_states = cache
+            .granted_states
+            .into_iter()
+            .map(|cached| {
+                let mut gs = GrantedUserState::new(cached.user, cached.permissions);
+                gs.state.restore_cache(cached.state);
+                gs
+            })
+            .collect();
+        self.cache_status.stale = true;
+        self.clear_events(self.me.id);
+    }
+
+    /// Cached session turned out to be invalid
+    pub(super) fn discard_stale_state(&mut self) {
+        if self.cache_status.stale {
+            self.user_state = UserState::new(-1);
+            self.granted_states.clear();
+            self.me = User::default();
+            self.cache_status = CacheStatus::new();
+            self.cache_status.discarded = true;
+            AuditLog::get().clear();
+            UndoHistory::get().clear();
+            self.clear_events(-1);
+        }
+    }
+}
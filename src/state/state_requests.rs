@@ -6,21 +6,37 @@ use calendar_lib::api::{
 
 use crate::{
     db::{aliases::UserUtils, request::RequestIdentifier},
-    tables::TableId,
+    tables::{
+        plan_overrides::PlanOverride,
+        schedule_policies::{MaterializePolicy, SchedulePolicy},
+        TableId,
+    },
 };
 
 use super::{
+    audit_log::AuditLog,
     custom_requests::*,
     main_state::{AdminState, State, UserState},
+    materializer::Materializer,
     request::make_state_request,
+    state_cache::CacheStatus,
+    undo_history::UndoHistory,
 };
 
 impl State {
     pub fn logout(&mut self) -> RequestIdentifier<LogoutRequest> {
+        self.stop_live_sync();
         self.user_state = UserState::new(-1);
         self.granted_states.clear();
         self.admin_state = AdminState::new();
         self.me = User::default();
+        self.cache_status = CacheStatus::new();
+        self.public_user_id = None;
+        self.batch = None;
+        self.restore = None;
+        self.materializer = Materializer::new();
+        AuditLog::get().clear();
+        UndoHistory::get().clear();
         make_state_request((), |connector| {
             connector
                 .make_request::<LogoutRequest>()
@@ -88,14 +104,87 @@ impl UserState {
         })
     }
 
+    pub fn load_changes(&self) -> RequestIdentifier<LoadStateChangesRequest> {
+        let versions = self.get_versions();
+        make_state_request(
+            LoadStateChangesInfo {
+                user_id: self.user_id,
+                versions,
+            },
+            |connector| {
+                connector
+                    .make_request::<LoadStateChangesRequest>()
+                    .json(&LoadStateChangesBody {
+                        user_id: self.user_id,
+                        versions,
+                    })
+            },
+        )
+    }
+
+    /// Empty overrides are dropped
+    pub fn set_plan_overrides(
+        &self,
+        schedule_id: TableId,
+        overrides: Vec<PlanOverride>,
+    ) -> RequestIdentifier<SetPlanOverridesRequest> {
+        make_state_request(self.user_id, |connector| {
+            connector
+                .make_request::<SetPlanOverridesRequest>()
+                .json(&SetPlanOverridesBody {
+                    schedule_id,
+                    overrides: overrides
+                        .into_iter()
+                        .filter(|plan_override| !plan_override.is_empty())
+                        .collect(),
+                })
+        })
+    }
+
+    /// Policy only applies to the occurrences from now on
+    pub fn set_schedule_policy(
+        &self,
+        schedule_id: TableId,
+        policy: MaterializePolicy,
+    ) -> RequestIdentifier<SetSchedulePolicyRequest> {
+        make_state_request(self.user_id, |connector| {
+            connector
+                .make_request::<SetSchedulePolicyRequest>()
+                .json(&SchedulePolicy {
+                    schedule_id,
+                    policy,
+                    since: chrono::Local::now().naive_local(),
+                })
+        })
+    }
+
+    pub fn set_schedule_template(
+        &self,
+        schedule_id: TableId,
+        template_id: TableId,
+    ) -> RequestIdentifier<SetScheduleTemplateRequest> {
+        make_state_request((self.user_id, schedule_id), |connector| {
+            connector
+                .make_request::<SetScheduleTemplateRequest>()
+                .json(&SetScheduleTemplateBody {
+                    schedule_id,
+                    template_id,
+                })
+        })
+    }
+
     pub fn change_access_levels(
         &self,
         changes: Vec<AccessLevelChange>,
+        reassign: Vec<AccessLevelReassign>,
     ) -> RequestIdentifier<ChangeAccessLevelsRequest> {
         make_state_request(self.user_id, |connector| {
             connector
                 .make_request::<ChangeAccessLevelsRequest>()
-                .json(&change_access_levels::Body { array: changes })
+                .json(&ChangeAccessLevelsBody {
+                    array: changes,
+                    reassign,
+                })
                 .query(&change_access_levels::Args {
                     user_id: self.user_id,
                 })
@@ -106,6 +195,7 @@ impl UserState {
 impl AdminState {
     pub fn load_state(&self) {
         self.users.load_all();
+        self.load_users_info();
     }
 
     pub fn load_user_state(&self, user_id: TableId) -> RequestIdentifier<LoadStateRequest> {
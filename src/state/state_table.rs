@@ -1,22 +1,29 @@
+use std::collections::HashMap;
+
 use calendar_lib::api::utils::{DeleteByIdQuery, LoadArrayQuery, LoadByIdQuery};
 
 use crate::{
     db::request::RequestIdentifier,
-    tables::{table::Table, DbTableItem, DbTableUpdateItem, TableId},
+    tables::{table::Table, DbTableGetById, DbTableItem, DbTableUpdateItem, TableDelta, TableId},
 };
 
 use super::{
+    audit_log::{AuditAction, TableItemAudit, TrackedChange},
     request::{make_state_request, RequestType},
     table_requests::{
         StateRequestInfo, TableDeleteRequest, TableInsertRequest, TableItemDelete, TableItemInsert,
         TableItemLoadAll, TableItemLoadById, TableItemUpdate, TableLoadAllRequest,
-        TableLoadByIdRequest, TableUpdateRequest,
+        TableLoadByIdRequest, TableUpdateRequest, UpdateQuery,
     },
 };
 
 pub struct StateTable<T: DbTableItem> {
     user_id: TableId, // Propagated from UserState
     data: Table<T>,
+    /// Server version of the data, None until the first delta is received
+    version: Option<u64>,
+    /// Version each item was received at. Only items, that came with a delta, are known
+    item_versions: HashMap<TableId, u64>,
 }
 
 impl<T: DbTableItem> StateTable<T> {
@@ -24,6 +31,8 @@ impl<T: DbTableItem> StateTable<T> {
         Self {
             user_id: -1,
             data: Table::new(),
+            version: None,
+            item_versions: HashMap::new(),
         }
     }
 
@@ -39,6 +48,65 @@ impl<T: DbTableItem> StateTable<T> {
     pub fn get_table_mut(&mut self) -> &mut Table<T> {
         &mut self.data
     }
+
+    pub fn get_version(&self) -> Option<u64> {
+        self.version
+    }
+
+    /// Version, the item was at when it was received, to update it from
+    pub fn get_item_version(&self, id: TableId) -> Option<u64> {
+        self.item_versions.get(&id).copied()
+    }
+
+    /// Full data has no version, so deltas requested before it are dropped
+    pub(super) fn replace_all(&mut self, items: Vec<T>) {
+        self.data.replace_all(items);
+        self.version = None;
+        self.item_versions.clear();
+    }
+
+    /// `requested_version` is the version the delta was requested for.
+    /// Overlapping or outdated responses are dropped, the next refresh picks their changes up
+    pub(super) fn push_delta(&mut self, requested_version: Option<u64>, delta: TableDelta<T>) {
+        if requested_version != self.version {
+            println!(
+                "Dropping delta for version {requested_version:?}, table is at {:?}",
+                self.version
+            );
+            return;
+        }
+        let version = delta.version;
+        if requested_version.is_none() {
+            self.item_versions.clear();
+        }
+        delta.deleted.iter().for_each(|id| {
+            self.item_versions.remove(id);
+        });
+        delta.updated.iter().for_each(|item| {
+            self.item_versions.insert(item.get_id(), version);
+        });
+        if requested_version.is_some() {
+            self.data.apply_delta(delta);
+        } else {
+            // Server sends everything, if we didn't know any version
+            self.data.replace_all(delta.updated);
+        }
+        self.version = Some(version);
+    }
+}
+
+impl<T: TableItemAudit> StateTable<T> {
+    fn track(
+        &self,
+        action: AuditAction,
+        item_id: Option<TableId>,
+        after: Option<serde_json::Value>,
+    ) -> TrackedChange {
+        let before = item_id
+            .and_then(|id| self.data.get_by_id(id))
+            .and_then(|item| serde_json::to_value(item).ok());
+        TrackedChange::new(T::AUDIT_TABLE, action, item_id, before, after)
+    }
 }
 
 impl<T: TableItemLoadById> StateTable<T> {
@@ -64,7 +132,8 @@ impl<T: TableItemLoadAll> StateTable<T> {
 }
 
 impl<T: TableItemInsert> StateTable<T> {
-    pub fn insert(
+    /// Insert, that the client makes on its own, so it is neither audited nor undone
+    pub(super) fn insert_untracked(
         &self,
         item: <TableInsertRequest<T> as RequestType>::Body,
     ) -> RequestIdentifier<TableInsertRequest<T>>
@@ -77,62 +146,125 @@ impl<T: TableItemInsert> StateTable<T> {
                 .json(&item)
         })
     }
+}
+
+impl<T: TableItemInsert + TableItemAudit> StateTable<T> {
+    pub fn insert(
+        &self,
+        item: <TableInsertRequest<T> as RequestType>::Body,
+    ) -> RequestIdentifier<TableInsertRequest<T>>
+    where
+        T::Info: Default,
+    {
+        self.insert_with_info(item, T::Info::default())
+    }
 
     pub fn insert_with_info(
         &self,
         item: <TableInsertRequest<T> as RequestType>::Body,
         info: T::Info,
     ) -> RequestIdentifier<TableInsertRequest<T>> {
-        make_state_request(StateRequestInfo::new(self.user_id, info), |connector| {
-            connector
-                .make_request::<TableInsertRequest<T>>()
-                .json(&item)
-        })
+        let change = self.track(AuditAction::Insert, None, serde_json::to_value(&item).ok());
+        self.make_insert(item, info, change)
     }
-}
 
-impl<T: TableItemUpdate> StateTable<T> {
-    pub fn update(
+    /// Insert of a deleted item. Entries, that refer to `old_id`, are moved to the new item
+    pub(super) fn recreate(
         &self,
-        item: <TableUpdateRequest<T> as RequestType>::Body,
-    ) -> RequestIdentifier<TableUpdateRequest<T>>
+        item: <TableInsertRequest<T> as RequestType>::Body,
+        old_id: TableId,
+    ) -> RequestIdentifier<TableInsertRequest<T>>
     where
         T::Info: Default,
     {
-        let item_id = item.get_id();
+        let change = self
+            .track(AuditAction::Insert, None, serde_json::to_value(&item).ok())
+            .recreating(old_id);
+        self.make_insert(item, T::Info::default(), change)
+    }
+
+    fn make_insert(
+        &self,
+        item: <TableInsertRequest<T> as RequestType>::Body,
+        info: T::Info,
+        change: TrackedChange,
+    ) -> RequestIdentifier<TableInsertRequest<T>> {
         make_state_request(
-            StateRequestInfo::new(self.user_id, (item_id, T::Info::default())),
+            StateRequestInfo::new(self.user_id, info).with_change(change),
             |connector| {
                 connector
-                    .make_request::<TableUpdateRequest<T>>()
+                    .make_request::<TableInsertRequest<T>>()
                     .json(&item)
             },
         )
     }
+}
+
+impl<T: TableItemUpdate + TableItemAudit> StateTable<T> {
+    pub fn update(
+        &self,
+        item: <TableUpdateRequest<T> as RequestType>::Body,
+    ) -> RequestIdentifier<TableUpdateRequest<T>>
+    where
+        T::Info: Default,
+    {
+        self.update_with_info(item, T::Info::default())
+    }
+
+    /// Update, that is rejected as stale if the item changed since `base_version`
+    pub fn update_from_version(
+        &self,
+        item: <TableUpdateRequest<T> as RequestType>::Body,
+        base_version: Option<u64>,
+    ) -> RequestIdentifier<TableUpdateRequest<T>>
+    where
+        T::Info: Default,
+    {
+        self.make_update(item, T::Info::default(), UpdateQuery { base_version })
+    }
 
     pub fn update_with_info(
         &self,
         item: <TableUpdateRequest<T> as RequestType>::Body,
         info: T::Info,
+    ) -> RequestIdentifier<TableUpdateRequest<T>> {
+        self.make_update(item, info, UpdateQuery::default())
+    }
+
+    fn make_update(
+        &self,
+        item: <TableUpdateRequest<T> as RequestType>::Body,
+        info: T::Info,
+        query: UpdateQuery,
     ) -> RequestIdentifier<TableUpdateRequest<T>> {
         let item_id = item.get_id();
+        let change = self.track(
+            AuditAction::Update,
+            Some(item_id),
+            serde_json::to_value(&item).ok(),
+        );
         make_state_request(
-            StateRequestInfo::new(self.user_id, (item_id, info)),
+            StateRequestInfo::new(self.user_id, (item_id, info)).with_change(change),
             |connector| {
                 connector
                     .make_request::<TableUpdateRequest<T>>()
+                    .query(&query)
                     .json(&item)
             },
         )
     }
 }
 
-impl<T: TableItemDelete> StateTable<T> {
+impl<T: TableItemDelete + TableItemAudit> StateTable<T> {
     pub fn delete(&self, id: TableId) -> RequestIdentifier<TableDeleteRequest<T>> {
-        make_state_request(StateRequestInfo::new(self.user_id, id), |connector| {
-            connector
-                .make_request::<TableDeleteRequest<T>>()
-                .query(&DeleteByIdQuery { id })
-        })
+        let change = self.track(AuditAction::Delete, Some(id), None);
+        make_state_request(
+            StateRequestInfo::new(self.user_id, id).with_change(change),
+            |connector| {
+                connector
+                    .make_request::<TableDeleteRequest<T>>()
+                    .query(&DeleteByIdQuery { id })
+            },
+        )
     }
 }
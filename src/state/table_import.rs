@@ -0,0 +1,269 @@
+use calendar_lib::api::{
+    event_templates::types::{EventTemplate, NewEventTemplate},
+    events::types::{Event, NewEvent},
+    schedules::types::{NewSchedule, Schedule},
+};
+use itertools::Itertools;
+
+use crate::{
+    tables::{DbTable, DbTableGetById},
+    utils::parse_csv,
+};
+
+use super::{
+    audit_log::TableItemAudit, capabilities::CapabilityTable, state_table::StateTable,
+    table_requests::TableItemInsert, user_state::UserState,
+};
+
+/// How CSV cells of a column are read
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImportColumn {
+    Text,
+    /// Empty cell is None
+    OptionalText,
+    /// Numbers, lists and objects, written as JSON. Empty cell is None
+    Json,
+}
+
+pub trait TableItemImport
+where
+    Self: TableItemAudit + TableItemInsert<Info = ()>,
+{
+    const CAPABILITY_TABLE: CapabilityTable;
+    /// Fields of the new item, user_id is set by the import
+    const IMPORT_COLUMNS: &'static [(&'static str, ImportColumn)];
+
+    fn get_state_table(state: &UserState) -> &StateTable<Self>;
+    /// Checks, that the server would do
+    fn validate(item: &Self::NewItem, state: &UserState) -> Result<(), String>;
+    /// Existing item, that the new one would duplicate
+    fn is_same(&self, item: &Self::NewItem) -> bool;
+}
+
+impl TableItemImport for Event {
+    const CAPABILITY_TABLE: CapabilityTable = CapabilityTable::Events;
+    const IMPORT_COLUMNS: &'static [(&'static str, ImportColumn)] = &[
+        ("name", ImportColumn::Text),
+        ("description", ImportColumn::OptionalText),
+        ("start", ImportColumn::Text),
+        ("end", ImportColumn::Text),
+        ("access_level", ImportColumn::Json),
+        ("visibility", ImportColumn::Text),
+        ("plan_id", ImportColumn::Json),
+    ];
+
+    fn get_state_table(state: &UserState) -> &StateTable<Self> {
+        &state.events
+    }
+
+    fn validate(item: &NewEvent, _state: &UserState) -> Result<(), String> {
+        if item.name.is_empty() {
+            Err("Name cannot be empty".to_owned())
+        } else if item.end < item.start {
+            Err("Event must end after it starts".to_owned())
+        } else {
+            Ok(())
+        }
+    }
+
+    fn is_same(&self, item: &NewEvent) -> bool {
+        self.name == item.name && self.start == item.start && self.end == item.end
+    }
+}
+
+impl TableItemImport for EventTemplate {
+    const CAPABILITY_TABLE: CapabilityTable = CapabilityTable::EventTemplates;
+    const IMPORT_COLUMNS: &'static [(&'static str, ImportColumn)] = &[
+        ("name", ImportColumn::Text),
+        ("event_name", ImportColumn::Text),
+        ("event_description", ImportColumn::OptionalText),
+        ("duration", ImportColumn::Json),
+        ("access_level", ImportColumn::Json),
+    ];
+
+    fn get_state_table(state: &UserState) -> &StateTable<Self> {
+        &state.event_templates
+    }
+
+    fn validate(item: &NewEventTemplate, _state: &UserState) -> Result<(), String> {
+        if item.name.is_empty() {
+            Err("Name cannot be empty".to_owned())
+        } else if item.event_name.is_empty() {
+            Err("Event name cannot be empty".to_owned())
+        } else if item.duration.is_zero() {
+            Err("Duration cannot be zero".to_owned())
+        } else {
+            Ok(())
+        }
+    }
+
+    fn is_same(&self, item: &NewEventTemplate) -> bool {
+        self.name == item.name
+            && self.event_name == item.event_name
+            && self.duration == item.duration
+    }
+}
+
+impl TableItemImport for Schedule {
+    const CAPABILITY_TABLE: CapabilityTable = CapabilityTable::Schedules;
+    const IMPORT_COLUMNS: &'static [(&'static str, ImportColumn)] = &[
+        ("template_id", ImportColumn::Json),
+        ("name", ImportColumn::Text),
+        ("description", ImportColumn::OptionalText),
+        ("first_day", ImportColumn::Text),
+        ("last_day", ImportColumn::OptionalText),
+        ("access_level", ImportColumn::Json),
+        ("events", ImportColumn::Json),
+    ];
+
+    fn get_state_table(state: &UserState) -> &StateTable<Self> {
+        &state.schedules
+    }
+
+    fn validate(item: &NewSchedule, state: &UserState) -> Result<(), String> {
+        if item.name.is_empty() {
+            Err("Name cannot be empty".to_owned())
+        } else if item
+            .last_day
+            .is_some_and(|last_day| last_day < item.first_day)
+        {
+            Err("Last day is before the first one".to_owned())
+        } else if state
+            .event_templates
+            .get_table()
+            .get_by_id(item.template_id)
+            .is_none()
+        {
+            Err(format!("Template #{} doesn't exist", item.template_id))
+        } else {
+            Ok(())
+        }
+    }
+
+    fn is_same(&self, item: &NewSchedule) -> bool {
+        self.name == item.name
+            && self.template_id == item.template_id
+            && self.first_day == item.first_day
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImportFormat {
+    Csv,
+    Json,
+}
+
+pub enum ImportRowStatus<T: TableItemImport> {
+    New(T::NewItem),
+    Exists,
+    Invalid(String),
+}
+
+pub struct ImportRow<T: TableItemImport> {
+    /// Starting from 1, header is not counted
+    pub number: usize,
+    pub name: String,
+    pub status: ImportRowStatus<T>,
+}
+
+impl ImportColumn {
+    fn parse(&self, name: &str, cell: String) -> Result<serde_json::Value, String> {
+        match self {
+            ImportColumn::Text => Ok(cell.into()),
+            _ if cell.is_empty() => Ok(serde_json::Value::Null),
+            ImportColumn::OptionalText => Ok(cell.into()),
+            ImportColumn::Json => {
+                serde_json::from_str(&cell).map_err(|err| format!("Field {name}: {err}"))
+            }
+        }
+    }
+}
+
+/// Header row names the fields. Cells are read by the type of their column, unknown columns are skipped
+fn parse_csv_objects(
+    text: &str,
+    columns: &[(&str, ImportColumn)],
+) -> Result<Vec<Result<serde_json::Value, String>>, String> {
+    let mut rows = parse_csv(text)?.into_iter();
+    let header = rows.next().ok_or("Header row is missing".to_owned())?;
+    Ok(rows
+        .map(|row| {
+            header
+                .iter()
+                .zip(row)
+                .filter_map(|(name, cell)| {
+                    let (name, column) = columns.iter().find(|(column, _)| column == name)?;
+                    Some(
+                        column
+                            .parse(name, cell)
+                            .map(|value| (name.to_string(), value)),
+                    )
+                })
+                .collect::<Result<serde_json::Map<_, _>, _>>()
+                .map(serde_json::Value::from)
+        })
+        .collect())
+}
+
+fn parse_json_objects(text: &str) -> Result<Vec<Result<serde_json::Value, String>>, String> {
+    serde_json::from_str::<Vec<serde_json::Value>>(text)
+        .map(|objects| objects.into_iter().map(Ok).collect())
+        .map_err(|err| err.to_string())
+}
+
+impl UserState {
+    /// Dry run of the import, nothing is sent
+    pub fn check_import<T: TableItemImport>(
+        &self,
+        text: &str,
+        format: ImportFormat,
+    ) -> Result<Vec<ImportRow<T>>, String> {
+        let objects = match format {
+            ImportFormat::Csv => parse_csv_objects(text, T::IMPORT_COLUMNS)?,
+            ImportFormat::Json => parse_json_objects(text)?,
+        };
+        let existing = T::get_state_table(self).get_table().get();
+
+        Ok(objects
+            .into_iter()
+            .enumerate()
+            .map(|(i, object)| {
+                let mut object = match object {
+                    Ok(object) => object,
+                    Err(err) => {
+                        return ImportRow {
+                            number: i + 1,
+                            name: String::new(),
+                            status: ImportRowStatus::Invalid(err),
+                        }
+                    }
+                };
+                let name = object
+                    .get("name")
+                    .and_then(|name| name.as_str())
+                    .unwrap_or_default()
+                    .to_owned();
+                if let Some(object) = object.as_object_mut() {
+                    // Exports contain the id and other fields of the existing item
+                    object.retain(|key, _| T::IMPORT_COLUMNS.iter().any(|(name, _)| name == key));
+                    object.insert("user_id".to_owned(), self.user_id.into());
+                }
+                let status = match serde_json::from_value::<T::NewItem>(object) {
+                    Err(err) => ImportRowStatus::Invalid(err.to_string()),
+                    Ok(item) => match T::validate(&item, self) {
+                        Err(err) => ImportRowStatus::Invalid(err),
+                        Ok(()) if existing.iter().any(|current| current.is_same(&item)) => {
+                            ImportRowStatus::Exists
+                        }
+                        Ok(()) => ImportRowStatus::New(item),
+                    },
+                };
+                ImportRow {
+                    number: i + 1,
+                    name,
+                    status,
+                }
+            })
+            .collect_vec())
+    }
+}
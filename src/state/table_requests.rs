@@ -1,10 +1,11 @@
 use calendar_lib::api::utils::*;
-use serde::de::DeserializeOwned;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use std::{fmt::Debug, marker::PhantomData};
 
 use crate::tables::{DbTableItem, DbTableNewItem, DbTableUpdateItem};
 
 use super::{
+    audit_log::TrackedChange,
     main_state::State,
     request::{RequestType, StateRequestType},
 };
@@ -16,6 +17,8 @@ where
 {
     pub user_id: TableId,
     pub info: T,
+    /// Mutation, which is recorded once it succeeds
+    pub(super) change: Option<TrackedChange>,
 }
 
 impl<T> StateRequestInfo<T>
@@ -23,7 +26,11 @@ where
     T: 'static + Clone + Debug + Send,
 {
     pub fn new(user_id: TableId, info: T) -> Self {
-        Self { user_id, info }
+        Self {
+            user_id,
+            info,
+            change: None,
+        }
     }
     pub fn new_default(user_id: TableId) -> Self
     where
@@ -32,6 +39,26 @@ where
         Self {
             user_id,
             info: T::default(),
+            change: None,
+        }
+    }
+    pub(super) fn with_change(self, change: TrackedChange) -> Self {
+        Self {
+            change: Some(change),
+            ..self
+        }
+    }
+
+    /// `inserted_id` is the id, given to an inserted item by the server
+    fn accept_change(&mut self, inserted_id: Option<TableId>) {
+        if let Some(change) = self.change.take() {
+            change.accept(self.user_id, inserted_id);
+        }
+    }
+
+    fn reject_change(&mut self) {
+        if let Some(change) = self.change.take() {
+            change.reject();
         }
     }
 }
@@ -105,6 +132,35 @@ where
     );
 }
 
+// TODO: move to lib
+#[derive(Debug, Clone, Copy, Deserialize)]
+pub struct InsertResponse {
+    pub id: TableId,
+}
+
+// TODO: move to lib
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+pub struct UpdateQuery {
+    /// Table version the update was made against.
+    /// Server rejects the update, if the item changed after it. None updates unconditionally
+    pub base_version: Option<u64>,
+}
+
+// TODO: move to lib
+#[derive(Debug, Clone, Copy, Deserialize)]
+pub struct StaleUpdate {
+    pub current_version: u64,
+}
+
+// TODO: move to lib
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+pub enum UpdateRejection<T> {
+    /// Item was changed by someone else since the base version
+    Stale(StaleUpdate),
+    Other(T),
+}
+
 #[derive(Clone, Copy)]
 pub struct TableLoadByIdRequest<T: TableItemLoadById> {
     _data: PhantomData<T>,
@@ -171,19 +227,25 @@ impl<T: TableItemInsert> RequestType for TableInsertRequest<T> {
     const METHOD: reqwest::Method = reqwest::Method::POST;
     type Query = ();
     type Body = T::NewItem;
-    type Response = EmptyResponse;
+    type Response = InsertResponse;
     type BadResponse = T::BadResponse;
     type Info = StateRequestInfo<T::Info>;
 }
 #[allow(unused_variables)]
 impl<T: TableItemInsert> StateRequestType for TableInsertRequest<T> {
-    fn push_to_state(response: Self::Response, info: Self::Info, state: &mut State) {
+    fn push_to_state(response: Self::Response, mut info: Self::Info, state: &mut State) {
+        info.accept_change(Some(response.id));
         T::push_from_insert(state, info.user_id);
     }
 
-    fn push_bad_to_state(response: Self::BadResponse, info: Self::Info, state: &mut State) {
+    fn push_bad_to_state(response: Self::BadResponse, mut info: Self::Info, state: &mut State) {
+        info.reject_change();
         T::push_bad_from_insert(state, info.user_id, response);
     }
+
+    fn push_error_to_state(error: String, mut info: Self::Info, state: &mut State) {
+        info.reject_change();
+    }
 }
 
 #[allow(unused_variables)]
@@ -191,20 +253,32 @@ impl<T: TableItemUpdate> RequestType for TableUpdateRequest<T> {
     const URL: &'static str = T::UPDATE_PATH;
     const IS_AUTHORIZED: bool = true;
     const METHOD: reqwest::Method = reqwest::Method::PATCH;
-    type Query = ();
+    type Query = UpdateQuery;
     type Body = T::UpdItem;
     type Response = EmptyResponse;
-    type BadResponse = T::BadResponse;
+    type BadResponse = UpdateRejection<T::BadResponse>;
     type Info = StateRequestInfo<(TableId, T::Info)>;
 }
 #[allow(unused_variables)]
 impl<T: TableItemUpdate> StateRequestType for TableUpdateRequest<T> {
-    fn push_to_state(response: Self::Response, info: Self::Info, state: &mut State) {
+    fn push_to_state(response: Self::Response, mut info: Self::Info, state: &mut State) {
+        info.accept_change(None);
         T::push_from_update(state, info.user_id, info.info.0);
     }
 
-    fn push_bad_to_state(response: Self::BadResponse, info: Self::Info, state: &mut State) {
-        T::push_bad_from_update(state, info.user_id, info.info.0, response);
+    fn push_bad_to_state(response: Self::BadResponse, mut info: Self::Info, state: &mut State) {
+        info.reject_change();
+        match response {
+            // Nothing was changed, the sender reloads the item and decides what to send
+            UpdateRejection::Stale(_) => {}
+            UpdateRejection::Other(response) => {
+                T::push_bad_from_update(state, info.user_id, info.info.0, response)
+            }
+        }
+    }
+
+    fn push_error_to_state(error: String, mut info: Self::Info, state: &mut State) {
+        info.reject_change();
     }
 }
 
@@ -220,11 +294,17 @@ impl<T: TableItemDelete> RequestType for TableDeleteRequest<T> {
 }
 #[allow(unused_variables)]
 impl<T: TableItemDelete> StateRequestType for TableDeleteRequest<T> {
-    fn push_to_state(response: Self::Response, info: Self::Info, state: &mut State) {
+    fn push_to_state(response: Self::Response, mut info: Self::Info, state: &mut State) {
+        info.accept_change(None);
         T::push_from_delete(state, info.user_id, info.info)
     }
 
-    fn push_bad_to_state(response: Self::BadResponse, info: Self::Info, state: &mut State) {
+    fn push_bad_to_state(response: Self::BadResponse, mut info: Self::Info, state: &mut State) {
+        info.reject_change();
         T::push_bad_from_delete(state, info.user_id, info.info, response);
     }
+
+    fn push_error_to_state(error: String, mut info: Self::Info, state: &mut State) {
+        info.reject_change();
+    }
 }
@@ -21,7 +21,16 @@ use calendar_lib::api::{
     utils::*,
 };
 
-use crate::db::aliases::UserUtils;
+use crate::{
+    db::aliases::UserUtils,
+    tables::{
+        plan_exceptions::{NewPlanException, PlanException, UpdatePlanException},
+        plan_overrides::PlanOverride,
+        schedule_policies::SchedulePolicy,
+        share_links::{NewShareLink, ShareLink},
+        share_validities::{ShareValidity, ShareWithValidity},
+    },
+};
 
 use super::{
     table_requests::{
@@ -496,7 +505,7 @@ impl TableItemLoadAll for GrantedPermission {
 
 #[allow(unused_variables)]
 impl TableItemInsert for GrantedPermission {
-    type NewItem = NewGrantedPermission;
+    type NewItem = ShareWithValidity<NewGrantedPermission>;
 
     const INSERT_PATH: &'static str = permissions::insert::PATH;
 
@@ -506,10 +515,9 @@ impl TableItemInsert for GrantedPermission {
 
     fn push_from_insert(state: &mut State, user_id: TableId) {
         state.clear_events(user_id);
-        state
-            .get_user_state_mut(user_id)
-            .granted_permissions
-            .default_push_from_insert();
+        let user_state = state.get_user_state_mut(user_id);
+        user_state.granted_permissions.default_push_from_insert();
+        user_state.share_validities.load_all();
     }
 
     fn push_bad_from_insert(state: &mut State, user_id: TableId, response: Self::BadResponse) {
@@ -523,7 +531,7 @@ impl TableItemInsert for GrantedPermission {
 
 #[allow(unused_variables)]
 impl TableItemUpdate for GrantedPermission {
-    type UpdItem = UpdateGrantedPermission;
+    type UpdItem = ShareWithValidity<UpdateGrantedPermission>;
 
     const UPDATE_PATH: &'static str = permissions::update::PATH;
 
@@ -533,10 +541,9 @@ impl TableItemUpdate for GrantedPermission {
 
     fn push_from_update(state: &mut State, user_id: TableId, id: TableId) {
         state.clear_events(user_id);
-        state
-            .get_user_state_mut(user_id)
-            .granted_permissions
-            .default_push_from_update(id);
+        let user_state = state.get_user_state_mut(user_id);
+        user_state.granted_permissions.default_push_from_update(id);
+        user_state.share_validities.load_all();
     }
 
     fn push_bad_from_update(
@@ -577,3 +584,196 @@ impl TableItemDelete for GrantedPermission {
             .default_push_bad_from_delete(id, response);
     }
 }
+
+impl TableItemLoadAll for ShareValidity {
+    const LOAD_ALL_PATH: &'static str = "permissions/load_validities";
+
+    fn push_from_load_all(state: &mut State, user_id: TableId, items: Vec<Self>) {
+        state.clear_events(user_id);
+        state
+            .get_user_state_mut(user_id)
+            .share_validities
+            .default_push_from_load_all(items);
+        state.populate_granted_user_states(user_id);
+    }
+
+    fn push_bad_from_load_all(state: &mut State, user_id: TableId) {
+        state
+            .get_user_state_mut(user_id)
+            .share_validities
+            .default_push_bad_from_load_all();
+    }
+}
+
+impl TableItemLoadAll for PlanOverride {
+    const LOAD_ALL_PATH: &'static str = "schedules/load_plan_overrides";
+
+    fn push_from_load_all(state: &mut State, user_id: TableId, items: Vec<Self>) {
+        state.clear_events(user_id);
+        state
+            .get_user_state_mut(user_id)
+            .plan_overrides
+            .default_push_from_load_all(items);
+    }
+
+    fn push_bad_from_load_all(state: &mut State, user_id: TableId) {
+        state
+            .get_user_state_mut(user_id)
+            .plan_overrides
+            .default_push_bad_from_load_all();
+    }
+}
+
+impl TableItemLoadAll for PlanException {
+    const LOAD_ALL_PATH: &'static str = "schedules/load_exceptions";
+
+    fn push_from_load_all(state: &mut State, user_id: TableId, items: Vec<Self>) {
+        state.clear_events(user_id);
+        state
+            .get_user_state_mut(user_id)
+            .plan_exceptions
+            .default_push_from_load_all(items);
+    }
+
+    fn push_bad_from_load_all(state: &mut State, user_id: TableId) {
+        state
+            .get_user_state_mut(user_id)
+            .plan_exceptions
+            .default_push_bad_from_load_all();
+    }
+}
+
+impl TableItemInsert for PlanException {
+    type NewItem = NewPlanException;
+    const INSERT_PATH: &'static str = "schedules/insert_exception";
+
+    fn push_from_insert(state: &mut State, user_id: TableId) {
+        state
+            .get_user_state_mut(user_id)
+            .plan_exceptions
+            .default_push_from_insert();
+    }
+
+    fn push_bad_from_insert(state: &mut State, user_id: TableId, _: Self::BadResponse) {
+        state
+            .get_user_state_mut(user_id)
+            .plan_exceptions
+            .default_push_bad_from_insert();
+    }
+}
+
+impl TableItemUpdate for PlanException {
+    type UpdItem = UpdatePlanException;
+    const UPDATE_PATH: &'static str = "schedules/update_exception";
+
+    fn push_from_update(state: &mut State, user_id: TableId, _id: TableId) {
+        state.get_user_state(user_id).plan_exceptions.load_all();
+    }
+
+    fn push_bad_from_update(
+        state: &mut State,
+        user_id: TableId,
+        _id: TableId,
+        _response: UpdateBadRequestResponse,
+    ) {
+        state.get_user_state(user_id).plan_exceptions.load_all();
+    }
+}
+
+impl TableItemDelete for PlanException {
+    const DELETE_PATH: &'static str = "schedules/delete_exception";
+
+    fn push_from_delete(state: &mut State, user_id: TableId, id: TableId) {
+        // The occurrence is generated again
+        state.clear_events(user_id);
+        state
+            .get_user_state_mut(user_id)
+            .plan_exceptions
+            .default_push_from_delete(id);
+    }
+
+    fn push_bad_from_delete(
+        state: &mut State,
+        user_id: TableId,
+        _id: TableId,
+        _response: DeleteBadRequestResponse,
+    ) {
+        state.get_user_state(user_id).plan_exceptions.load_all();
+    }
+}
+
+impl TableItemLoadAll for SchedulePolicy {
+    const LOAD_ALL_PATH: &'static str = "schedules/load_policies";
+
+    fn push_from_load_all(state: &mut State, user_id: TableId, items: Vec<Self>) {
+        state
+            .get_user_state_mut(user_id)
+            .schedule_policies
+            .default_push_from_load_all(items);
+    }
+
+    fn push_bad_from_load_all(state: &mut State, user_id: TableId) {
+        state
+            .get_user_state_mut(user_id)
+            .schedule_policies
+            .default_push_bad_from_load_all();
+    }
+}
+
+impl TableItemLoadAll for ShareLink {
+    const LOAD_ALL_PATH: &'static str = "share_links/load_array";
+
+    fn push_from_load_all(state: &mut State, user_id: TableId, items: Vec<Self>) {
+        state
+            .get_user_state_mut(user_id)
+            .share_links
+            .default_push_from_load_all(items);
+    }
+
+    fn push_bad_from_load_all(state: &mut State, user_id: TableId) {
+        state
+            .get_user_state_mut(user_id)
+            .share_links
+            .default_push_bad_from_load_all();
+    }
+}
+
+impl TableItemInsert for ShareLink {
+    type NewItem = NewShareLink;
+    const INSERT_PATH: &'static str = "share_links/insert";
+
+    fn push_from_insert(state: &mut State, user_id: TableId) {
+        state
+            .get_user_state_mut(user_id)
+            .share_links
+            .default_push_from_insert();
+    }
+
+    fn push_bad_from_insert(state: &mut State, user_id: TableId, _: Self::BadResponse) {
+        state
+            .get_user_state_mut(user_id)
+            .share_links
+            .default_push_bad_from_insert();
+    }
+}
+
+impl TableItemDelete for ShareLink {
+    const DELETE_PATH: &'static str = "share_links/delete";
+
+    fn push_from_delete(state: &mut State, user_id: TableId, id: TableId) {
+        state
+            .get_user_state_mut(user_id)
+            .share_links
+            .default_push_from_delete(id);
+    }
+
+    fn push_bad_from_delete(
+        state: &mut State,
+        user_id: TableId,
+        _id: TableId,
+        _response: DeleteBadRequestResponse,
+    ) {
+        // There is no single link loading, so everything is reloaded
+        state.get_user_state(user_id).share_links.load_all();
+    }
+}
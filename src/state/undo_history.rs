@@ -0,0 +1,465 @@
+use std::sync::{Mutex, MutexGuard};
+
+use calendar_lib::api::{
+    event_templates::types::{EventTemplate, NewEventTemplate, UpdateEventTemplate},
+    events::types::{Event, NewEvent, UpdateEvent},
+    utils::*,
+};
+use chrono::{Duration, NaiveDateTime};
+use itertools::Itertools;
+
+use super::{
+    audit_log::{AuditAction, AuditEntry, AuditTable, TableItemAudit},
+    main_state::{State, UserState},
+    request::{make_request_check, RequestCheck},
+    state_table::StateTable,
+    table_requests::{TableItemDelete, TableItemInsert, TableItemUpdate},
+};
+
+pub trait TableItemUndo
+where
+    Self:
+        TableItemAudit + TableItemInsert<Info = ()> + TableItemUpdate<Info = ()> + TableItemDelete,
+{
+    fn get_state_table(state: &UserState) -> &StateTable<Self>;
+    fn make_new_item(self, user_id: TableId) -> Self::NewItem;
+    /// Sets every field to the value of this item
+    fn make_full_update(self) -> Self::UpdItem;
+}
+
+impl TableItemUndo for Event {
+    fn get_state_table(state: &UserState) -> &StateTable<Self> {
+        &state.events
+    }
+
+    fn make_new_item(self, user_id: TableId) -> NewEvent {
+        NewEvent {
+            user_id,
+            name: self.name,
+            description: self.description,
+            start: self.start,
+            end: self.end,
+            access_level: self.access_level,
+            visibility: self.visibility,
+            plan_id: self.plan_id,
+        }
+    }
+
+    fn make_full_update(self) -> UpdateEvent {
+        UpdateEvent {
+            id: self.id,
+            name: USome(self.name),
+            description: USome(self.description),
+            start: USome(self.start),
+            end: USome(self.end),
+            access_level: USome(self.access_level),
+            visibility: USome(self.visibility),
+            plan_id: UNone,
+        }
+    }
+}
+
+impl TableItemUndo for EventTemplate {
+    fn get_state_table(state: &UserState) -> &StateTable<Self> {
+        &state.event_templates
+    }
+
+    fn make_new_item(self, user_id: TableId) -> NewEventTemplate {
+        NewEventTemplate {
+            user_id,
+            name: self.name,
+            event_name: self.event_name,
+            event_description: self.event_description,
+            duration: self.duration,
+            access_level: self.access_level,
+        }
+    }
+
+    fn make_full_update(self) -> UpdateEventTemplate {
+        UpdateEventTemplate {
+            id: self.id,
+            name: USome(self.name),
+            event_name: USome(self.event_name),
+            event_description: USome(self.event_description),
+            duration: USome(self.duration),
+            access_level: USome(self.access_level),
+        }
+    }
+}
+
+impl AuditTable {
+    pub fn is_undoable(&self) -> bool {
+        matches!(self, AuditTable::Events | AuditTable::EventTemplates)
+    }
+}
+
+impl UserState {
+    /// Recreated items get new ids, which are written back to the entries once the server responds
+    fn apply_change<T: TableItemUndo>(
+        &self,
+        entry: &AuditEntry,
+        inverse: bool,
+    ) -> Option<RequestCheck> {
+        let table = T::get_state_table(self);
+        match (entry.action, inverse) {
+            (AuditAction::Insert, false) => {
+                let item = serde_json::from_value(entry.after.clone()?).ok()?;
+                Some(make_request_check(table.recreate(item, entry.item_id?)))
+            }
+            (AuditAction::Insert, true) => Some(make_request_check(table.delete(entry.item_id?))),
+            (AuditAction::Update, false) => {
+                let item = serde_json::from_value(entry.after.clone()?).ok()?;
+                Some(make_request_check(table.update(item)))
+            }
+            (AuditAction::Update, true) => {
+                let item = serde_json::from_value::<T>(entry.before.clone()?).ok()?;
+                Some(make_request_check(table.update(item.make_full_update())))
+            }
+            (AuditAction::Delete, false) => Some(make_request_check(table.delete(entry.item_id?))),
+            (AuditAction::Delete, true) => {
+                let item = serde_json::from_value::<T>(entry.before.clone()?).ok()?;
+                Some(make_request_check(
+                    table.recreate(item.make_new_item(self.user_id), entry.item_id?),
+                ))
+            }
+        }
+    }
+
+    /// Repeats the change or issues the inverse of it.
+    /// Must be called without holding the audit log or the undo history
+    pub(super) fn apply_audit_entry(
+        &self,
+        entry: &AuditEntry,
+        inverse: bool,
+    ) -> Option<RequestCheck> {
+        match entry.table {
+            AuditTable::Events => self.apply_change::<Event>(entry, inverse),
+            AuditTable::EventTemplates => self.apply_change::<EventTemplate>(entry, inverse),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum UndoDirection {
+    Undo,
+    Redo,
+}
+
+/// Changes, which are undone together
+struct UndoStep {
+    id: u64,
+    entries: Vec<AuditEntry>,
+}
+
+/// Step, which requests are waiting for the server
+struct PendingStep {
+    direction: UndoDirection,
+    step: UndoStep,
+    checks: Vec<RequestCheck>,
+}
+
+#[derive(Debug, Clone)]
+pub struct UndoToast {
+    pub text: String,
+    pub time: NaiveDateTime,
+    pub can_undo: bool,
+}
+
+#[derive(Default)]
+pub struct UndoHistory {
+    undo: Vec<UndoStep>,
+    redo: Vec<UndoStep>,
+    next_step_id: u64,
+    /// Changes of a bulk operation are undone together
+    group: Option<u64>,
+    /// Changes, made while undoing, are not recorded
+    applying: bool,
+    pending: Option<PendingStep>,
+    toast: Option<UndoToast>,
+}
+
+impl UndoHistory {
+    const MAX_STEPS: usize = 100;
+    const TOAST_SECONDS: i64 = 5;
+
+    pub fn get() -> MutexGuard<'static, Self> {
+        use std::sync::OnceLock;
+
+        static DATA: OnceLock<Mutex<UndoHistory>> = OnceLock::new();
+        DATA.get_or_init(|| Mutex::new(UndoHistory::default()))
+            .lock()
+            .unwrap()
+    }
+
+    pub(super) fn clear(&mut self) {
+        *self = Self::default();
+    }
+
+    fn describe(group: &[AuditEntry]) -> String {
+        match group {
+            [entry] => format!(
+                "{} {}",
+                entry.table.item_human_name(),
+                entry.action.human_name().to_lowercase()
+            ),
+            group => format!("{} changes", group.len()),
+        }
+    }
+
+    fn show_toast(&mut self, text: String, can_undo: bool) {
+        self.toast = Some(UndoToast {
+            text,
+            time: chrono::Local::now().naive_local(),
+            can_undo,
+        });
+    }
+
+    fn push_undo(&mut self, step: UndoStep) {
+        self.undo.push(step);
+        if self.undo.len() > Self::MAX_STEPS {
+            self.undo.remove(0);
+        }
+    }
+
+    fn new_step_id(&mut self) -> u64 {
+        self.next_step_id += 1;
+        self.next_step_id
+    }
+
+    /// Step, a change that is being sent will be recorded to.
+    /// None, if the change can't be undone
+    pub(super) fn reserve_step(&mut self, table: AuditTable) -> Option<u64> {
+        if self.applying || !table.is_undoable() {
+            return None;
+        }
+        Some(match self.group {
+            Some(group) => group,
+            None => self.new_step_id(),
+        })
+    }
+
+    /// Called once the server accepts the change
+    pub(super) fn record(&mut self, step_id: u64, entry: AuditEntry) {
+        self.redo.clear();
+        let index = match self.undo.iter().position(|step| step.id == step_id) {
+            Some(index) => {
+                self.undo[index].entries.push(entry);
+                index
+            }
+            None => {
+                self.push_undo(UndoStep {
+                    id: step_id,
+                    entries: vec![entry],
+                });
+                self.undo.len() - 1
+            }
+        };
+        let text = Self::describe(&self.undo[index].entries);
+        self.show_toast(text, true);
+    }
+
+    pub(super) fn replace_item_id(
+        &mut self,
+        user_id: TableId,
+        table: AuditTable,
+        old_id: TableId,
+        new_id: TableId,
+    ) {
+        self.undo
+            .iter_mut()
+            .chain(self.redo.iter_mut())
+            .chain(self.pending.iter_mut().map(|pending| &mut pending.step))
+            .flat_map(|step| step.entries.iter_mut())
+            .for_each(|entry| entry.replace_item_id(user_id, table, old_id, new_id));
+    }
+
+    pub fn begin_group(&mut self) {
+        if self.group.is_none() {
+            self.group = Some(self.new_step_id());
+        }
+    }
+
+    pub fn end_group(&mut self) {
+        self.group = None;
+    }
+
+    /// Nothing can be undone, while the previous step is not finished
+    pub fn can_undo(&self) -> bool {
+        self.pending.is_none() && !self.undo.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        self.pending.is_none() && !self.redo.is_empty()
+    }
+
+    pub fn get_toast(&self) -> Option<&UndoToast> {
+        self.toast.as_ref().filter(|toast| {
+            chrono::Local::now().naive_local() - toast.time
+                < Duration::try_seconds(Self::TOAST_SECONDS).unwrap()
+        })
+    }
+
+    pub fn hide_toast(&mut self) {
+        self.toast = None;
+    }
+}
+
+impl State {
+    fn apply_undo_step(&self, direction: UndoDirection) {
+        let step = {
+            let mut history = UndoHistory::get();
+            if history.pending.is_some() {
+                return;
+            }
+            let step = match direction {
+                UndoDirection::Undo => history.undo.pop(),
+                UndoDirection::Redo => history.redo.pop(),
+            };
+            match step {
+                Some(step) => {
+                    history.applying = true;
+                    history.hide_toast();
+                    step
+                }
+                None => return,
+            }
+        };
+
+        let inverse = direction == UndoDirection::Undo;
+        let entries = if inverse {
+            step.entries.iter().rev().collect_vec()
+        } else {
+            step.entries.iter().collect_vec()
+        };
+        let checks = entries
+            .into_iter()
+            .map(|entry| {
+                self.try_get_user_state(entry.user_id)
+                    .and_then(|user_state| user_state.apply_audit_entry(entry, inverse))
+            })
+            .collect_vec();
+
+        let mut history = UndoHistory::get();
+        history.applying = false;
+        if checks.iter().any(|check| check.is_none()) {
+            // Item is gone or state is no longer available, whatever was sent can't be tracked
+            history.show_toast("Can't undo, data has changed".to_owned(), false);
+            return;
+        }
+        history.pending = Some(PendingStep {
+            direction,
+            step,
+            checks: checks.into_iter().flatten().collect(),
+        });
+    }
+
+    pub fn undo(&self) {
+        self.apply_undo_step(UndoDirection::Undo);
+    }
+
+    pub fn redo(&self) {
+        self.apply_undo_step(UndoDirection::Redo);
+    }
+
+    /// Rejected step is dropped, as some of its requests could have succeeded.
+    /// Rejected tables are reloaded by their requests
+    pub(super) fn update_undo_history(&mut self) {
+        let mut history = UndoHistory::get();
+        let results = match history.pending.as_ref() {
+            Some(pending) => pending.checks.iter().map(|check| check(self)).collect_vec(),
+            None => return,
+        };
+        if results.iter().any(|result| result.is_none()) {
+            return;
+        }
+        let pending = history.pending.take().unwrap();
+        if results.iter().all(|result| *result == Some(true)) {
+            match pending.direction {
+                UndoDirection::Undo => history.redo.push(pending.step),
+                UndoDirection::Redo => history.push_undo(pending.step),
+            }
+        } else {
+            let text = match pending.direction {
+                UndoDirection::Undo => "Undo was rejected by the server",
+                UndoDirection::Redo => "Redo was rejected by the server",
+            };
+            history.show_toast(text.to_owned(), false);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bytes::Bytes;
+    use reqwest::StatusCode;
+
+    use super::*;
+    use crate::{
+        config::Config,
+        db::request::RequestIdentifier,
+        state::table_requests::{StateRequestInfo, TableDeleteRequest},
+    };
+
+    fn transport_error() -> reqwest::Error {
+        reqwest::Client::new().get("not a url").build().unwrap_err()
+    }
+
+    fn start_undo(id: u64) {
+        let identifier =
+            RequestIdentifier::<TableDeleteRequest<Event>>::new(id, StateRequestInfo::new(1, 1));
+        let mut history = UndoHistory::get();
+        history.clear();
+        history.undo.push(UndoStep {
+            id: 1,
+            entries: vec![],
+        });
+        history.pending = Some(PendingStep {
+            direction: UndoDirection::Undo,
+            step: UndoStep {
+                id: 2,
+                entries: vec![],
+            },
+            checks: vec![make_request_check(identifier)],
+        });
+    }
+
+    fn assert_rejected() {
+        let history = UndoHistory::get();
+        assert!(history.pending.is_none());
+        assert!(history.can_undo());
+        assert!(history.redo.is_empty());
+        assert_eq!(
+            history.toast.as_ref().map(|toast| toast.text.as_str()),
+            Some("Undo was rejected by the server")
+        );
+    }
+
+    // One test, as the history is shared
+    #[test]
+    fn failed_inverse_request_releases_the_history() {
+        let mut state = State::new(&Config::local());
+
+        start_undo(1);
+        state.update_undo_history();
+        assert!(UndoHistory::get().pending.is_some());
+        state
+            .db_connector
+            .complete_request(1, Err(transport_error()));
+        state.update_undo_history();
+        assert_rejected();
+
+        start_undo(2);
+        state.db_connector.complete_request(
+            2,
+            Ok((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Bytes::from_static(b"oops"),
+            )),
+        );
+        state.update_undo_history();
+        assert_rejected();
+
+        UndoHistory::get().clear();
+    }
+}
@@ -10,9 +10,42 @@ use calendar_lib::api::{
 
 use chrono::{Duration, NaiveDate, NaiveDateTime};
 
-use crate::{db::request::RequestIdentifier, tables::DbTable};
+use crate::{
+    db::request::RequestIdentifier,
+    tables::{
+        plan_exceptions::{
+            NewPlanException, PlanException, PlanExceptionKind, UpdatePlanException,
+        },
+        plan_overrides::PlanOverride,
+        schedule_policies::SchedulePolicy,
+        share_links::ShareLink,
+        share_validities::ShareValidity,
+        DbTable, DbTableGetById,
+    },
+};
+
+use super::{
+    custom_requests::{StateChanges, TableVersions},
+    main_state::State,
+    state_table::StateTable,
+    table_requests::{TableInsertRequest, TableUpdateRequest},
+};
 
-use super::{state_table::StateTable, table_requests::TableInsertRequest};
+/// Exception is inserted for the occurrence, or the existing one is updated
+pub enum PlanExceptionRequest {
+    Insert(RequestIdentifier<TableInsertRequest<PlanException>>),
+    Update(RequestIdentifier<TableUpdateRequest<PlanException>>),
+}
+
+impl PlanExceptionRequest {
+    /// Whether the request succeeded, once it is known
+    pub fn get_result(&self, state: &State) -> Option<bool> {
+        match self {
+            PlanExceptionRequest::Insert(identifier) => state.get_request_result(identifier),
+            PlanExceptionRequest::Update(identifier) => state.get_request_result(identifier),
+        }
+    }
+}
 
 pub struct UserState {
     pub(super) user_id: TableId,
@@ -23,6 +56,11 @@ pub struct UserState {
     pub event_templates: StateTable<EventTemplate>,
     pub schedules: StateTable<Schedule>,
     pub granted_permissions: StateTable<GrantedPermission>,
+    pub share_validities: StateTable<ShareValidity>,
+    pub share_links: StateTable<ShareLink>,
+    pub plan_overrides: StateTable<PlanOverride>,
+    pub plan_exceptions: StateTable<PlanException>,
+    pub schedule_policies: StateTable<SchedulePolicy>,
 }
 
 impl UserState {
@@ -35,6 +73,11 @@ impl UserState {
             schedules: StateTable::new(),
             event_templates: StateTable::new(),
             granted_permissions: StateTable::new(),
+            share_validities: StateTable::new(),
+            share_links: StateTable::new(),
+            plan_overrides: StateTable::new(),
+            plan_exceptions: StateTable::new(),
+            schedule_policies: StateTable::new(),
         };
         state.set_user_id(user_id);
         state
@@ -48,21 +91,122 @@ impl UserState {
         self.event_templates.set_user_id(user_id);
         self.schedules.set_user_id(user_id);
         self.granted_permissions.set_user_id(user_id);
+        self.share_validities.set_user_id(user_id);
+        self.share_links.set_user_id(user_id);
+        self.plan_overrides.set_user_id(user_id);
+        self.plan_exceptions.set_user_id(user_id);
+        self.schedule_policies.set_user_id(user_id);
     }
 
     pub fn replace_data(&mut self, data: user_state::load::Response) {
-        self.users.get_table_mut().replace_all(data.users);
+        self.users.replace_all(data.users);
+        self.access_levels.replace_all(data.access_levels);
+        self.events.replace_all(data.events);
+        self.schedules.replace_all(data.schedules);
+        self.event_templates.replace_all(data.event_templates);
+        self.granted_permissions
+            .replace_all(data.granted_permissions);
+    }
+
+    pub fn get_versions(&self) -> TableVersions {
+        TableVersions {
+            users: self.users.get_version(),
+            access_levels: self.access_levels.get_version(),
+            events: self.events.get_version(),
+            event_templates: self.event_templates.get_version(),
+            schedules: self.schedules.get_version(),
+            granted_permissions: self.granted_permissions.get_version(),
+            share_validities: self.share_validities.get_version(),
+            plan_overrides: self.plan_overrides.get_version(),
+            plan_exceptions: self.plan_exceptions.get_version(),
+            schedule_policies: self.schedule_policies.get_version(),
+        }
+    }
+
+    pub(super) fn push_changes(&mut self, versions: TableVersions, changes: StateChanges) {
+        self.users.push_delta(versions.users, changes.users);
         self.access_levels
-            .get_table_mut()
-            .replace_all(data.access_levels);
-        self.events.get_table_mut().replace_all(data.events);
-        self.schedules.get_table_mut().replace_all(data.schedules);
+            .push_delta(versions.access_levels, changes.access_levels);
+        self.events.push_delta(versions.events, changes.events);
         self.event_templates
-            .get_table_mut()
-            .replace_all(data.event_templates);
+            .push_delta(versions.event_templates, changes.event_templates);
+        self.schedules
+            .push_delta(versions.schedules, changes.schedules);
         self.granted_permissions
-            .get_table_mut()
-            .replace_all(data.granted_permissions);
+            .push_delta(versions.granted_permissions, changes.granted_permissions);
+        self.share_validities
+            .push_delta(versions.share_validities, changes.share_validities);
+        self.plan_overrides
+            .push_delta(versions.plan_overrides, changes.plan_overrides);
+        self.plan_exceptions
+            .push_delta(versions.plan_exceptions, changes.plan_exceptions);
+        self.schedule_policies
+            .push_delta(versions.schedule_policies, changes.schedule_policies);
+    }
+
+    /// Template of the plan events, with the override of the schedule defaults
+    pub fn get_plan_template(
+        &self,
+        schedule: &Schedule,
+        plan_id: TableId,
+    ) -> Option<(&EventTemplate, PlanOverride)> {
+        let plan_override = self
+            .plan_overrides
+            .get_table()
+            .get_by_id(plan_id)
+            .copied()
+            .unwrap_or(PlanOverride::new(plan_id));
+        let template_id = plan_override.template_id.unwrap_or(schedule.template_id);
+        self.event_templates
+            .get_table()
+            .get_by_id(template_id)
+            .map(|template| (template, plan_override))
+    }
+
+    /// Exception of the occurrence, planned on the date
+    pub fn get_plan_exception(&self, plan_id: TableId, date: NaiveDate) -> Option<&PlanException> {
+        self.plan_exceptions
+            .get_table()
+            .get()
+            .iter()
+            .find(|exception| exception.plan_id == plan_id && exception.date == date)
+    }
+
+    /// Date, the occurrence was planned on, before it could be moved
+    pub fn get_occurrence_date(&self, plan_id: TableId, start: NaiveDateTime) -> NaiveDate {
+        self.plan_exceptions
+            .get_table()
+            .get()
+            .iter()
+            .filter(|exception| exception.plan_id == plan_id)
+            .find(|exception| match exception.kind {
+                PlanExceptionKind::Change { start: moved, .. } => moved == start,
+                PlanExceptionKind::Skip => false,
+            })
+            .map_or(start.date(), |exception| exception.date)
+    }
+
+    /// Occurrence can only have one exception, so the existing one is updated
+    pub fn set_plan_exception(
+        &self,
+        plan_id: TableId,
+        date: NaiveDate,
+        kind: PlanExceptionKind,
+    ) -> PlanExceptionRequest {
+        match self.get_plan_exception(plan_id, date) {
+            Some(exception) => {
+                PlanExceptionRequest::Update(self.plan_exceptions.update(UpdatePlanException {
+                    id: exception.id,
+                    kind,
+                }))
+            }
+            None => PlanExceptionRequest::Insert(self.plan_exceptions.insert(NewPlanException {
+                user_id: self.user_id,
+                plan_id,
+                date,
+                kind,
+            })),
+        }
     }
 
     pub fn accept_scheduled_event(
@@ -70,6 +214,20 @@ impl UserState {
         plan_id: TableId,
         date: NaiveDate,
     ) -> Option<RequestIdentifier<TableInsertRequest<Event>>> {
+        self.make_scheduled_event(plan_id, date)
+            .map(|event| self.events.insert(event))
+    }
+
+    /// Event of the occurrence with its exception applied. None, if it is skipped
+    pub(super) fn make_scheduled_event(
+        &self,
+        plan_id: TableId,
+        date: NaiveDate,
+    ) -> Option<NewEvent> {
+        let exception = self.get_plan_exception(plan_id, date).map(|e| e.kind);
+        if exception == Some(PlanExceptionKind::Skip) {
+            return None;
+        }
         self.schedules
             .get_table()
             .get()
@@ -80,28 +238,31 @@ impl UserState {
                     .iter()
                     .find(|plan| plan.id == plan_id)
                     .and_then(|plan| {
-                        self.event_templates
-                            .get_table()
-                            .get()
-                            .iter()
-                            .find(|template| schedule.template_id == template.id)
-                            .map(|template| (plan, template))
+                        self.get_plan_template(schedule, plan_id)
+                            .map(|(template, plan_override)| (plan, template, plan_override))
                     })
             })
-            .map(|(plan, template)| {
-                let start = NaiveDateTime::new(date, plan.time);
-                self.events.insert(NewEvent {
+            .map(|(plan, template, plan_override)| {
+                let duration = plan_override.duration.unwrap_or(template.duration);
+                let (start, duration) = match exception {
+                    Some(PlanExceptionKind::Change {
+                        start,
+                        duration: changed,
+                    }) => (start, changed.unwrap_or(duration)),
+                    _ => (NaiveDateTime::new(date, plan.time), duration),
+                };
+                NewEvent {
                     user_id: self.user_id,
                     name: template.event_name.clone(),
                     description: template.event_description.clone(),
                     start,
                     end: start
-                        .checked_add_signed(Duration::from_std(template.duration).unwrap())
+                        .checked_add_signed(Duration::from_std(duration).unwrap())
                         .unwrap(),
-                    access_level: template.access_level,
+                    access_level: plan_override.access_level.unwrap_or(template.access_level),
                     visibility: EventVisibility::HideName,
                     plan_id: Some(plan_id),
-                })
+                }
             })
     }
 }
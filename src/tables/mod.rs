@@ -2,8 +2,13 @@ pub mod access_levels;
 pub mod event_templates;
 pub mod events;
 pub mod permissions;
+pub mod plan_exceptions;
+pub mod plan_overrides;
 pub mod roles;
+pub mod schedule_policies;
 pub mod schedules;
+pub mod share_links;
+pub mod share_validities;
 pub mod users;
 
 pub use crate::db::table::*;
@@ -0,0 +1,58 @@
+use chrono::{NaiveDate, NaiveDateTime};
+use serde::{Deserialize, Serialize};
+
+use super::{DbTableItem, DbTableNewItem, DbTableUpdateItem, TableId};
+
+// TODO: move to lib
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum PlanExceptionKind {
+    Skip,
+    /// None keeps the duration of the plan
+    Change {
+        start: NaiveDateTime,
+        duration: Option<std::time::Duration>,
+    },
+}
+
+// TODO: move to lib
+/// Replacement of a single event plan occurrence
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PlanException {
+    pub id: TableId,
+    pub user_id: TableId,
+    pub plan_id: TableId,
+    /// Date, the occurrence was planned on
+    pub date: NaiveDate,
+    pub kind: PlanExceptionKind,
+}
+
+// TODO: move to lib
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NewPlanException {
+    pub user_id: TableId,
+    pub plan_id: TableId,
+    pub date: NaiveDate,
+    pub kind: PlanExceptionKind,
+}
+
+// TODO: move to lib
+/// Occurrence keeps its exception, only the change is replaced
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UpdatePlanException {
+    pub id: TableId,
+    pub kind: PlanExceptionKind,
+}
+
+impl DbTableItem for PlanException {
+    fn get_id(&self) -> TableId {
+        self.id
+    }
+}
+
+impl DbTableNewItem for NewPlanException {}
+
+impl DbTableUpdateItem for UpdatePlanException {
+    fn get_id(&self) -> TableId {
+        self.id
+    }
+}
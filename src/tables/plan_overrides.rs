@@ -0,0 +1,34 @@
+use serde::{Deserialize, Serialize};
+
+use super::{DbTableItem, TableId};
+
+// TODO: move to lib
+/// Per event plan replacements of the schedule defaults, None keeps the default
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct PlanOverride {
+    pub plan_id: TableId,
+    pub template_id: Option<TableId>,
+    pub duration: Option<std::time::Duration>,
+    pub access_level: Option<i32>,
+}
+
+impl PlanOverride {
+    pub fn new(plan_id: TableId) -> Self {
+        Self {
+            plan_id,
+            template_id: None,
+            duration: None,
+            access_level: None,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.template_id.is_none() && self.duration.is_none() && self.access_level.is_none()
+    }
+}
+
+impl DbTableItem for PlanOverride {
+    fn get_id(&self) -> TableId {
+        self.plan_id
+    }
+}
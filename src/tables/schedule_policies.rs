@@ -0,0 +1,39 @@
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+
+use super::{DbTableItem, TableId};
+
+// TODO: move to lib
+/// When occurrences of the schedule become events without the user accepting them
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MaterializePolicy {
+    Manual,
+    DaysAhead(u32),
+    WhenPassed,
+}
+
+impl MaterializePolicy {
+    pub fn human_name(&self) -> String {
+        match self {
+            MaterializePolicy::Manual => "Manually".to_owned(),
+            MaterializePolicy::DaysAhead(1) => "1 day ahead".to_owned(),
+            MaterializePolicy::DaysAhead(days) => format!("{days} days ahead"),
+            MaterializePolicy::WhenPassed => "When the time passes".to_owned(),
+        }
+    }
+}
+
+// TODO: move to lib
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct SchedulePolicy {
+    pub schedule_id: TableId,
+    pub policy: MaterializePolicy,
+    /// Occurrences before the policy was set are left to the user
+    pub since: NaiveDateTime,
+}
+
+impl DbTableItem for SchedulePolicy {
+    fn get_id(&self) -> TableId {
+        self.schedule_id
+    }
+}
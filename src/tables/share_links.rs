@@ -0,0 +1,28 @@
+use serde::{Deserialize, Serialize};
+
+use super::{DbTableItem, DbTableNewItem, TableId};
+
+// TODO: move to lib
+/// Read-only access to a calendar for anyone, who knows the token
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ShareLink {
+    pub id: TableId,
+    pub user_id: TableId,
+    pub token: String,
+    pub access_level: i32,
+}
+
+// TODO: move to lib
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NewShareLink {
+    pub user_id: TableId,
+    pub access_level: i32,
+}
+
+impl DbTableItem for ShareLink {
+    fn get_id(&self) -> TableId {
+        self.id
+    }
+}
+
+impl DbTableNewItem for NewShareLink {}
@@ -0,0 +1,59 @@
+use calendar_lib::api::permissions::types::{NewGrantedPermission, UpdateGrantedPermission};
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+
+use super::{DbTableItem, DbTableNewItem, DbTableUpdateItem, TableId};
+
+// TODO: move to lib
+/// Optional validity window of a GrantedPermission, both days are inclusive
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ShareValidity {
+    pub permission_id: TableId,
+    pub start: Option<NaiveDate>,
+    pub end: Option<NaiveDate>,
+}
+
+impl ShareValidity {
+    pub fn is_active(&self, date: NaiveDate) -> bool {
+        self.start.map_or(true, |start| start <= date) && !self.is_expired(date)
+    }
+
+    pub fn is_expired(&self, date: NaiveDate) -> bool {
+        self.end.is_some_and(|end| end < date)
+    }
+
+    /// Days until the share stops working, counting today
+    pub fn days_left(&self, date: NaiveDate) -> Option<i64> {
+        self.end.map(|end| (end - date).num_days() + 1)
+    }
+}
+
+impl DbTableItem for ShareValidity {
+    fn get_id(&self) -> TableId {
+        self.permission_id
+    }
+}
+
+// TODO: move to lib
+/// Share and its validity, so that both are saved by one request
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ShareWithValidity<T> {
+    #[serde(flatten)]
+    pub share: T,
+    pub start: Option<NaiveDate>,
+    pub end: Option<NaiveDate>,
+}
+
+impl<T> ShareWithValidity<T> {
+    pub fn new(share: T, (start, end): (Option<NaiveDate>, Option<NaiveDate>)) -> Self {
+        Self { share, start, end }
+    }
+}
+
+impl DbTableNewItem for ShareWithValidity<NewGrantedPermission> {}
+
+impl DbTableUpdateItem for ShareWithValidity<UpdateGrantedPermission> {
+    fn get_id(&self) -> TableId {
+        self.share.id
+    }
+}
@@ -0,0 +1,310 @@
+use egui::{Grid, Id, RichText, Widget};
+use std::hash::Hash;
+
+use crate::{
+    app::CalendarApp,
+    db::request::RequestIdentifier,
+    state::{
+        audit_log::TableItemAudit,
+        state_table::StateTable,
+        table_requests::{
+            TableItemLoadById, TableItemUpdate, TableLoadByIdRequest, TableUpdateRequest,
+            UpdateRejection,
+        },
+    },
+    tables::{DbTable, DbTableGetById, DbTableItem, TableId},
+};
+
+/// Field that was changed both by us and by someone else since editing started
+pub struct FieldConflict<F> {
+    pub field: F,
+    pub name: &'static str,
+    pub mine: String,
+    pub theirs: String,
+    pub keep_mine: bool,
+}
+
+/// Three-way comparison against the item the edit was started from
+pub fn find_conflict<F, T: PartialEq>(
+    field: F,
+    name: &'static str,
+    base: &T,
+    mine: &T,
+    theirs: &T,
+    to_string: impl Fn(&T) -> String,
+) -> Option<FieldConflict<F>> {
+    (mine != base && theirs != base && mine != theirs).then(|| FieldConflict {
+        field,
+        name,
+        mine: to_string(mine),
+        theirs: to_string(theirs),
+        keep_mine: true,
+    })
+}
+
+pub struct ConflictResolver<'a, F> {
+    id: Id,
+    conflicts: &'a mut Vec<FieldConflict<F>>,
+}
+
+impl<'a, F> ConflictResolver<'a, F> {
+    pub fn new(id: impl Hash, conflicts: &'a mut Vec<FieldConflict<F>>) -> Self {
+        Self {
+            id: Id::new(id),
+            conflicts,
+        }
+    }
+}
+
+impl<'a, F> Widget for ConflictResolver<'a, F> {
+    fn ui(self, ui: &mut egui::Ui) -> egui::Response {
+        ui.vertical(|ui| {
+            ui.label(RichText::new("Someone else changed this while you were editing").strong());
+            Grid::new(self.id).striped(true).show(ui, |ui| {
+                ui.label("Field");
+                ui.label("Keep mine");
+                ui.label("Take theirs");
+                ui.end_row();
+
+                self.conflicts.iter_mut().for_each(|conflict| {
+                    ui.label(conflict.name);
+                    ui.radio_value(&mut conflict.keep_mine, true, &conflict.mine);
+                    ui.radio_value(&mut conflict.keep_mine, false, &conflict.theirs);
+                    ui.end_row();
+                });
+            });
+            ui.separator();
+        })
+        .response
+    }
+}
+
+/// Why the server copy of the item is loaded
+#[derive(Clone, Copy)]
+enum Reload {
+    /// Version of the base is unknown, so the item is compared with the server copy first
+    BeforeUpdate,
+    /// Update was rejected, because the item was changed after the base version
+    Stale(u64),
+}
+
+/// Edit of an existing item, that is merged with changes made by someone else meanwhile
+pub struct ConflictEdit<T: TableItemLoadById + TableItemUpdate, F> {
+    /// Item as it was when editing started
+    base: T,
+    /// Version the base was received at, checked against the state on the first frame
+    base_version: Option<Option<u64>>,
+    /// Server copy of the item and its version, while conflicts are being resolved
+    theirs: Option<(T, Option<u64>)>,
+    pub conflicts: Vec<FieldConflict<F>>,
+    deleted: bool,
+
+    update_request: Option<RequestIdentifier<TableUpdateRequest<T>>>,
+    reload_request: Option<(RequestIdentifier<TableLoadByIdRequest<T>>, Reload)>,
+}
+
+impl<T: TableItemLoadById + TableItemUpdate, F> ConflictEdit<T, F> {
+    pub fn new(base: T) -> Self {
+        Self {
+            base,
+            base_version: None,
+            theirs: None,
+            conflicts: vec![],
+            deleted: false,
+            update_request: None,
+            reload_request: None,
+        }
+    }
+
+    pub fn get_base(&self) -> &T {
+        &self.base
+    }
+
+    pub fn is_deleted(&self) -> bool {
+        self.deleted
+    }
+}
+
+/// Popup, that changes an existing item and resolves conflicts with concurrent changes on save
+pub trait ConflictEditor
+where
+    <Self::Item as TableItemUpdate>::Info: Default,
+{
+    type Item: TableItemLoadById + TableItemUpdate + TableItemAudit + Clone;
+    type Field: 'static + Copy + PartialEq;
+
+    const FIELDS: &'static [Self::Field];
+
+    fn get_edit(&mut self) -> &mut Option<ConflictEdit<Self::Item, Self::Field>>;
+    fn get_state_table<'a>(&self, app: &'a CalendarApp) -> &'a StateTable<Self::Item>;
+
+    fn find_conflicts(
+        &self,
+        app: &CalendarApp,
+        base: &Self::Item,
+        theirs: &Self::Item,
+    ) -> Vec<FieldConflict<Self::Field>>;
+    fn is_changed(&self, field: Self::Field, base: &Self::Item) -> bool;
+    fn take_theirs(&mut self, field: Self::Field, theirs: &Self::Item);
+    /// Only fields changed by us are sent, so that concurrent changes to others are kept
+    fn make_update(
+        &self,
+        id: TableId,
+        base: &Self::Item,
+    ) -> <Self::Item as TableItemUpdate>::UpdItem;
+
+    /// Server copy became the base of the edit
+    #[allow(unused_variables)]
+    fn on_rebase(&mut self, base: &Self::Item) {}
+    /// Update was sent on save. Not called, when it is sent again after a stale rejection
+    #[allow(unused_variables)]
+    fn on_update(&mut self, app: &CalendarApp, base: &Self::Item) {}
+
+    /// Handles the responses of the edit, called each frame.
+    /// Some(true) once the item is saved, Some(false) if the save failed
+    fn update_edit(&mut self, app: &CalendarApp) -> Option<bool> {
+        let mut edit = self.get_edit().take()?;
+        let table = self.get_state_table(app);
+        let id = edit.base.get_id();
+        let item = table.get_table().get_by_id(id);
+        // Popup is made from the item before the first frame, so its version is only
+        // trusted if the item didn't change since
+        if edit.base_version.is_none() {
+            let is_same = item.is_some_and(|item| {
+                serde_json::to_value(item).ok() == serde_json::to_value(&edit.base).ok()
+            });
+            edit.base_version = Some(table.get_item_version(id).filter(|_| is_same));
+        }
+        edit.deleted = item.is_none();
+
+        let mut result = None;
+        if let Some(identifier) = edit.update_request.as_ref() {
+            if let Some(succeeded) = app.state.get_request_result(identifier) {
+                result = Some(succeeded);
+                if let Some(Err(rejection)) = app.state.get_response(identifier) {
+                    if let UpdateRejection::Stale(stale) = &*rejection {
+                        edit.reload_request =
+                            Some((table.load_by_id(id), Reload::Stale(stale.current_version)));
+                        result = None;
+                    }
+                }
+                edit.update_request = None;
+            }
+        }
+        if let Some((identifier, reload)) = edit.reload_request.as_ref() {
+            if let Some(succeeded) = app.state.get_request_result(identifier) {
+                let reload = *reload;
+                let theirs = app
+                    .state
+                    .get_response(identifier)
+                    .and_then(|response| response.ok().map(|theirs| theirs.clone()));
+                edit.reload_request = None;
+                match theirs.filter(|_| succeeded) {
+                    Some(theirs) => on_reload(self, app, &mut edit, theirs, reload),
+                    None => result = Some(false),
+                }
+            }
+        }
+
+        *self.get_edit() = Some(edit);
+        result
+    }
+
+    /// Sends the changed fields, or shows the conflicts with the current item
+    fn save_edit(&mut self, app: &CalendarApp) {
+        let Some(mut edit) = self.get_edit().take() else {
+            return;
+        };
+        if let Some((theirs, version)) = edit.theirs.take() {
+            resolve_conflicts(self, &mut edit, theirs, version);
+        }
+
+        let table = self.get_state_table(app);
+        let id = edit.base.get_id();
+        if let Some(theirs) = table.get_table().get_by_id(id).cloned() {
+            let conflicts = self.find_conflicts(app, &edit.base, &theirs);
+            if !conflicts.is_empty() {
+                edit.conflicts = conflicts;
+                edit.theirs = Some((theirs, table.get_item_version(id)));
+            } else if let Some(version) = edit.base_version.flatten() {
+                send_update(self, app, &mut edit, Some(version));
+                self.on_update(app, &edit.base);
+            } else {
+                edit.reload_request = Some((table.load_by_id(id), Reload::BeforeUpdate));
+            }
+        }
+
+        *self.get_edit() = Some(edit);
+    }
+}
+
+/// Applies "take theirs" choices and their changes to fields we didn't touch,
+/// then continues editing on top of the server version
+fn resolve_conflicts<E: ConflictEditor + ?Sized>(
+    editor: &mut E,
+    edit: &mut ConflictEdit<E::Item, E::Field>,
+    theirs: E::Item,
+    version: Option<u64>,
+) where
+    <E::Item as TableItemUpdate>::Info: Default,
+{
+    let conflicts = std::mem::take(&mut edit.conflicts);
+    E::FIELDS.iter().for_each(|&field| {
+        let keep_mine = conflicts
+            .iter()
+            .find(|conflict| conflict.field == field)
+            .map_or_else(
+                || editor.is_changed(field, &edit.base),
+                |conflict| conflict.keep_mine,
+            );
+        if !keep_mine {
+            editor.take_theirs(field, &theirs);
+        }
+    });
+    editor.on_rebase(&theirs);
+    edit.base = theirs;
+    edit.base_version = Some(version);
+}
+
+fn on_reload<E: ConflictEditor + ?Sized>(
+    editor: &mut E,
+    app: &CalendarApp,
+    edit: &mut ConflictEdit<E::Item, E::Field>,
+    theirs: E::Item,
+    reload: Reload,
+) where
+    <E::Item as TableItemUpdate>::Info: Default,
+{
+    let version = match reload {
+        // Item was just compared with the server copy, so it is updated as is
+        Reload::BeforeUpdate => None,
+        Reload::Stale(version) => Some(version),
+    };
+    let conflicts = editor.find_conflicts(app, &edit.base, &theirs);
+    if conflicts.is_empty() {
+        // Only our fields are sent, so their changes are kept
+        send_update(editor, app, edit, version);
+        if let Reload::BeforeUpdate = reload {
+            editor.on_update(app, &edit.base);
+        }
+    } else {
+        edit.conflicts = conflicts;
+        edit.theirs = Some((theirs, version));
+    }
+}
+
+fn send_update<E: ConflictEditor + ?Sized>(
+    editor: &E,
+    app: &CalendarApp,
+    edit: &mut ConflictEdit<E::Item, E::Field>,
+    version: Option<u64>,
+) where
+    <E::Item as TableItemUpdate>::Info: Default,
+{
+    let update = editor.make_update(edit.base.get_id(), &edit.base);
+    edit.update_request = Some(
+        editor
+            .get_state_table(app)
+            .update_from_version(update, version),
+    );
+}
@@ -1,14 +1,18 @@
-use super::popups::popup_manager::PopupManager;
-use crate::{app::CalendarApp, db::aliases::Event};
-use calendar_lib::api::{events::types::EventVisibility, permissions::types::TablePermissions};
+use super::{event_selection::EventSelection, popups::popup_manager::PopupManager, utils::UiUtils};
+use crate::{
+    app::CalendarApp,
+    db::aliases::Event,
+    state::capabilities::{Capabilities, CapabilityAction, CapabilityTable},
+    tables::{plan_exceptions::PlanExceptionKind, DbTable},
+};
+use calendar_lib::api::{events::types::EventVisibility, utils::TableId};
 use egui::{Align, Color32, Layout, Stroke, Vec2, Widget};
 
 pub struct EventCard<'a> {
     app: &'a CalendarApp,
     desired_size: Vec2,
     event: &'a Event,
-    permission: TablePermissions,
-    access_level: i32,
+    capabilities: Capabilities,
 
     show_description: bool,
     show_date: bool,
@@ -21,15 +25,13 @@ impl<'a> EventCard<'a> {
         app: &'a CalendarApp,
         desired_size: Vec2,
         event: &'a Event,
-        access_level: i32,
-        permission: TablePermissions,
+        capabilities: Capabilities,
     ) -> Self {
         Self {
             app,
             desired_size,
             event,
-            access_level,
-            permission,
+            capabilities,
             show_description: true,
             show_date: true,
             show_time: true,
@@ -71,7 +73,7 @@ impl<'a> EventCard<'a> {
 impl<'a> EventCard<'a> {
     fn get_name_text(&self) -> &'a str {
         if self.event.visibility == EventVisibility::HideName
-            && self.access_level < self.event.access_level
+            && self.capabilities.get_access_level() < self.event.access_level
         {
             "Hidden"
         } else {
@@ -160,16 +162,101 @@ impl<'a> EventCard<'a> {
             })
             .response;
 
-        if is_phantom && response.double_clicked() {
+        if is_phantom
+            && self
+                .capabilities
+                .table(CapabilityTable::Events, CapabilityAction::Create)
+                .is_ok()
+            && response.double_clicked()
+        {
             if let Some(plan_id) = plan_id {
-                self.app
-                    .get_selected_user_state()
-                    .accept_scheduled_event(*plan_id, start.date());
+                let user_state = self.app.get_selected_user_state();
+                user_state.accept_scheduled_event(
+                    *plan_id,
+                    user_state.get_occurrence_date(*plan_id, *start),
+                );
             }
         }
 
         response
     }
+
+    /// Phantom events are occurrences of a schedule, that are changed one at a time
+    fn show_phantom_menu(&self, response: &egui::Response, plan_id: TableId) {
+        let can_accept = self.capabilities.item(
+            CapabilityTable::Events,
+            CapabilityAction::Create,
+            self.event.access_level,
+        );
+        let can_change = self.capabilities.item(
+            CapabilityTable::Schedules,
+            CapabilityAction::Edit,
+            self.event.access_level,
+        );
+        if can_accept.is_err() && can_change.is_err() {
+            return;
+        }
+
+        let user_state = self.app.get_selected_user_state();
+        let date = user_state.get_occurrence_date(plan_id, self.event.start);
+        let exception_id = user_state
+            .get_plan_exception(plan_id, date)
+            .map(|exception| exception.id);
+        response.context_menu(|ui| {
+            if ui
+                .add_permitted(can_accept, egui::Button::new("Accept"))
+                .clicked()
+            {
+                user_state.accept_scheduled_event(plan_id, date);
+                ui.close_menu();
+            }
+            ui.separator();
+            if ui
+                .add_permitted(can_change, egui::Button::new("Skip this time"))
+                .clicked()
+            {
+                user_state.set_plan_exception(plan_id, date, PlanExceptionKind::Skip);
+                ui.close_menu();
+            }
+            if ui
+                .add_permitted(can_change, egui::Button::new("Change this time…"))
+                .clicked()
+            {
+                PopupManager::get().open_plan_exception(self.event, plan_id, date);
+                ui.close_menu();
+            }
+            if let Some(exception_id) = exception_id {
+                if ui
+                    .add_permitted(can_change, egui::Button::new("Restore as planned"))
+                    .clicked()
+                {
+                    user_state.plan_exceptions.delete(exception_id);
+                    ui.close_menu();
+                }
+            }
+        });
+    }
+
+    /// Ctrl-click toggles the event, shift-click selects a range
+    fn update_selection(&self, ui: &egui::Ui, response: &egui::Response) {
+        if !response.clicked() {
+            return;
+        }
+        let modifiers = ui.input(|input| input.modifiers);
+        if !modifiers.command && !modifiers.shift {
+            return;
+        }
+        let mut selection = EventSelection::load(ui.ctx(), self.app.selected_user_id);
+        if modifiers.shift {
+            selection.select_range(
+                self.event.id,
+                self.app.get_selected_user_state().events.get_table().get(),
+            );
+        } else {
+            selection.toggle(self.event.id);
+        }
+        selection.store(ui.ctx());
+    }
 }
 
 impl<'a> Widget for EventCard<'a> {
@@ -183,17 +270,25 @@ impl<'a> Widget for EventCard<'a> {
 
             let is_planned = plan_id.is_some();
             let is_phantom = *event_id == -1;
+            let is_selectable =
+                !is_phantom && self.capabilities.can_change_any(CapabilityTable::Events);
+            let is_selected = is_selectable
+                && EventSelection::load(ui.ctx(), self.app.selected_user_id).is_selected(*event_id);
 
             let response = egui::Frame::none()
                 .rounding(4.)
-                .stroke(Stroke::new(
-                    1.,
-                    if is_planned {
-                        Color32::BLUE
-                    } else {
-                        Color32::RED
-                    },
-                ))
+                .stroke(if is_selected {
+                    Stroke::new(2., Color32::YELLOW)
+                } else {
+                    Stroke::new(
+                        1.,
+                        if is_planned {
+                            Color32::BLUE
+                        } else {
+                            Color32::RED
+                        },
+                    )
+                })
                 .inner_margin(4.)
                 .show(ui, |ui| {
                     if self.small {
@@ -202,7 +297,11 @@ impl<'a> Widget for EventCard<'a> {
                         self.show_content(ui);
                     }
                 })
-                .response;
+                .response
+                .interact(egui::Sense::click());
+            if is_selectable {
+                self.update_selection(ui, &response);
+            }
             /*
                        if self.small {
                            response.context_menu(|ui| {
@@ -213,25 +312,82 @@ impl<'a> Widget for EventCard<'a> {
                            });
                        }
             */
-            if !is_phantom
-                && (self.permission.edit || self.permission.delete)
-                && self.access_level >= self.event.access_level
-            {
+            let can_edit = self.capabilities.item(
+                CapabilityTable::Events,
+                CapabilityAction::Edit,
+                self.event.access_level,
+            );
+            let can_delete = self.capabilities.item(
+                CapabilityTable::Events,
+                CapabilityAction::Delete,
+                self.event.access_level,
+            );
+            let can_duplicate = self.capabilities.item(
+                CapabilityTable::Events,
+                CapabilityAction::Create,
+                self.event.access_level,
+            );
+            let can_repeat = self.capabilities.item(
+                CapabilityTable::Schedules,
+                CapabilityAction::Create,
+                self.event.access_level,
+            );
+            let can_make_template = self.capabilities.item(
+                CapabilityTable::EventTemplates,
+                CapabilityAction::Create,
+                self.event.access_level,
+            );
+            let can_copy = can_duplicate.is_ok() || can_repeat.is_ok() || can_make_template.is_ok();
+            if is_selectable || (!is_phantom && can_copy) {
                 response.context_menu(|ui| {
-                    if self.permission.edit {
-                        if ui.button("Edit").clicked() {
-                            PopupManager::get().open_update_event(&self.event);
-                            ui.close_menu();
-                        }
+                    if ui
+                        .add_permitted(can_edit, egui::Button::new("Edit"))
+                        .clicked()
+                    {
+                        PopupManager::get().open_update_event(&self.event);
+                        ui.close_menu();
                     }
-                    if self.permission.delete {
-                        if ui.button("Delete").clicked() {
-                            self.app.get_selected_user_state().events.delete(*event_id);
-                            ui.close_menu();
-                        }
+                    if ui
+                        .add_permitted(can_delete, egui::Button::new("Delete"))
+                        .clicked()
+                    {
+                        self.app.get_selected_user_state().events.delete(*event_id);
+                        ui.close_menu();
+                    }
+                    ui.separator();
+                    if ui
+                        .add_permitted(can_duplicate, egui::Button::new("Duplicate"))
+                        .clicked()
+                    {
+                        PopupManager::get().open_duplicate_event(&self.event);
+                        ui.close_menu();
+                    }
+                    if ui
+                        .add_permitted(can_repeat, egui::Button::new("Repeat weekly…"))
+                        .clicked()
+                    {
+                        PopupManager::get().open_schedule_from_event(
+                            &self.event,
+                            self.app
+                                .get_selected_user_state()
+                                .event_templates
+                                .get_table()
+                                .get(),
+                        );
+                        ui.close_menu();
+                    }
+                    if ui
+                        .add_permitted(can_make_template, egui::Button::new("Save as template"))
+                        .clicked()
+                    {
+                        PopupManager::get().open_event_template_from_event(&self.event);
+                        ui.close_menu();
                     }
                 });
             };
+            if let (true, Some(plan_id)) = (is_phantom, plan_id) {
+                self.show_phantom_menu(&response, *plan_id);
+            }
         })
         .response
     }
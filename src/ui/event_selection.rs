@@ -0,0 +1,92 @@
+use std::collections::HashSet;
+
+use calendar_lib::api::{events::types::Event, utils::TableId};
+use itertools::Itertools;
+
+/// Events, picked for a batch action. Kept in the egui memory, so that cards can change it
+#[derive(Debug, Clone, Default)]
+pub struct EventSelection {
+    user_id: TableId,
+    selected: HashSet<TableId>,
+    /// Last clicked event, range selection starts from it
+    anchor: Option<TableId>,
+}
+
+impl EventSelection {
+    fn id() -> egui::Id {
+        egui::Id::new("event_selection")
+    }
+
+    /// Selection of another user is dropped
+    pub fn load(ctx: &egui::Context, user_id: TableId) -> Self {
+        ctx.data(|data| data.get_temp::<Self>(Self::id()))
+            .filter(|selection| selection.user_id == user_id)
+            .unwrap_or_else(|| Self {
+                user_id,
+                ..Default::default()
+            })
+    }
+
+    pub fn store(self, ctx: &egui::Context) {
+        ctx.data_mut(|data| data.insert_temp(Self::id(), self));
+    }
+
+    pub fn is_selected(&self, id: TableId) -> bool {
+        self.selected.contains(&id)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.selected.is_empty()
+    }
+
+    pub fn get_ids(&self) -> Vec<TableId> {
+        self.selected.iter().copied().sorted().collect()
+    }
+
+    pub fn get_selected_mut(&mut self) -> &mut HashSet<TableId> {
+        &mut self.selected
+    }
+
+    pub fn toggle(&mut self, id: TableId) {
+        if !self.selected.remove(&id) {
+            self.selected.insert(id);
+        }
+        self.anchor = Some(id);
+    }
+
+    pub fn extend(&mut self, ids: impl IntoIterator<Item = TableId>) {
+        self.selected.extend(ids);
+    }
+
+    /// Selects every event between the anchor and the clicked one
+    pub fn select_range(&mut self, id: TableId, events: &[Event]) {
+        let anchor = self
+            .anchor
+            .and_then(|anchor| events.iter().find(|event| event.id == anchor));
+        let clicked = events.iter().find(|event| event.id == id);
+        match (anchor, clicked) {
+            (Some(anchor), Some(clicked)) => {
+                let from = anchor.start.min(clicked.start);
+                let to = anchor.start.max(clicked.start);
+                self.selected.extend(
+                    events
+                        .iter()
+                        .filter(|event| from <= event.start && event.start <= to)
+                        .map(|event| event.id),
+                );
+            }
+            _ => self.toggle(id),
+        }
+    }
+
+    /// Removes events, that no longer exist
+    pub fn retain_existing(&mut self, events: &[Event]) {
+        self.selected
+            .retain(|id| events.iter().any(|event| event.id == *id));
+    }
+
+    pub fn clear(&mut self) {
+        self.selected.clear();
+        self.anchor = None;
+    }
+}
@@ -1,14 +1,16 @@
-use super::popups::popup_manager::PopupManager;
-use crate::{app::CalendarApp, db::aliases::EventTemplate};
-use calendar_lib::api::permissions::types::TablePermissions;
+use super::{popups::popup_manager::PopupManager, utils::UiUtils};
+use crate::{
+    app::CalendarApp,
+    db::aliases::EventTemplate,
+    state::capabilities::{Capabilities, CapabilityAction, CapabilityTable},
+};
 use egui::{Align, Color32, Layout, Stroke, Vec2, Widget};
 
 pub struct EventTemplateCard<'a> {
     app: &'a CalendarApp,
     desired_size: Vec2,
     event_template: &'a EventTemplate,
-    permission: TablePermissions,
-    access_level: i32,
+    capabilities: Capabilities,
 
     show_description: bool,
 }
@@ -18,15 +20,13 @@ impl<'a> EventTemplateCard<'a> {
         app: &'a CalendarApp,
         desired_size: Vec2,
         template: &'a EventTemplate,
-        access_level: i32,
-        permission: TablePermissions,
+        capabilities: Capabilities,
     ) -> Self {
         Self {
             app,
             desired_size,
             event_template: template,
-            access_level,
-            permission,
+            capabilities,
             show_description: true,
         }
     }
@@ -67,24 +67,37 @@ impl<'a> Widget for EventTemplateCard<'a> {
                 })
                 .response;
 
-            if (self.permission.edit || self.permission.delete)
-                && self.access_level >= self.event_template.access_level
+            let can_edit = self.capabilities.item(
+                CapabilityTable::EventTemplates,
+                CapabilityAction::Edit,
+                self.event_template.access_level,
+            );
+            let can_delete = self.capabilities.item(
+                CapabilityTable::EventTemplates,
+                CapabilityAction::Delete,
+                self.event_template.access_level,
+            );
+            if self
+                .capabilities
+                .can_change_any(CapabilityTable::EventTemplates)
             {
                 response.context_menu(|ui| {
-                    if self.permission.edit {
-                        if ui.button("Edit").clicked() {
-                            PopupManager::get().open_update_event_template(&self.event_template);
-                            ui.close_menu();
-                        }
+                    if ui
+                        .add_permitted(can_edit, egui::Button::new("Edit"))
+                        .clicked()
+                    {
+                        PopupManager::get().open_update_event_template(&self.event_template);
+                        ui.close_menu();
                     }
-                    if self.permission.delete {
-                        if ui.button("Delete").clicked() {
-                            self.app
-                                .get_selected_user_state()
-                                .event_templates
-                                .delete(*template_id);
-                            ui.close_menu();
-                        }
+                    if ui
+                        .add_permitted(can_delete, egui::Button::new("Delete"))
+                        .clicked()
+                    {
+                        self.app
+                            .get_selected_user_state()
+                            .event_templates
+                            .delete(*template_id);
+                        ui.close_menu();
                     }
                 });
             };
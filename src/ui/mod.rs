@@ -1,11 +1,16 @@
 pub mod access_level_picker;
+pub mod conflict_resolver;
 pub mod event_card;
+pub mod event_selection;
 pub mod event_template_card;
 pub mod event_visibility_picker;
 pub mod layout_info;
 pub mod popups;
 pub mod schedule_card;
+pub mod schedule_designer;
+pub mod schedule_plan_editor;
 pub mod table_view;
 pub mod tables;
 pub mod time_picker;
+pub mod usage_chart;
 pub mod utils;
@@ -0,0 +1,97 @@
+use calendar_lib::api::utils::User;
+
+use super::{
+    popup::PopupType,
+    popup_content::{ContentInfo, PopupContent},
+};
+use crate::{
+    app::CalendarApp, db::request::RequestIdentifier,
+    state::admin_requests::AdminResetPasswordRequest, utils::is_password_strong_enough,
+};
+
+pub struct AdminPasswordReset {
+    user: User,
+
+    pub password: String,
+    pub password2: String,
+
+    request: Option<RequestIdentifier<AdminResetPasswordRequest>>,
+}
+
+impl AdminPasswordReset {
+    pub fn new(user: &User) -> Self {
+        Self {
+            user: user.clone(),
+            password: String::default(),
+            password2: String::default(),
+            request: None,
+        }
+    }
+}
+
+impl PopupContent for AdminPasswordReset {
+    fn get_type(&self) -> PopupType {
+        PopupType::AdminPasswordReset
+    }
+
+    fn init_frame(&mut self, app: &CalendarApp, info: &mut ContentInfo) {
+        if let Some(identifier) = self.request.as_ref() {
+            if let Some(response_info) = app.state.get_response(identifier) {
+                self.request = None;
+                if !response_info.is_err() {
+                    info.close();
+                }
+            }
+        }
+    }
+
+    fn get_title(&mut self) -> Option<String> {
+        Some(format!("Reset Password of '{}'", self.user.name))
+    }
+
+    fn show_content(&mut self, _app: &CalendarApp, ui: &mut egui::Ui, info: &mut ContentInfo) {
+        ui.vertical_centered(|ui| {
+            ui.label(format!("{} will have to log in again", self.user.email));
+            ui.add(
+                egui::TextEdit::singleline(&mut self.password)
+                    .desired_width(f32::INFINITY)
+                    .hint_text("New Password")
+                    .password(true),
+            );
+            ui.add(
+                egui::TextEdit::singleline(&mut self.password2)
+                    .desired_width(f32::INFINITY)
+                    .hint_text("Confirm Password")
+                    .password(true),
+            );
+        });
+
+        info.error(
+            !is_password_strong_enough(&self.password),
+            "Password is not strong enough",
+        );
+        info.error(
+            self.password != self.password2,
+            "Passwords must be the same",
+        );
+    }
+
+    fn show_buttons(&mut self, app: &CalendarApp, ui: &mut egui::Ui, info: &mut ContentInfo) {
+        if ui
+            .add_enabled(
+                !info.is_error() && self.request.is_none(),
+                egui::Button::new("Reset"),
+            )
+            .clicked()
+        {
+            self.request = Some(
+                app.state
+                    .admin_state
+                    .reset_password(self.user.id, self.password.clone()),
+            );
+        }
+        if ui.button("Cancel").clicked() {
+            info.close();
+        }
+    }
+}
@@ -0,0 +1,144 @@
+use calendar_lib::api::utils::User;
+use egui::{Color32, RichText};
+
+use super::{
+    popup::PopupType,
+    popup_content::{ContentInfo, PopupContent},
+};
+use crate::{
+    app::CalendarApp,
+    db::request::RequestIdentifier,
+    state::admin_requests::{AdminDeleteUserRequest, AdminUpdateUserBody, AdminUpdateUserRequest},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AdminUserAction {
+    Disable,
+    Enable,
+    Delete,
+}
+
+impl AdminUserAction {
+    fn human_name(&self) -> &'static str {
+        match self {
+            AdminUserAction::Disable => "Disable",
+            AdminUserAction::Enable => "Enable",
+            AdminUserAction::Delete => "Delete",
+        }
+    }
+
+    fn explain(&self) -> &'static str {
+        match self {
+            AdminUserAction::Disable => "User won't be able to log in, data is kept",
+            AdminUserAction::Enable => "User will be able to log in again",
+            AdminUserAction::Delete => "Account and all of its data are deleted for good",
+        }
+    }
+}
+
+pub struct AdminUserConfirm {
+    user: User,
+    action: AdminUserAction,
+    /// Deletion is confirmed by typing the email
+    confirm_email: String,
+
+    update_request: Option<RequestIdentifier<AdminUpdateUserRequest>>,
+    delete_request: Option<RequestIdentifier<AdminDeleteUserRequest>>,
+}
+
+impl AdminUserConfirm {
+    pub fn new(user: &User, action: AdminUserAction) -> Self {
+        Self {
+            user: user.clone(),
+            action,
+            confirm_email: String::default(),
+            update_request: None,
+            delete_request: None,
+        }
+    }
+}
+
+impl PopupContent for AdminUserConfirm {
+    fn get_type(&self) -> PopupType {
+        PopupType::AdminUserConfirm
+    }
+
+    fn init_frame(&mut self, app: &CalendarApp, info: &mut ContentInfo) {
+        if let Some(identifier) = self.update_request.as_ref() {
+            if let Some(response_info) = app.state.get_response(identifier) {
+                self.update_request = None;
+                if !response_info.is_err() {
+                    info.close();
+                }
+            }
+        }
+        if let Some(identifier) = self.delete_request.as_ref() {
+            if let Some(response_info) = app.state.get_response(identifier) {
+                self.delete_request = None;
+                if !response_info.is_err() {
+                    info.close();
+                }
+            }
+        }
+    }
+
+    fn get_title(&mut self) -> Option<String> {
+        Some(format!(
+            "{} '{}'?",
+            self.action.human_name(),
+            self.user.name
+        ))
+    }
+
+    fn show_content(&mut self, app: &CalendarApp, ui: &mut egui::Ui, info: &mut ContentInfo) {
+        ui.vertical(|ui| {
+            ui.label(self.action.explain());
+            if self.action == AdminUserAction::Delete {
+                ui.label(RichText::new(format!("Type {} to confirm", self.user.email)).strong());
+                ui.add(
+                    egui::TextEdit::singleline(&mut self.confirm_email)
+                        .desired_width(f32::INFINITY)
+                        .hint_text("Email"),
+                );
+                info.error(self.confirm_email != self.user.email, "Email doesn't match");
+            }
+        });
+        info.error(
+            self.user.id == app.state.get_me().id,
+            "You can't do this to your own account",
+        );
+    }
+
+    fn show_buttons(&mut self, app: &CalendarApp, ui: &mut egui::Ui, info: &mut ContentInfo) {
+        let is_sent = self.update_request.is_some() || self.delete_request.is_some();
+        let button = egui::Button::new(RichText::new(self.action.human_name()).color(
+            match self.action {
+                AdminUserAction::Delete => Color32::RED,
+                _ => ui.visuals().text_color(),
+            },
+        ));
+        if ui
+            .add_enabled(!info.is_error() && !is_sent, button)
+            .clicked()
+        {
+            match self.action {
+                AdminUserAction::Disable | AdminUserAction::Enable => {
+                    self.update_request =
+                        Some(app.state.admin_state.update_user(AdminUpdateUserBody {
+                            user_id: self.user.id,
+                            name: None,
+                            email: None,
+                            roles: None,
+                            disabled: Some(self.action == AdminUserAction::Disable),
+                        }));
+                }
+                AdminUserAction::Delete => {
+                    self.delete_request = Some(app.state.admin_state.delete_user(self.user.id));
+                }
+            }
+        }
+        if ui.button("Cancel").clicked() {
+            info.close();
+        }
+    }
+}
@@ -0,0 +1,188 @@
+use calendar_lib::api::{roles::types::Role, utils::User};
+
+use super::{
+    popup::PopupType,
+    popup_content::{ContentInfo, PopupContent},
+};
+use crate::{
+    app::CalendarApp,
+    db::request::RequestIdentifier,
+    state::admin_requests::{AdminCreateUserRequest, AdminUpdateUserBody, AdminUpdateUserRequest},
+    utils::{is_password_strong_enough, is_valid_email},
+};
+
+/// Roles, that admins can grant
+const ROLES: [Role; 2] = [Role::Admin, Role::SuperAdmin];
+
+pub struct AdminUserInput {
+    /// None for a new user
+    user: Option<User>,
+
+    pub name: String,
+    pub email: String,
+    pub password: String,
+    pub password2: String,
+    pub roles: Vec<Role>,
+
+    create_request: Option<RequestIdentifier<AdminCreateUserRequest>>,
+    update_request: Option<RequestIdentifier<AdminUpdateUserRequest>>,
+}
+
+impl AdminUserInput {
+    pub fn new() -> Self {
+        Self {
+            user: None,
+            name: String::default(),
+            email: String::default(),
+            password: String::default(),
+            password2: String::default(),
+            roles: vec![],
+            create_request: None,
+            update_request: None,
+        }
+    }
+
+    pub fn change(user: &User) -> Self {
+        Self {
+            user: Some(user.clone()),
+            name: user.name.clone(),
+            email: user.email.clone(),
+            roles: user.roles.clone(),
+            ..Self::new()
+        }
+    }
+
+    fn make_update(&self, user: &User) -> AdminUpdateUserBody {
+        let roles_changed = self.roles.len() != user.roles.len()
+            || self.roles.iter().any(|role| !user.roles.contains(role));
+        AdminUpdateUserBody {
+            user_id: user.id,
+            name: (self.name != user.name).then(|| self.name.clone()),
+            email: (self.email != user.email).then(|| self.email.clone()),
+            roles: roles_changed.then(|| self.roles.clone()),
+            disabled: None,
+        }
+    }
+}
+
+impl PopupContent for AdminUserInput {
+    fn get_type(&self) -> PopupType {
+        if self.user.is_some() {
+            PopupType::AdminUpdateUser
+        } else {
+            PopupType::AdminNewUser
+        }
+    }
+
+    fn init_frame(&mut self, app: &CalendarApp, info: &mut ContentInfo) {
+        if let Some(identifier) = self.create_request.as_ref() {
+            if let Some(response_info) = app.state.get_response(identifier) {
+                self.create_request = None;
+                if !response_info.is_err() {
+                    info.close();
+                }
+            }
+        }
+        if let Some(identifier) = self.update_request.as_ref() {
+            if let Some(response_info) = app.state.get_response(identifier) {
+                self.update_request = None;
+                if !response_info.is_err() {
+                    info.close();
+                }
+            }
+        }
+    }
+
+    fn get_title(&mut self) -> Option<String> {
+        Some(match &self.user {
+            Some(user) => format!("Edit '{}'", user.name),
+            None => "New User".to_owned(),
+        })
+    }
+
+    fn show_content(&mut self, app: &CalendarApp, ui: &mut egui::Ui, info: &mut ContentInfo) {
+        let show_input_field =
+            |ui: &mut egui::Ui, value: &mut String, hint: &str, password: bool| {
+                ui.add(
+                    egui::TextEdit::singleline(value)
+                        .desired_width(f32::INFINITY)
+                        .hint_text(hint)
+                        .password(password),
+                );
+            };
+        let is_me = self
+            .user
+            .as_ref()
+            .is_some_and(|user| user.id == app.state.get_me().id);
+
+        ui.vertical_centered(|ui| {
+            show_input_field(ui, &mut self.name, "Name", false);
+            show_input_field(ui, &mut self.email, "Email", false);
+            if self.user.is_none() {
+                show_input_field(ui, &mut self.password, "Password", true);
+                show_input_field(ui, &mut self.password2, "Confirm Password", true);
+            }
+        });
+        ui.horizontal(|ui| {
+            ui.label("Roles:");
+            // Admins can't lock themselves out
+            ui.add_enabled_ui(!is_me, |ui| {
+                ROLES.into_iter().for_each(|role| {
+                    let mut has_role = self.roles.contains(&role);
+                    if ui.checkbox(&mut has_role, format!("{role:?}")).changed() {
+                        if has_role {
+                            self.roles.push(role);
+                        } else {
+                            self.roles.retain(|r| *r != role);
+                        }
+                    }
+                });
+            });
+        });
+
+        info.error(self.name.is_empty(), "Name cannot be empty");
+        info.error(self.name.len() > 30, "Name must be at most 30 symbols");
+        info.error(!is_valid_email(&self.email), "Email is not valid");
+        if self.user.is_none() {
+            info.error(
+                !is_password_strong_enough(&self.password),
+                "Password is not strong enough",
+            );
+            info.error(
+                self.password != self.password2,
+                "Passwords must be the same",
+            );
+        }
+    }
+
+    fn show_buttons(&mut self, app: &CalendarApp, ui: &mut egui::Ui, info: &mut ContentInfo) {
+        let is_sent = self.create_request.is_some() || self.update_request.is_some();
+        match &self.user {
+            Some(user) => {
+                if ui
+                    .add_enabled(!info.is_error() && !is_sent, egui::Button::new("Update"))
+                    .clicked()
+                {
+                    self.update_request =
+                        Some(app.state.admin_state.update_user(self.make_update(user)));
+                }
+            }
+            None => {
+                if ui
+                    .add_enabled(!info.is_error() && !is_sent, egui::Button::new("Create"))
+                    .clicked()
+                {
+                    self.create_request = Some(app.state.admin_state.create_user(
+                        self.name.clone(),
+                        self.email.clone(),
+                        self.password.clone(),
+                        self.roles.clone(),
+                    ));
+                }
+            }
+        }
+        if ui.button("Cancel").clicked() {
+            info.close();
+        }
+    }
+}
@@ -0,0 +1,163 @@
+use super::{
+    popup::PopupType,
+    popup_content::{ContentInfo, PopupContent},
+};
+use crate::{
+    app::CalendarApp,
+    state::{
+        backup::CalendarBackup,
+        capabilities::{CapabilityAction, CapabilityTable},
+    },
+    tables::DbTable,
+    ui::utils::UiUtils,
+    utils::access_levels_human_name,
+};
+use calendar_lib::api::utils::TableId;
+use egui::{Color32, TextEdit};
+use std::hash::Hash;
+
+pub struct BackupRestoreInput {
+    eid: egui::Id,
+    user_id: TableId,
+
+    text: String,
+    backup: Option<Result<CalendarBackup, String>>,
+    is_started: bool,
+}
+
+impl BackupRestoreInput {
+    pub fn new(eid: impl Hash, user_id: TableId) -> Self {
+        Self {
+            eid: egui::Id::new(eid),
+            user_id,
+            text: String::new(),
+            backup: None,
+            is_started: false,
+        }
+    }
+
+    fn show_summary(&self, app: &CalendarApp, ui: &mut egui::Ui, backup: &CalendarBackup) {
+        ui.label(format!(
+            "Backup from {}: {} events, {} templates, {} schedules, {} shares",
+            backup.created.format("%Y-%m-%d %H:%M"),
+            backup.events.len(),
+            backup.event_templates.len(),
+            backup.schedules.len(),
+            backup.granted_permissions.len(),
+        ));
+        ui.weak("Restored items are added to the current calendar");
+
+        let target_levels = app
+            .state
+            .get_user_state(self.user_id)
+            .access_levels
+            .get_table()
+            .get();
+        egui::Grid::new(self.eid.with("access_levels")).show(ui, |ui| {
+            backup.access_levels.iter().for_each(|level| {
+                let target = CalendarBackup::remap_access_level(level.level, target_levels);
+                ui.label(&level.name);
+                ui.label("→");
+                ui.label(access_levels_human_name(target_levels, target));
+                ui.end_row();
+            });
+        });
+    }
+
+    fn show_progress(&self, app: &CalendarApp, ui: &mut egui::Ui) {
+        let restore = match app.state.get_backup_restore() {
+            Some(restore) if restore.user_id == self.user_id => restore,
+            _ => {
+                ui.spinner();
+                return;
+            }
+        };
+        ui.horizontal(|ui| {
+            if !restore.is_finished() {
+                ui.spinner();
+            }
+            ui.label(restore.stage.human_name());
+        });
+        restore.failed.iter().for_each(|failure| {
+            ui.colored_label(Color32::RED, failure);
+        });
+    }
+}
+
+impl PopupContent for BackupRestoreInput {
+    fn get_type(&self) -> PopupType {
+        PopupType::BackupRestore
+    }
+
+    fn init_frame(&mut self, _app: &CalendarApp, info: &mut ContentInfo) {
+        if let Some(Err(error)) = &self.backup {
+            info.error(true, error);
+        }
+    }
+
+    fn get_title(&mut self) -> Option<String> {
+        Some("Restore Backup".to_owned())
+    }
+
+    fn show_content(&mut self, app: &CalendarApp, ui: &mut egui::Ui, _info: &mut ContentInfo) {
+        ui.vertical(|ui| {
+            if self.is_started {
+                self.show_progress(app, ui);
+                return;
+            }
+            let text = ui.add(
+                TextEdit::multiline(&mut self.text)
+                    .code_editor()
+                    .hint_text("Paste the backup")
+                    .desired_rows(8)
+                    .desired_width(f32::INFINITY),
+            );
+            if text.changed() {
+                self.backup =
+                    (!self.text.trim().is_empty()).then(|| CalendarBackup::from_json(&self.text));
+            }
+            if let Some(Ok(backup)) = &self.backup {
+                self.show_summary(app, ui, backup);
+            }
+        });
+    }
+
+    fn show_buttons(&mut self, app: &CalendarApp, ui: &mut egui::Ui, info: &mut ContentInfo) {
+        // Restore goes on after the popup is closed
+        if self.is_started {
+            if ui.button("Close").clicked() {
+                info.close();
+            }
+            return;
+        }
+
+        let capabilities = app.state.get_capabilities(self.user_id);
+        let can_restore = [
+            CapabilityTable::Events,
+            CapabilityTable::EventTemplates,
+            CapabilityTable::Schedules,
+        ]
+        .into_iter()
+        .try_for_each(|table| capabilities.table(table, CapabilityAction::Create));
+        let is_busy = app
+            .state
+            .get_backup_restore()
+            .is_some_and(|restore| !restore.is_finished());
+        if ui
+            .add_enabled_permitted(
+                matches!(self.backup, Some(Ok(_))) && !is_busy,
+                can_restore,
+                egui::Button::new("Restore"),
+            )
+            .clicked()
+        {
+            if let Some(Ok(backup)) = self.backup.take() {
+                CalendarApp::request_restore(ui.ctx(), self.user_id, backup);
+                self.is_started = true;
+            }
+        }
+        if ui.button("Cancel").clicked() {
+            info.close();
+        }
+    }
+}
@@ -5,60 +5,295 @@ use super::{
 use crate::{
     app::CalendarApp,
     db::request::RequestIdentifier,
-    state::{custom_requests::ChangeAccessLevelsRequest, state_table::StateTable},
-    tables::DbTable,
-    ui::utils::DirectionSymbol,
+    state::{
+        capabilities::{CapabilityAction, CapabilityTable},
+        custom_requests::{AccessLevelReassign, ChangeAccessLevelsRequest},
+        state_table::StateTable,
+    },
+    tables::{DbTable, DbTableGetById},
+    ui::utils::{DirectionSymbol, UiUtils},
 };
 use calendar_lib::api::{
     auth::types::{AccessLevel, AccessLevelChange},
     utils::*,
 };
-use egui::{Align, Button, Direction, Layout, TextEdit, TextStyle, Vec2};
+use egui::{Align, Button, Color32, Direction, Layout, RichText, TextEdit, TextStyle, Vec2};
 use itertools::Itertools;
 use std::hash::Hash;
 
 #[derive(Debug, Clone)]
 struct AccessLevelData {
+    /// Identifies the level in the popup, new levels don't have an id yet
+    key: usize,
     origin: AccessLevel,
     name: String,
     deleted: bool,
+    /// Key of the level, that gets the items of this level once it's deleted
+    move_to: Option<usize>,
 }
 
 impl AccessLevelData {
-    fn new(origin: AccessLevel) -> Self {
+    fn new(key: usize, origin: AccessLevel) -> Self {
         Self {
+            key,
             name: origin.name.clone(),
             origin: origin,
             deleted: false,
+            move_to: None,
         }
     }
+
+    fn is_new(&self) -> bool {
+        self.origin.id == -1
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+struct LevelItems {
+    events: usize,
+    schedules: usize,
+    event_templates: usize,
+}
+
+impl LevelItems {
+    fn total(&self) -> usize {
+        self.events + self.schedules + self.event_templates
+    }
 }
 
 pub struct ChangeAccessLevelsPopup {
-    #[allow(dead_code)]
     eid: egui::Id,
     pub user_id: TableId,
 
     access_levels: Vec<AccessLevelData>,
+    next_key: usize,
 
     update_request: Option<RequestIdentifier<ChangeAccessLevelsRequest>>,
 }
 
 impl ChangeAccessLevelsPopup {
     pub fn new(eid: impl Hash, user_id: TableId, table: &StateTable<AccessLevel>) -> Self {
+        let access_levels = table
+            .get_table()
+            .get()
+            .iter()
+            .sorted_by_key(|al| -al.level)
+            .enumerate()
+            .map(|(key, al)| AccessLevelData::new(key, al.clone()))
+            .collect_vec();
         Self {
             eid: egui::Id::new(eid),
             user_id,
-            access_levels: table
+            next_key: access_levels.len(),
+            access_levels,
+            update_request: None,
+        }
+    }
+
+    fn get_new_level(&self, key: usize) -> Option<i32> {
+        self.access_levels
+            .iter()
+            .filter(|al| !al.deleted)
+            .position(|al| al.key == key)
+            .map(|i| AccessLevel::MAX_LEVEL - i as i32)
+    }
+
+    fn get_name(&self, key: usize) -> &str {
+        self.access_levels
+            .iter()
+            .find(|al| al.key == key)
+            .map_or("", |al| al.name.as_str())
+    }
+
+    /// Level, that items of an existing level will have after the change
+    fn remap_level(&self, old_level: i32) -> Option<i32> {
+        let al = self
+            .access_levels
+            .iter()
+            .find(|al| !al.is_new() && al.origin.level == old_level)?;
+        if al.deleted {
+            al.move_to.and_then(|key| self.get_new_level(key))
+        } else {
+            self.get_new_level(al.key)
+        }
+    }
+
+    /// Items of a deleted level go to the closest level below it, or above, if there is none
+    fn default_move_to(&self, index: usize) -> Option<usize> {
+        self.access_levels[index + 1..]
+            .iter()
+            .chain(self.access_levels[..index].iter().rev())
+            .find(|al| !al.deleted)
+            .map(|al| al.key)
+    }
+
+    fn make_changes(&self) -> (Vec<AccessLevelChange>, Vec<AccessLevelReassign>) {
+        let changes = self
+            .access_levels
+            .iter()
+            .filter(|al| !al.deleted)
+            .enumerate()
+            .map(|(i, al)| AccessLevelChange {
+                id: al.origin.id,
+                old_level: al.origin.level,
+                new_level: AccessLevel::MAX_LEVEL - i as i32,
+                name: al.name.clone(),
+            })
+            .collect();
+        let reassign = self
+            .access_levels
+            .iter()
+            .filter(|al| al.deleted)
+            .filter_map(|al| {
+                self.remap_level(al.origin.level)
+                    .map(|new_level| AccessLevelReassign {
+                        old_level: al.origin.level,
+                        new_level,
+                    })
+            })
+            .collect();
+        (changes, reassign)
+    }
+
+    fn count_items(app: &CalendarApp, user_id: TableId, level: i32) -> LevelItems {
+        let user_state = app.state.get_user_state(user_id);
+        LevelItems {
+            events: user_state
+                .events
                 .get_table()
                 .get()
                 .iter()
-                .sorted_by_key(|al| -al.level)
-                .map(|al| AccessLevelData::new(al.clone()))
-                .collect(),
-            update_request: None,
+                .filter(|e| e.access_level == level)
+                .count(),
+            schedules: user_state
+                .schedules
+                .get_table()
+                .get()
+                .iter()
+                .filter(|s| s.access_level == level)
+                .count(),
+            event_templates: user_state
+                .event_templates
+                .get_table()
+                .get()
+                .iter()
+                .filter(|t| t.access_level == level)
+                .count(),
         }
     }
+
+    fn show_preview(&self, app: &CalendarApp, ui: &mut egui::Ui) {
+        let mut changes = vec![];
+        self.access_levels.iter().for_each(|al| {
+            if al.is_new() {
+                if !al.deleted {
+                    changes.push(format!("'{}' is added", al.name));
+                }
+                return;
+            }
+            let items = Self::count_items(app, self.user_id, al.origin.level).total();
+            if al.deleted {
+                match al.move_to {
+                    Some(key) => changes.push(format!(
+                        "'{}' is deleted, {items} items move to '{}'",
+                        al.origin.name,
+                        self.get_name(key)
+                    )),
+                    None => changes.push(format!("'{}' is deleted", al.origin.name)),
+                }
+                return;
+            }
+            if al.name != al.origin.name {
+                changes.push(format!("'{}' is renamed to '{}'", al.origin.name, al.name));
+            }
+            if let Some(new_level) = self.get_new_level(al.key) {
+                if new_level != al.origin.level {
+                    changes.push(format!(
+                        "'{}' moves from level {} to {new_level}, with {items} items",
+                        al.name, al.origin.level
+                    ));
+                }
+            }
+        });
+
+        ui.label(RichText::new("Changes").strong());
+        if changes.is_empty() {
+            ui.label("No changes");
+        }
+        changes.into_iter().for_each(|change| {
+            ui.label(change);
+        });
+    }
+
+    /// Shares keep their level, so moving a level under a share makes its items visible
+    fn show_exposure_warnings(&self, app: &CalendarApp, ui: &mut egui::Ui) {
+        let user_state = app.state.get_user_state(self.user_id);
+        let item_levels = user_state
+            .events
+            .get_table()
+            .get()
+            .iter()
+            .map(|e| e.access_level)
+            .chain(
+                user_state
+                    .schedules
+                    .get_table()
+                    .get()
+                    .iter()
+                    .map(|s| s.access_level),
+            )
+            .chain(
+                user_state
+                    .event_templates
+                    .get_table()
+                    .get()
+                    .iter()
+                    .map(|t| t.access_level),
+            )
+            .collect_vec();
+        let count_exposed = |share_level: i32| {
+            item_levels
+                .iter()
+                .filter(|&&level| {
+                    level > share_level && self.remap_level(level).unwrap_or(level) <= share_level
+                })
+                .count()
+        };
+
+        let shares = user_state
+            .granted_permissions
+            .get_table()
+            .get()
+            .iter()
+            .filter(|gp| gp.giver_user_id == self.user_id)
+            .map(|gp| {
+                let name = user_state
+                    .users
+                    .get_table()
+                    .get_by_id(gp.receiver_user_id)
+                    .map_or_else(|| "Unknown user".to_owned(), |user| user.name.clone());
+                (name, gp.permissions.access_level)
+            })
+            .chain(
+                user_state
+                    .share_links
+                    .get_table()
+                    .get()
+                    .iter()
+                    .map(|link| ("Share link".to_owned(), link.access_level)),
+            );
+        shares.for_each(|(name, share_level)| {
+            let exposed = count_exposed(share_level);
+            if exposed > 0 {
+                ui.label(
+                    RichText::new(format!(
+                        "{name} will see {exposed} items, that are hidden from them now"
+                    ))
+                    .color(Color32::YELLOW),
+                );
+            }
+        });
+    }
 }
 
 impl PopupContent for ChangeAccessLevelsPopup {
@@ -81,13 +316,14 @@ impl PopupContent for ChangeAccessLevelsPopup {
         Some("Change Access Levels".to_owned())
     }
 
-    fn show_content(&mut self, _app: &CalendarApp, ui: &mut egui::Ui, info: &mut ContentInfo) {
+    fn show_content(&mut self, app: &CalendarApp, ui: &mut egui::Ui, info: &mut ContentInfo) {
         let access_levels_count = self.access_levels.len();
 
         let mut move_up = None;
         let mut move_down = None;
         let mut add_after = None;
         let mut delete_at = None;
+        let mut move_to = None;
 
         ui.vertical(|ui| {
             ui.horizontal(|ui| {
@@ -172,13 +408,66 @@ impl PopupContent for ChangeAccessLevelsPopup {
                         );
                     });
                 });
+
+                ui.vertical(|ui| {
+                    self.access_levels.iter().enumerate().for_each(|(i, al)| {
+                        ui.allocate_ui_with_layout(
+                            Vec2::new(0., height_per_item),
+                            Layout::left_to_right(Align::Center),
+                            |ui| {
+                                if al.deleted {
+                                    let mut target = al.move_to;
+                                    egui::ComboBox::from_id_source(self.eid.with(("move_to", i)))
+                                        .selected_text(format!(
+                                            "Move to {}",
+                                            target.map_or("", |key| self.get_name(key))
+                                        ))
+                                        .show_ui(ui, |ui| {
+                                            self.access_levels
+                                                .iter()
+                                                .filter(|level| !level.deleted)
+                                                .for_each(|target_al| {
+                                                    ui.selectable_value(
+                                                        &mut target,
+                                                        Some(target_al.key),
+                                                        &target_al.name,
+                                                    );
+                                                });
+                                        });
+                                    if target != al.move_to {
+                                        move_to = Some((i, target));
+                                    }
+                                } else if !al.is_new() {
+                                    let items =
+                                        Self::count_items(app, self.user_id, al.origin.level);
+                                    ui.label(format!(
+                                        "E {} S {} T {}",
+                                        items.events, items.schedules, items.event_templates
+                                    ))
+                                    .on_hover_text("Events / Schedules / Templates");
+                                }
+                            },
+                        );
+                    });
+                });
             });
+
+            ui.separator();
+            self.show_preview(app, ui);
+            self.show_exposure_warnings(app, ui);
         });
 
         info.error(
             self.access_levels.iter().any(|al| al.name.is_empty()),
             "Name cannot be empty",
         );
+        info.error(
+            self.access_levels
+                .iter()
+                .filter(|al| al.deleted && !al.is_new())
+                .any(|al| self.remap_level(al.origin.level).is_none()),
+            "Choose where to move items of deleted levels",
+        );
 
         if let Some(move_up) = move_up {
             self.access_levels.swap(move_up - 1, move_up);
@@ -189,43 +478,50 @@ impl PopupContent for ChangeAccessLevelsPopup {
         if let Some(add_after) = add_after {
             self.access_levels.insert(
                 add_after + 1,
-                AccessLevelData::new(AccessLevel {
-                    id: -1,
-                    user_id: self.user_id,
-                    level: 0,
-                    name: "".to_owned(),
-                }),
+                AccessLevelData::new(
+                    self.next_key,
+                    AccessLevel {
+                        id: -1,
+                        user_id: self.user_id,
+                        level: 0,
+                        name: "".to_owned(),
+                    },
+                ),
             );
+            self.next_key += 1;
         }
         if let Some(delete_at) = delete_at {
-            if self.access_levels[delete_at].origin.id == -1 {
+            if self.access_levels[delete_at].is_new() {
                 self.access_levels.remove(delete_at);
+            } else if self.access_levels[delete_at].deleted {
+                self.access_levels[delete_at].deleted = false;
+                self.access_levels[delete_at].move_to = None;
             } else {
                 self.access_levels[delete_at].deleted = true;
+                self.access_levels[delete_at].move_to = self.default_move_to(delete_at);
             }
         }
+        if let Some((index, target)) = move_to {
+            self.access_levels[index].move_to = target;
+        }
     }
 
     fn show_buttons(&mut self, app: &CalendarApp, ui: &mut egui::Ui, info: &mut ContentInfo) {
         if ui
-            .add_enabled(self.update_request.is_none(), Button::new("Save"))
+            .add_enabled_permitted(
+                self.update_request.is_none(),
+                app.state
+                    .get_capabilities(self.user_id)
+                    .table(CapabilityTable::AccessLevels, CapabilityAction::Edit),
+                Button::new("Save"),
+            )
             .clicked()
         {
+            let (changes, reassign) = self.make_changes();
             self.update_request = Some(
-                app.state.get_user_state(self.user_id).change_access_levels(
-                    self.access_levels
-                        .iter()
-                        .filter(|al| !al.deleted)
-                        .cloned()
-                        .enumerate()
-                        .map(|(i, al)| AccessLevelChange {
-                            id: al.origin.id,
-                            old_level: al.origin.level,
-                            new_level: AccessLevel::MAX_LEVEL - i as i32,
-                            name: al.name,
-                        })
-                        .collect(),
-                ),
+                app.state
+                    .get_user_state(self.user_id)
+                    .change_access_levels(changes, reassign),
             );
         }
         if ui.button("Cancel").clicked() {
@@ -5,12 +5,22 @@ use super::{
 use crate::{
     app::CalendarApp,
     db::request::RequestIdentifier,
-    state::table_requests::{TableInsertRequest, TableUpdateRequest},
+    state::{
+        capabilities::{CapabilityAction, CapabilityTable},
+        state_table::StateTable,
+        table_requests::TableInsertRequest,
+    },
     tables::DbTable,
     ui::{
-        access_level_picker::AccessLevelPicker, event_visibility_picker::EventVisibilityPicker,
+        access_level_picker::AccessLevelPicker,
+        conflict_resolver::{
+            find_conflict, ConflictEdit, ConflictEditor, ConflictResolver, FieldConflict,
+        },
+        event_visibility_picker::EventVisibilityPicker,
         time_picker::TimePicker,
+        utils::UiUtils,
     },
+    utils::{access_levels_human_name, event_visibility_human_name},
 };
 use calendar_lib::api::{events::types::*, utils::*};
 use chrono::{Duration, Local, NaiveDate, NaiveDateTime, NaiveTime};
@@ -18,6 +28,25 @@ use egui::TextEdit;
 use egui_extras::DatePickerButton;
 use std::hash::Hash;
 
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum EventField {
+    Name,
+    Description,
+    AccessLevel,
+    Visibility,
+    Time,
+}
+
+impl EventField {
+    const ALL: [EventField; 5] = [
+        EventField::Name,
+        EventField::Description,
+        EventField::AccessLevel,
+        EventField::Visibility,
+        EventField::Time,
+    ];
+}
+
 pub struct EventInput {
     eid: egui::Id,
     pub orig_name: String,
@@ -33,7 +62,8 @@ pub struct EventInput {
     pub start: NaiveTime,
     pub end: NaiveTime,
 
-    update_request: Option<RequestIdentifier<TableUpdateRequest<Event>>>,
+    /// Changes of an existing event
+    edit: Option<ConflictEdit<Event, EventField>>,
     insert_request: Option<RequestIdentifier<TableInsertRequest<Event>>>,
 }
 
@@ -52,7 +82,7 @@ impl EventInput {
             date: now.date(),
             start: now.time(),
             end: now.time() + Duration::try_minutes(30).unwrap(),
-            update_request: None,
+            edit: None,
             insert_request: None,
         }
     }
@@ -70,10 +100,182 @@ impl EventInput {
             date: event.start.date(),
             start: event.start.time(),
             end: event.end.time(),
-            update_request: None,
+            edit: Some(ConflictEdit::new(event.clone())),
             insert_request: None,
         }
     }
+
+    /// New event with the fields of an existing one
+    pub fn duplicate(eid: impl Hash, event: &Event) -> Self {
+        Self {
+            name: event.name.clone(),
+            description: event.description.clone().unwrap_or_default(),
+            access_level: event.access_level,
+            visibility: event.visibility,
+            date: event.start.date(),
+            start: event.start.time(),
+            end: event.end.time(),
+            ..Self::new(eid, event.user_id)
+        }
+    }
+
+    fn make_event(&self, base: &Event) -> Event {
+        Event {
+            name: self.name.clone(),
+            description: (!self.description.is_empty()).then_some(self.description.clone()),
+            start: NaiveDateTime::new(self.date, self.start),
+            end: NaiveDateTime::new(self.date, self.end),
+            access_level: self.access_level,
+            visibility: self.visibility,
+            ..base.clone()
+        }
+    }
+}
+
+impl ConflictEditor for EventInput {
+    type Item = Event;
+    type Field = EventField;
+
+    const FIELDS: &'static [EventField] = &EventField::ALL;
+
+    fn get_edit(&mut self) -> &mut Option<ConflictEdit<Event, EventField>> {
+        &mut self.edit
+    }
+
+    fn get_state_table<'a>(&self, app: &'a CalendarApp) -> &'a StateTable<Event> {
+        &app.state.get_user_state(self.user_id).events
+    }
+
+    fn find_conflicts(
+        &self,
+        app: &CalendarApp,
+        base: &Event,
+        theirs: &Event,
+    ) -> Vec<FieldConflict<EventField>> {
+        let mine = self.make_event(base);
+        let access_levels = app
+            .state
+            .get_user_state(self.user_id)
+            .access_levels
+            .get_table()
+            .get();
+        [
+            find_conflict(
+                EventField::Name,
+                "Name",
+                &base.name,
+                &mine.name,
+                &theirs.name,
+                |name| name.clone(),
+            ),
+            find_conflict(
+                EventField::Description,
+                "Description",
+                &base.description,
+                &mine.description,
+                &theirs.description,
+                |description| description.clone().unwrap_or_default(),
+            ),
+            find_conflict(
+                EventField::AccessLevel,
+                "Access level",
+                &base.access_level,
+                &mine.access_level,
+                &theirs.access_level,
+                |level| access_levels_human_name(access_levels, *level),
+            ),
+            find_conflict(
+                EventField::Visibility,
+                "Visibility",
+                &base.visibility,
+                &mine.visibility,
+                &theirs.visibility,
+                |visibility| event_visibility_human_name(visibility).to_owned(),
+            ),
+            find_conflict(
+                EventField::Time,
+                "Time",
+                &(base.start, base.end),
+                &(mine.start, mine.end),
+                &(theirs.start, theirs.end),
+                |(start, end)| {
+                    format!(
+                        "{} - {}",
+                        start.format("%d.%m.%Y %H:%M"),
+                        end.format("%H:%M")
+                    )
+                },
+            ),
+        ]
+        .into_iter()
+        .flatten()
+        .collect()
+    }
+
+    fn is_changed(&self, field: EventField, base: &Event) -> bool {
+        let mine = self.make_event(base);
+        match field {
+            EventField::Name => mine.name != base.name,
+            EventField::Description => mine.description != base.description,
+            EventField::AccessLevel => mine.access_level != base.access_level,
+            EventField::Visibility => mine.visibility != base.visibility,
+            EventField::Time => (mine.start, mine.end) != (base.start, base.end),
+        }
+    }
+
+    fn take_theirs(&mut self, field: EventField, theirs: &Event) {
+        match field {
+            EventField::Name => self.name = theirs.name.clone(),
+            EventField::Description => {
+                self.description = theirs.description.clone().unwrap_or_default()
+            }
+            EventField::AccessLevel => self.access_level = theirs.access_level,
+            EventField::Visibility => self.visibility = theirs.visibility,
+            EventField::Time => {
+                self.date = theirs.start.date();
+                self.start = theirs.start.time();
+                self.end = theirs.end.time();
+            }
+        }
+    }
+
+    fn make_update(&self, id: TableId, base: &Event) -> UpdateEvent {
+        let mine = self.make_event(base);
+        UpdateEvent {
+            id,
+            name: if mine.name != base.name {
+                USome(mine.name)
+            } else {
+                UNone
+            },
+            description: if mine.description != base.description {
+                USome(mine.description)
+            } else {
+                UNone
+            },
+            start: if mine.start != base.start {
+                USome(mine.start)
+            } else {
+                UNone
+            },
+            end: if mine.end != base.end {
+                USome(mine.end)
+            } else {
+                UNone
+            },
+            access_level: if mine.access_level != base.access_level {
+                USome(mine.access_level)
+            } else {
+                UNone
+            },
+            visibility: if mine.visibility != base.visibility {
+                USome(mine.visibility)
+            } else {
+                UNone
+            },
+            plan_id: UNone,
+        }
+    }
 }
 
 impl PopupContent for EventInput {
@@ -86,18 +288,13 @@ impl PopupContent for EventInput {
     }
 
     fn init_frame(&mut self, app: &CalendarApp, info: &mut ContentInfo) {
-        if let Some(identifier) = self.update_request.as_ref() {
-            if let Some(response_info) = app.state.get_response(&identifier) {
-                self.update_request = None;
-                if !response_info.is_err() {
-                    info.close();
-                }
-            }
+        if self.update_edit(app) == Some(true) {
+            info.close();
         }
         if let Some(identifier) = self.insert_request.as_ref() {
-            if let Some(response_info) = app.state.get_response(&identifier) {
+            if let Some(succeeded) = app.state.get_request_result(identifier) {
                 self.insert_request = None;
-                if !response_info.is_err() {
+                if succeeded {
                     info.close();
                 }
             }
@@ -118,6 +315,13 @@ impl PopupContent for EventInput {
 
     fn show_content(&mut self, app: &CalendarApp, ui: &mut egui::Ui, info: &mut ContentInfo) {
         ui.vertical(|ui| {
+            if let Some(edit) = self.edit.as_mut().filter(|edit| !edit.conflicts.is_empty()) {
+                ui.add(ConflictResolver::new(
+                    self.eid.with("conflicts"),
+                    &mut edit.conflicts,
+                ));
+            }
+
             ui.add(TextEdit::singleline(&mut self.name).hint_text("Name"));
             ui.add(TextEdit::multiline(&mut self.description).hint_text("Description"));
 
@@ -152,33 +356,46 @@ impl PopupContent for EventInput {
 
             info.error(self.name.is_empty(), "Name cannot be empty");
             info.error(self.name.len() > 200, "Name is too long");
+            info.error(
+                self.edit.as_ref().is_some_and(|edit| edit.is_deleted()),
+                "Event was deleted by someone else",
+            );
         });
     }
 
     fn show_buttons(&mut self, app: &CalendarApp, ui: &mut egui::Ui, info: &mut ContentInfo) {
-        if let Some(id) = self.id {
+        let capabilities = app.state.get_capabilities(self.user_id);
+        if let Some(edit) = self.edit.as_ref() {
+            let label = if edit.conflicts.is_empty() {
+                "Save"
+            } else {
+                "Resolve"
+            };
             if ui
-                .add_enabled(!info.is_error(), egui::Button::new("Save"))
+                .add_enabled_permitted(
+                    !info.is_error(),
+                    capabilities.item(
+                        CapabilityTable::Events,
+                        CapabilityAction::Edit,
+                        self.access_level,
+                    ),
+                    egui::Button::new(label),
+                )
                 .clicked()
             {
-                self.update_request = Some(app.state.get_user_state(self.user_id).events.update(
-                    UpdateEvent {
-                        id,
-                        name: USome(self.name.clone()),
-                        description: USome(
-                            (!self.description.is_empty()).then_some(self.description.clone()),
-                        ),
-                        start: USome(NaiveDateTime::new(self.date, self.start)),
-                        end: USome(NaiveDateTime::new(self.date, self.end)),
-                        access_level: USome(self.access_level),
-                        visibility: USome(self.visibility),
-                        plan_id: UNone,
-                    },
-                ));
+                self.save_edit(app);
             }
         } else {
             if ui
-                .add_enabled(!info.is_error(), egui::Button::new("Create"))
+                .add_enabled_permitted(
+                    !info.is_error(),
+                    capabilities.item(
+                        CapabilityTable::Events,
+                        CapabilityAction::Create,
+                        self.access_level,
+                    ),
+                    egui::Button::new("Create"),
+                )
                 .clicked()
             {
                 self.insert_request = Some(app.state.get_user_state(self.user_id).events.insert(
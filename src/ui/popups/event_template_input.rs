@@ -5,14 +5,45 @@ use super::{
 use crate::{
     app::CalendarApp,
     db::request::RequestIdentifier,
-    state::table_requests::{TableInsertRequest, TableUpdateRequest},
+    state::{
+        capabilities::{CapabilityAction, CapabilityTable},
+        state_table::StateTable,
+        table_requests::TableInsertRequest,
+    },
     tables::DbTable,
-    ui::{access_level_picker::AccessLevelPicker, time_picker::TimePicker},
+    ui::{
+        access_level_picker::AccessLevelPicker,
+        conflict_resolver::{
+            find_conflict, ConflictEdit, ConflictEditor, ConflictResolver, FieldConflict,
+        },
+        time_picker::TimePicker,
+        utils::UiUtils,
+    },
+    utils::access_levels_human_name,
 };
-use calendar_lib::api::{event_templates::types::*, utils::*};
+use calendar_lib::api::{event_templates::types::*, events::types::Event, utils::*};
 use chrono::NaiveTime;
 use egui::TextEdit;
-use std::hash::Hash;
+use std::{hash::Hash, time::Duration};
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum EventTemplateField {
+    Name,
+    EventName,
+    EventDescription,
+    Duration,
+    AccessLevel,
+}
+
+impl EventTemplateField {
+    const ALL: [EventTemplateField; 5] = [
+        EventTemplateField::Name,
+        EventTemplateField::EventName,
+        EventTemplateField::EventDescription,
+        EventTemplateField::Duration,
+        EventTemplateField::AccessLevel,
+    ];
+}
 
 pub struct EventTemplateInput {
     eid: egui::Id,
@@ -26,7 +57,8 @@ pub struct EventTemplateInput {
     pub duration: NaiveTime,
     pub access_level: i32,
 
-    update_request: Option<RequestIdentifier<TableUpdateRequest<EventTemplate>>>,
+    /// Changes of an existing template
+    edit: Option<ConflictEdit<EventTemplate, EventTemplateField>>,
     insert_request: Option<RequestIdentifier<TableInsertRequest<EventTemplate>>>,
 }
 
@@ -42,13 +74,12 @@ impl EventTemplateInput {
             event_description: String::default(),
             duration: NaiveTime::from_hms_opt(0, 30, 0).unwrap(),
             access_level: -1,
-            update_request: None,
+            edit: None,
             insert_request: None,
         }
     }
 
     pub fn change(eid: impl Hash, template: &EventTemplate) -> Self {
-        let duration_minutes = template.duration.as_secs() as u32 / 60;
         Self {
             eid: egui::Id::new(eid),
             orig_name: template.name.clone(),
@@ -57,13 +88,177 @@ impl EventTemplateInput {
             name: template.name.clone(),
             event_name: template.event_name.clone(),
             event_description: template.event_description.clone().unwrap_or_default(),
-            duration: NaiveTime::from_hms_opt(duration_minutes / 60, duration_minutes % 60, 0)
-                .unwrap(),
+            duration: Self::duration_to_time(template.duration),
             access_level: template.access_level,
-            update_request: None,
+            edit: Some(ConflictEdit::new(template.clone())),
             insert_request: None,
         }
     }
+
+    pub fn from_event(eid: impl Hash, event: &Event) -> Self {
+        Self {
+            name: event.name.clone(),
+            event_name: event.name.clone(),
+            event_description: event.description.clone().unwrap_or_default(),
+            duration: Self::duration_to_time(
+                (event.end - event.start).to_std().unwrap_or_default(),
+            ),
+            access_level: event.access_level,
+            ..Self::new(eid, event.user_id)
+        }
+    }
+
+    /// Durations of a day and longer don't fit into the picker, so they are clamped to 23:59
+    fn duration_to_time(duration: Duration) -> NaiveTime {
+        let duration_minutes = (duration.as_secs() / 60).min(24 * 60 - 1) as u32;
+        NaiveTime::from_hms_opt(duration_minutes / 60, duration_minutes % 60, 0).unwrap()
+    }
+
+    fn get_duration(&self) -> Duration {
+        self.duration
+            .signed_duration_since(NaiveTime::default())
+            .to_std()
+            .unwrap()
+    }
+}
+
+impl ConflictEditor for EventTemplateInput {
+    type Item = EventTemplate;
+    type Field = EventTemplateField;
+
+    const FIELDS: &'static [EventTemplateField] = &EventTemplateField::ALL;
+
+    fn get_edit(&mut self) -> &mut Option<ConflictEdit<EventTemplate, EventTemplateField>> {
+        &mut self.edit
+    }
+
+    fn get_state_table<'a>(&self, app: &'a CalendarApp) -> &'a StateTable<EventTemplate> {
+        &app.state.get_user_state(self.user_id).event_templates
+    }
+
+    fn find_conflicts(
+        &self,
+        app: &CalendarApp,
+        base: &EventTemplate,
+        theirs: &EventTemplate,
+    ) -> Vec<FieldConflict<EventTemplateField>> {
+        let access_levels = app
+            .state
+            .get_user_state(self.user_id)
+            .access_levels
+            .get_table()
+            .get();
+        let event_description =
+            (!self.event_description.is_empty()).then_some(self.event_description.clone());
+        [
+            find_conflict(
+                EventTemplateField::Name,
+                "Template name",
+                &base.name,
+                &self.name,
+                &theirs.name,
+                |name| name.clone(),
+            ),
+            find_conflict(
+                EventTemplateField::EventName,
+                "Name",
+                &base.event_name,
+                &self.event_name,
+                &theirs.event_name,
+                |name| name.clone(),
+            ),
+            find_conflict(
+                EventTemplateField::EventDescription,
+                "Description",
+                &base.event_description,
+                &event_description,
+                &theirs.event_description,
+                |description| description.clone().unwrap_or_default(),
+            ),
+            find_conflict(
+                EventTemplateField::Duration,
+                "Duration",
+                &base.duration,
+                &self.get_duration(),
+                &theirs.duration,
+                |duration| {
+                    Self::duration_to_time(*duration)
+                        .format("%H:%M")
+                        .to_string()
+                },
+            ),
+            find_conflict(
+                EventTemplateField::AccessLevel,
+                "Access level",
+                &base.access_level,
+                &self.access_level,
+                &theirs.access_level,
+                |level| access_levels_human_name(access_levels, *level),
+            ),
+        ]
+        .into_iter()
+        .flatten()
+        .collect()
+    }
+
+    fn is_changed(&self, field: EventTemplateField, base: &EventTemplate) -> bool {
+        match field {
+            EventTemplateField::Name => self.name != base.name,
+            EventTemplateField::EventName => self.event_name != base.event_name,
+            EventTemplateField::EventDescription => {
+                (!self.event_description.is_empty()).then_some(&self.event_description)
+                    != base.event_description.as_ref()
+            }
+            EventTemplateField::Duration => self.get_duration() != base.duration,
+            EventTemplateField::AccessLevel => self.access_level != base.access_level,
+        }
+    }
+
+    fn take_theirs(&mut self, field: EventTemplateField, theirs: &EventTemplate) {
+        match field {
+            EventTemplateField::Name => self.name = theirs.name.clone(),
+            EventTemplateField::EventName => self.event_name = theirs.event_name.clone(),
+            EventTemplateField::EventDescription => {
+                self.event_description = theirs.event_description.clone().unwrap_or_default()
+            }
+            EventTemplateField::Duration => self.duration = Self::duration_to_time(theirs.duration),
+            EventTemplateField::AccessLevel => self.access_level = theirs.access_level,
+        }
+    }
+
+    fn make_update(&self, id: TableId, base: &EventTemplate) -> UpdateEventTemplate {
+        let event_description =
+            (!self.event_description.is_empty()).then_some(self.event_description.clone());
+        let duration = self.get_duration();
+        UpdateEventTemplate {
+            id,
+            name: if self.name != base.name {
+                USome(self.name.clone())
+            } else {
+                UNone
+            },
+            event_name: if self.event_name != base.event_name {
+                USome(self.event_name.clone())
+            } else {
+                UNone
+            },
+            event_description: if event_description != base.event_description {
+                USome(event_description)
+            } else {
+                UNone
+            },
+            duration: if duration != base.duration {
+                USome(duration)
+            } else {
+                UNone
+            },
+            access_level: if self.access_level != base.access_level {
+                USome(self.access_level)
+            } else {
+                UNone
+            },
+        }
+    }
 }
 
 impl PopupContent for EventTemplateInput {
@@ -76,18 +271,13 @@ impl PopupContent for EventTemplateInput {
     }
 
     fn init_frame(&mut self, app: &CalendarApp, info: &mut ContentInfo) {
-        if let Some(identifier) = self.update_request.as_ref() {
-            if let Some(response_info) = app.state.get_response(&identifier) {
-                self.update_request = None;
-                if !response_info.is_err() {
-                    info.close();
-                }
-            }
+        if self.update_edit(app) == Some(true) {
+            info.close();
         }
         if let Some(identifier) = self.insert_request.as_ref() {
-            if let Some(response_info) = app.state.get_response(&identifier) {
+            if let Some(succeeded) = app.state.get_request_result(identifier) {
                 self.insert_request = None;
-                if !response_info.is_err() {
+                if succeeded {
                     info.close();
                 }
             }
@@ -108,6 +298,13 @@ impl PopupContent for EventTemplateInput {
 
     fn show_content(&mut self, app: &CalendarApp, ui: &mut egui::Ui, info: &mut ContentInfo) {
         ui.vertical(|ui| {
+            if let Some(edit) = self.edit.as_mut().filter(|edit| !edit.conflicts.is_empty()) {
+                ui.add(ConflictResolver::new(
+                    self.eid.with("conflicts"),
+                    &mut edit.conflicts,
+                ));
+            }
+
             ui.add(TextEdit::singleline(&mut self.name).hint_text("Template name"));
             ui.separator();
 
@@ -139,40 +336,46 @@ impl PopupContent for EventTemplateInput {
             info.error(self.name.len() > 200, "Name is too long");
             info.error(self.event_name.is_empty(), "Event name cannot be empty");
             info.error(self.event_name.len() > 200, "Event name is too long");
+            info.error(
+                self.edit.as_ref().is_some_and(|edit| edit.is_deleted()),
+                "Template was deleted by someone else",
+            );
         });
     }
 
     fn show_buttons(&mut self, app: &CalendarApp, ui: &mut egui::Ui, info: &mut ContentInfo) {
-        if let Some(id) = self.id {
+        let capabilities = app.state.get_capabilities(self.user_id);
+        if let Some(edit) = self.edit.as_ref() {
+            let label = if edit.conflicts.is_empty() {
+                "Update"
+            } else {
+                "Resolve"
+            };
             if ui
-                .add_enabled(!info.is_error(), egui::Button::new("Update"))
+                .add_enabled_permitted(
+                    !info.is_error(),
+                    capabilities.item(
+                        CapabilityTable::EventTemplates,
+                        CapabilityAction::Edit,
+                        self.access_level,
+                    ),
+                    egui::Button::new(label),
+                )
                 .clicked()
             {
-                self.update_request = Some(
-                    app.state
-                        .get_user_state(self.user_id)
-                        .event_templates
-                        .update(UpdateEventTemplate {
-                            id,
-                            name: USome(self.name.clone()),
-                            event_name: USome(self.event_name.clone()),
-                            event_description: USome(
-                                (!self.event_description.is_empty())
-                                    .then_some(self.event_description.clone()),
-                            ),
-                            duration: USome(
-                                self.duration
-                                    .signed_duration_since(NaiveTime::default())
-                                    .to_std()
-                                    .unwrap(),
-                            ),
-                            access_level: USome(self.access_level),
-                        }),
-                );
+                self.save_edit(app);
             }
         } else {
             if ui
-                .add_enabled(!info.is_error(), egui::Button::new("Create"))
+                .add_enabled_permitted(
+                    !info.is_error(),
+                    capabilities.item(
+                        CapabilityTable::EventTemplates,
+                        CapabilityAction::Create,
+                        self.access_level,
+                    ),
+                    egui::Button::new("Create"),
+                )
                 .clicked()
             {
                 self.insert_request = Some(
@@ -185,11 +388,7 @@ impl PopupContent for EventTemplateInput {
                             event_name: self.event_name.clone(),
                             event_description: (!self.event_description.is_empty())
                                 .then_some(self.event_description.clone()),
-                            duration: self
-                                .duration
-                                .signed_duration_since(NaiveTime::default())
-                                .to_std()
-                                .unwrap(),
+                            duration: self.get_duration(),
                             access_level: self.access_level,
                         }),
                 );
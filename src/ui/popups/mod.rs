@@ -1,11 +1,20 @@
+pub mod admin_password_reset;
+pub mod admin_user_confirm;
+pub mod admin_user_input;
+pub mod backup_restore;
 pub mod change_access_levels;
 pub mod event_input;
 pub mod event_template_input;
 pub mod login;
 pub mod permission_input;
+pub mod plan_exception_input;
 pub mod popup;
 pub mod popup_content;
 pub mod popup_manager;
 pub mod profile;
 pub mod schedule_input;
+pub mod share_expiry_reminder;
+pub mod share_link_input;
+pub mod share_link_revoke;
 pub mod sign_up;
+pub mod table_import;
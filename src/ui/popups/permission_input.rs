@@ -3,10 +3,16 @@ use super::{
     popup_content::{ContentInfo, PopupContent},
 };
 use crate::{
-    app::CalendarApp,
+    app::{preview::Preview, CalendarApp},
     db::request::RequestIdentifier,
-    state::table_requests::{TableInsertRequest, TableUpdateRequest},
-    tables::DbTable,
+    permission_presets::{PermissionFlags, PermissionPreset},
+    preferences::Preferences,
+    state::table_requests::{TableInsertRequest, TableUpdateRequest, UpdateRejection},
+    tables::{
+        share_validities::{ShareValidity, ShareWithValidity},
+        DbTable,
+    },
+    ui::utils::UiUtils,
     utils::is_valid_email,
 };
 use calendar_lib::api::{
@@ -14,7 +20,9 @@ use calendar_lib::api::{
     permissions::{self, types::*},
     utils::*,
 };
+use chrono::{Days, Local, NaiveDate};
 use egui::Checkbox;
+use egui_extras::DatePickerButton;
 use itertools::Itertools;
 use std::hash::Hash;
 
@@ -28,14 +36,13 @@ pub struct PermissionInput {
     pub receiver_email: String,
 
     pub access_level: i32,
-    pub events_view: bool,
-    pub events_edit: bool,
-    pub event_templates_view: bool,
-    pub event_templates_edit: bool,
-    pub schedules_view: bool,
-    pub schedules_edit: bool,
-    pub sharing: bool,
-    pub access_levels_edit: bool,
+    pub flags: PermissionFlags,
+    new_preset_name: String,
+
+    pub valid_from_enabled: bool,
+    pub valid_from: NaiveDate,
+    pub valid_until_enabled: bool,
+    pub valid_until: NaiveDate,
 
     email_not_found: Option<String>,
     update_request: Option<RequestIdentifier<TableUpdateRequest<GrantedPermission>>>,
@@ -44,6 +51,7 @@ pub struct PermissionInput {
 
 impl PermissionInput {
     pub fn new(eid: impl Hash, giver_user_id: i32) -> Self {
+        let today = Local::now().naive_local().date();
         Self {
             eid: egui::Id::new(eid),
 
@@ -54,14 +62,13 @@ impl PermissionInput {
             id: None,
 
             access_level: AccessLevel::MAX_LEVEL,
-            events_view: false,
-            events_edit: false,
-            event_templates_view: false,
-            event_templates_edit: false,
-            schedules_view: false,
-            schedules_edit: false,
-            sharing: false,
-            access_levels_edit: false,
+            flags: PermissionFlags::default(),
+            new_preset_name: String::default(),
+
+            valid_from_enabled: false,
+            valid_from: today,
+            valid_until_enabled: false,
+            valid_until: today + Days::new(30),
 
             email_not_found: None,
             update_request: None,
@@ -69,7 +76,15 @@ impl PermissionInput {
         }
     }
 
-    pub fn change(eid: impl Hash, permissions: &GrantedPermission, user: &User) -> Self {
+    pub fn change(
+        eid: impl Hash,
+        permissions: &GrantedPermission,
+        user: &User,
+        validity: Option<&ShareValidity>,
+    ) -> Self {
+        let today = Local::now().naive_local().date();
+        let start = validity.and_then(|validity| validity.start);
+        let end = validity.and_then(|validity| validity.end);
         Self {
             eid: egui::Id::new(eid),
 
@@ -80,14 +95,13 @@ impl PermissionInput {
             id: Some(permissions.id),
 
             access_level: permissions.permissions.access_level,
-            events_view: permissions.permissions.events.view,
-            events_edit: permissions.permissions.events.edit,
-            event_templates_view: permissions.permissions.event_templates.view,
-            event_templates_edit: permissions.permissions.event_templates.edit,
-            schedules_view: permissions.permissions.schedules.view,
-            schedules_edit: permissions.permissions.schedules.edit,
-            sharing: permissions.permissions.allow_share,
-            access_levels_edit: permissions.permissions.access_levels.edit,
+            flags: PermissionFlags::from_permissions(&permissions.permissions),
+            new_preset_name: String::default(),
+
+            valid_from_enabled: start.is_some(),
+            valid_from: start.unwrap_or(today),
+            valid_until_enabled: end.is_some(),
+            valid_until: end.unwrap_or(today + Days::new(30)),
 
             email_not_found: None,
             update_request: None,
@@ -95,38 +109,94 @@ impl PermissionInput {
         }
     }
 
+    fn get_validity(&self) -> (Option<NaiveDate>, Option<NaiveDate>) {
+        (
+            self.valid_from_enabled.then_some(self.valid_from),
+            self.valid_until_enabled.then_some(self.valid_until),
+        )
+    }
+
     fn make_permissions(&self) -> Permissions {
-        Permissions {
-            access_level: self.access_level,
-            access_levels: TablePermissions {
-                view: self.access_levels_edit || self.sharing,
-                edit: self.access_levels_edit,
-                create: self.access_levels_edit,
-                delete: self.access_levels_edit,
-            },
-            events: TablePermissions {
-                view: self.events_view || self.events_edit,
-                edit: self.events_edit,
-                create: self.events_edit,
-                delete: self.events_edit,
-            },
-            event_templates: TablePermissions {
-                view: self.event_templates_view
-                    || self.event_templates_edit
-                    || self.schedules_view
-                    || self.schedules_edit,
-                edit: self.event_templates_edit,
-                create: self.event_templates_edit,
-                delete: self.event_templates_edit,
-            },
-            schedules: TablePermissions {
-                view: self.schedules_view || self.schedules_edit,
-                edit: self.schedules_edit,
-                create: self.schedules_edit,
-                delete: self.schedules_edit,
-            },
-            allow_share: self.sharing,
-        }
+        self.flags.make_permissions(self.access_level)
+    }
+
+    fn show_presets(&mut self, ui: &mut egui::Ui, edit_mode: bool) {
+        let presets = Preferences::get().get_permission_presets();
+        let builtin_count = PermissionPreset::builtin().len();
+
+        ui.add_enabled_ui(edit_mode, |ui| {
+            let current_preset = presets
+                .iter()
+                .find(|preset| preset.flags == self.flags)
+                .map_or("Custom", |preset| preset.name.as_str());
+            egui::ComboBox::new(self.eid.with("preset"), "Preset")
+                .selected_text(current_preset)
+                .show_ui(ui, |ui| {
+                    presets.iter().for_each(|preset| {
+                        ui.selectable_value(&mut self.flags, preset.flags, &preset.name);
+                    });
+                });
+
+            ui.horizontal(|ui| {
+                ui.add(
+                    egui::TextEdit::singleline(&mut self.new_preset_name)
+                        .desired_width(160.)
+                        .hint_text("Preset name"),
+                );
+                let name = self.new_preset_name.trim();
+                let can_save = !name.is_empty()
+                    && !presets[..builtin_count].iter().any(|p| p.name == name)
+                    && self.flags.find_inconsistency().is_none();
+                if ui
+                    .add_enabled(can_save, egui::Button::new("Save as preset"))
+                    .clicked()
+                {
+                    Preferences::get()
+                        .save_permission_preset(PermissionPreset::new(name, self.flags));
+                    self.new_preset_name.clear();
+                }
+            });
+        });
+
+        egui::CollapsingHeader::new("Compare presets")
+            .id_source(self.eid.with("compare_presets"))
+            .show(ui, |ui| {
+                egui::Grid::new(self.eid.with("presets_grid"))
+                    .striped(true)
+                    .show(ui, |ui| {
+                        ui.label("");
+                        presets.iter().for_each(|preset| {
+                            ui.strong(&preset.name);
+                        });
+                        ui.end_row();
+
+                        let described = presets
+                            .iter()
+                            .map(|preset| preset.flags.describe())
+                            .collect_vec();
+                        PermissionFlags::default()
+                            .describe()
+                            .iter()
+                            .enumerate()
+                            .for_each(|(i, (name, _))| {
+                                ui.label(*name);
+                                described.iter().for_each(|flags| {
+                                    ui.label(if flags[i].1 { "✔" } else { "-" });
+                                });
+                                ui.end_row();
+                            });
+
+                        ui.label("");
+                        presets.iter().enumerate().for_each(|(i, preset)| {
+                            if i < builtin_count {
+                                ui.label("");
+                            } else if ui.small_button("Delete").clicked() {
+                                Preferences::get().remove_permission_preset(&preset.name);
+                            }
+                        });
+                        ui.end_row();
+                    });
+            });
     }
 }
 
@@ -142,22 +212,32 @@ impl PopupContent for PermissionInput {
     fn init_frame(&mut self, app: &CalendarApp, info: &mut ContentInfo) {
         if let Some(identifier) = self.update_request.as_ref() {
             if let Some(response_info) = app.state.get_response(&identifier) {
+                let saved = response_info.is_ok();
                 match response_info {
-                    Ok(_) => info.close(),
-                    Err(err) => match *err {
-                        permissions::update::BadRequestResponse::NotFound => {}
-                        permissions::update::BadRequestResponse::UserEmailNotFound => {
+                    Ok(_) => {}
+                    Err(err) => match &*err {
+                        UpdateRejection::Stale(_)
+                        | UpdateRejection::Other(
+                            permissions::update::BadRequestResponse::NotFound,
+                        ) => {}
+                        UpdateRejection::Other(
+                            permissions::update::BadRequestResponse::UserEmailNotFound,
+                        ) => {
                             self.email_not_found = Some(identifier.info.info.1.clone());
                         }
                     },
                 }
                 self.update_request = None;
+                if saved {
+                    info.close();
+                }
             }
         }
         if let Some(identifier) = self.insert_request.as_ref() {
             if let Some(response_info) = app.state.get_response(&identifier) {
+                let saved = response_info.is_ok();
                 match response_info {
-                    Ok(_) => info.close(),
+                    Ok(_) => {}
                     Err(err) => match *err {
                         permissions::insert::BadRequestResponse::UserEmailNotFound => {
                             self.email_not_found = Some(identifier.info.info.clone());
@@ -165,6 +245,9 @@ impl PopupContent for PermissionInput {
                     },
                 }
                 self.insert_request = None;
+                if saved {
+                    info.close();
+                }
             }
         }
     }
@@ -180,8 +263,9 @@ impl PopupContent for PermissionInput {
     fn show_content(&mut self, app: &CalendarApp, ui: &mut egui::Ui, info: &mut ContentInfo) {
         let edit_mode = app
             .state
-            .get_user_permissions(self.giver_user_id)
-            .allow_share;
+            .get_capabilities(self.giver_user_id)
+            .share()
+            .is_ok();
         ui.vertical(|ui| {
             if self.id.is_none() {
                 ui.add(
@@ -211,7 +295,7 @@ impl PopupContent for PermissionInput {
                 .iter()
                 .find(|al| al.level == self.access_level)
                 .unwrap_or(access_levels.first().unwrap());
-            ui.add_enabled_ui(!self.access_levels_edit, |ui| {
+            ui.add_enabled_ui(!self.flags.access_levels_edit, |ui| {
                 egui::ComboBox::new(self.eid.with("access_level"), "Access Level")
                     .selected_text(&current_access_level.name)
                     .show_ui(ui, |ui| {
@@ -221,14 +305,37 @@ impl PopupContent for PermissionInput {
                     });
             });
 
-            let mut full_permissions = self.events_view
-                && self.events_edit
-                && self.event_templates_view
-                && self.event_templates_edit
-                && self.schedules_view
-                && self.schedules_edit
-                && self.sharing
-                && self.access_levels_edit;
+            egui::Grid::new(self.eid.with("validity_grid")).show(ui, |ui| {
+                ui.label("Valid from:");
+                ui.add_enabled(
+                    self.valid_from_enabled,
+                    DatePickerButton::new(&mut self.valid_from)
+                        .id_source("share_valid_from")
+                        .show_icon(false),
+                );
+                ui.checkbox(&mut self.valid_from_enabled, "");
+                ui.end_row();
+
+                ui.label("Valid until:");
+                ui.add_enabled(
+                    self.valid_until_enabled,
+                    DatePickerButton::new(&mut self.valid_until)
+                        .id_source("share_valid_until")
+                        .show_icon(false),
+                );
+                ui.checkbox(&mut self.valid_until_enabled, "");
+                ui.end_row();
+            });
+            info.error(
+                self.valid_from_enabled
+                    && self.valid_until_enabled
+                    && self.valid_from > self.valid_until,
+                "Share ends before it starts",
+            );
+
+            self.show_presets(ui, edit_mode);
+
+            let mut full_permissions = self.flags == PermissionFlags::CO_OWNER;
             if ui
                 .add_enabled(
                     edit_mode,
@@ -236,64 +343,45 @@ impl PopupContent for PermissionInput {
                 )
                 .clicked()
             {
-                self.events_view = full_permissions;
-                self.events_edit = full_permissions;
-                self.event_templates_view = full_permissions;
-                self.event_templates_edit = full_permissions;
-                self.schedules_view = full_permissions;
-                self.schedules_edit = full_permissions;
-                self.sharing = full_permissions;
-                self.access_levels_edit = full_permissions;
+                self.flags = if full_permissions {
+                    PermissionFlags::CO_OWNER
+                } else {
+                    PermissionFlags::default()
+                };
             }
 
             ui.heading("Events");
             ui.separator();
             ui.add_enabled(
-                edit_mode && !self.events_edit,
-                Checkbox::new(&mut self.events_view, "View Events"),
+                edit_mode,
+                Checkbox::new(&mut self.flags.events_view, "View Events"),
             );
             ui.add_enabled(
                 edit_mode,
-                Checkbox::new(&mut self.events_edit, "Edit Events"),
+                Checkbox::new(&mut self.flags.events_edit, "Edit Events"),
             );
-            if self.events_edit {
-                self.events_view = true;
-            }
 
             ui.heading("Event Templates");
             ui.separator();
             ui.add_enabled(
-                edit_mode
-                    && !self.event_templates_edit
-                    && !self.schedules_view
-                    && !self.schedules_edit,
-                Checkbox::new(&mut self.event_templates_view, "View Event Templates"),
+                edit_mode,
+                Checkbox::new(&mut self.flags.event_templates_view, "View Event Templates"),
             );
             ui.add_enabled(
                 edit_mode,
-                Checkbox::new(&mut self.event_templates_edit, "Edit Event Templates"),
+                Checkbox::new(&mut self.flags.event_templates_edit, "Edit Event Templates"),
             );
-            if self.event_templates_edit {
-                self.event_templates_view = true;
-            }
 
             ui.heading("Schedules");
             ui.separator();
             ui.add_enabled(
-                edit_mode && !self.schedules_edit,
-                Checkbox::new(&mut self.schedules_view, "View Schedules"),
+                edit_mode,
+                Checkbox::new(&mut self.flags.schedules_view, "View Schedules"),
             );
             ui.add_enabled(
                 edit_mode,
-                Checkbox::new(&mut self.schedules_edit, "Edit Schedules"),
+                Checkbox::new(&mut self.flags.schedules_edit, "Edit Schedules"),
             );
-            if self.schedules_view {
-                self.event_templates_view = true;
-            }
-            if self.schedules_edit {
-                self.event_templates_view = true;
-                self.schedules_view = true;
-            }
 
             ui.heading("Other");
             ui.separator();
@@ -301,26 +389,33 @@ impl PopupContent for PermissionInput {
                 edit_mode
                     // Can't revoke your own access
                     && self.receiver_user_id != app.state.get_me().id,
-                Checkbox::new(&mut self.sharing, "Manage Sharing"),
+                Checkbox::new(&mut self.flags.sharing, "Manage Sharing"),
             );
             if edit_mode && self.receiver_user_id == app.state.get_me().id {
-                self.sharing = true;
+                self.flags.sharing = true;
             }
             ui.add_enabled(
                 edit_mode,
-                Checkbox::new(&mut self.access_levels_edit, "Edit Access Levels"),
+                Checkbox::new(&mut self.flags.access_levels_edit, "Edit Access Levels"),
             );
-            if self.access_levels_edit {
+            if self.flags.access_levels_edit {
                 self.access_level = AccessLevel::MAX_LEVEL;
             }
+
+            // Flags are not corrected on their own, so the giver sees what is wrong
+            if let Some(inconsistency) = self.flags.find_inconsistency() {
+                info.error(true, inconsistency);
+            }
         });
     }
 
     fn show_buttons(&mut self, app: &CalendarApp, ui: &mut egui::Ui, info: &mut ContentInfo) {
+        let can_share = app.state.get_capabilities(self.giver_user_id).share();
         if let Some(id) = self.id {
             if ui
-                .add_enabled(
+                .add_enabled_permitted(
                     self.update_request.is_none() && !info.is_error(),
+                    can_share,
                     egui::Button::new("Update"),
                 )
                 .clicked()
@@ -330,19 +425,23 @@ impl PopupContent for PermissionInput {
                         .get_user_state(self.giver_user_id)
                         .granted_permissions
                         .update_with_info(
-                            UpdateGrantedPermission {
-                                id,
-                                receiver_email: USome(self.receiver_email.clone()),
-                                permissions: USome(self.make_permissions()),
-                            },
+                            ShareWithValidity::new(
+                                UpdateGrantedPermission {
+                                    id,
+                                    receiver_email: USome(self.receiver_email.clone()),
+                                    permissions: USome(self.make_permissions()),
+                                },
+                                self.get_validity(),
+                            ),
                             self.receiver_email.clone(),
                         ),
                 );
             }
         } else {
             if ui
-                .add_enabled(
+                .add_enabled_permitted(
                     self.insert_request.is_none() && !info.is_error(),
+                    can_share,
                     egui::Button::new("Create"),
                 )
                 .clicked()
@@ -352,11 +451,14 @@ impl PopupContent for PermissionInput {
                         .get_user_state(self.giver_user_id)
                         .granted_permissions
                         .insert_with_info(
-                            NewGrantedPermission {
-                                giver_user_id: self.giver_user_id,
-                                receiver_email: self.receiver_email.clone(),
-                                permissions: self.make_permissions(),
-                            },
+                            ShareWithValidity::new(
+                                NewGrantedPermission {
+                                    giver_user_id: self.giver_user_id,
+                                    receiver_email: self.receiver_email.clone(),
+                                    permissions: self.make_permissions(),
+                                },
+                                self.get_validity(),
+                            ),
                             self.receiver_email.clone(),
                         ),
                 );
@@ -365,5 +467,26 @@ impl PopupContent for PermissionInput {
         if ui.button("Cancel").clicked() {
             info.close();
         }
+        if ui
+            .button("Preview")
+            .on_hover_text("See the calendar the way the recipient will")
+            .clicked()
+        {
+            let recipient_name = if !self.receiver_name.is_empty() {
+                self.receiver_name.clone()
+            } else if !self.receiver_email.is_empty() {
+                self.receiver_email.clone()
+            } else {
+                "recipient".to_owned()
+            };
+            CalendarApp::request_preview(
+                ui.ctx(),
+                Preview {
+                    user_id: self.giver_user_id,
+                    recipient_name,
+                    permissions: self.make_permissions(),
+                },
+            );
+        }
     }
 }
@@ -0,0 +1,139 @@
+use super::{
+    popup::PopupType,
+    popup_content::{ContentInfo, PopupContent},
+};
+use crate::{
+    app::CalendarApp,
+    db::aliases::Event,
+    state::{
+        capabilities::{CapabilityAction, CapabilityTable},
+        user_state::PlanExceptionRequest,
+    },
+    tables::plan_exceptions::PlanExceptionKind,
+    ui::{time_picker::TimePicker, utils::UiUtils},
+};
+use calendar_lib::api::utils::TableId;
+use chrono::{NaiveDate, NaiveDateTime, NaiveTime};
+use egui::DragValue;
+use egui_extras::DatePickerButton;
+use std::{hash::Hash, time::Duration};
+
+/// Moves or resizes a single occurrence of an event plan
+pub struct PlanExceptionInput {
+    eid: egui::Id,
+    user_id: TableId,
+    plan_id: TableId,
+    /// Date, the occurrence was planned on
+    planned_date: NaiveDate,
+    name: String,
+    access_level: i32,
+
+    date: NaiveDate,
+    time: NaiveTime,
+    minutes: u64,
+    init_minutes: u64,
+
+    request: Option<PlanExceptionRequest>,
+}
+
+impl PlanExceptionInput {
+    pub fn new(eid: impl Hash, event: &Event, plan_id: TableId, planned_date: NaiveDate) -> Self {
+        let minutes = (event.end - event.start).num_minutes().max(1) as u64;
+        Self {
+            eid: egui::Id::new(eid),
+            user_id: event.user_id,
+            plan_id,
+            planned_date,
+            name: event.name.clone(),
+            access_level: event.access_level,
+            date: event.start.date(),
+            time: event.start.time(),
+            minutes,
+            init_minutes: minutes,
+            request: None,
+        }
+    }
+}
+
+impl PopupContent for PlanExceptionInput {
+    fn get_type(&self) -> PopupType {
+        PopupType::PlanException
+    }
+
+    fn init_frame(&mut self, app: &CalendarApp, info: &mut ContentInfo) {
+        if let Some(request) = self.request.as_ref() {
+            if let Some(succeeded) = request.get_result(&app.state) {
+                self.request = None;
+                if succeeded {
+                    info.close();
+                }
+            }
+        }
+    }
+
+    fn get_title(&mut self) -> Option<String> {
+        Some(format!(
+            "Change '{}' on {}",
+            self.name,
+            self.planned_date.format("%Y-%m-%d")
+        ))
+    }
+
+    fn show_content(&mut self, _app: &CalendarApp, ui: &mut egui::Ui, _info: &mut ContentInfo) {
+        ui.vertical(|ui| {
+            ui.label("Only this occurrence is changed, the schedule stays the same");
+            ui.add(DatePickerButton::new(&mut self.date).show_icon(false));
+            ui.horizontal(|ui| {
+                ui.add(TimePicker::new(self.eid.with("time"), &mut self.time));
+                ui.label("for");
+                ui.add(
+                    DragValue::new(&mut self.minutes)
+                        .clamp_range(1..=24 * 60)
+                        .suffix(" min"),
+                );
+            });
+        });
+    }
+
+    fn show_buttons(&mut self, app: &CalendarApp, ui: &mut egui::Ui, info: &mut ContentInfo) {
+        let user_state = app.state.get_user_state(self.user_id);
+        if ui
+            .add_enabled_permitted(
+                self.request.is_none() && !info.is_error(),
+                app.state.get_capabilities(self.user_id).item(
+                    CapabilityTable::Schedules,
+                    CapabilityAction::Edit,
+                    self.access_level,
+                ),
+                egui::Button::new("Save"),
+            )
+            .clicked()
+        {
+            // Untouched duration keeps following the plan
+            let had_duration = user_state
+                .get_plan_exception(self.plan_id, self.planned_date)
+                .is_some_and(|exception| {
+                    matches!(
+                        exception.kind,
+                        PlanExceptionKind::Change {
+                            duration: Some(_),
+                            ..
+                        }
+                    )
+                });
+            let duration = (had_duration || self.minutes != self.init_minutes)
+                .then(|| Duration::from_secs(self.minutes * 60));
+            self.request = Some(user_state.set_plan_exception(
+                self.plan_id,
+                self.planned_date,
+                PlanExceptionKind::Change {
+                    start: NaiveDateTime::new(self.date, self.time),
+                    duration,
+                },
+            ));
+        }
+        if ui.button("Cancel").clicked() {
+            info.close();
+        }
+    }
+}
@@ -14,9 +14,19 @@ pub enum PopupType {
     UpdateEventTemplate,
     NewSchedule,
     UpdateSchedule,
+    PlanException,
     NewPermission,
     UpdatePermission,
     ChangeAccessLevels,
+    ShareExpiryReminder,
+    NewShareLink,
+    RevokeShareLink,
+    TableImport,
+    BackupRestore,
+    AdminNewUser,
+    AdminUpdateUser,
+    AdminPasswordReset,
+    AdminUserConfirm,
 }
 
 pub struct Popup {
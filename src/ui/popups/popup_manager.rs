@@ -8,20 +8,34 @@ use calendar_lib::api::{
     schedules::types::Schedule,
     utils::{TableId, User},
 };
+use chrono::NaiveDate;
 use itertools::Itertools;
 
-use crate::{app::CalendarApp, state::state_table::StateTable};
+use crate::{
+    app::CalendarApp,
+    state::{state_table::StateTable, table_import::TableItemImport},
+    tables::{share_links::ShareLink, share_validities::ShareValidity},
+};
 
 use super::{
+    admin_password_reset::AdminPasswordReset,
+    admin_user_confirm::{AdminUserAction, AdminUserConfirm},
+    admin_user_input::AdminUserInput,
+    backup_restore::BackupRestoreInput,
     change_access_levels::ChangeAccessLevelsPopup,
     event_input::EventInput,
     event_template_input::EventTemplateInput,
     login::Login,
     permission_input::PermissionInput,
+    plan_exception_input::PlanExceptionInput,
     popup::{Popup, PopupType},
     profile::Profile,
     schedule_input::ScheduleInput,
+    share_expiry_reminder::ShareExpiryReminder,
+    share_link_input::ShareLinkInput,
+    share_link_revoke::ShareLinkRevoke,
     sign_up::SignUp,
+    table_import::TableImport,
 };
 
 pub struct PopupManager {
@@ -90,12 +104,24 @@ impl PopupManager {
             event,
         )));
     }
+    pub fn open_duplicate_event(&mut self, event: &Event) {
+        self.popups.push(Popup::new(EventInput::duplicate(
+            format!("duplicate_event_popup_{}", event.id),
+            event,
+        )));
+    }
     pub fn open_new_event_template(&mut self, user_id: i32) {
         self.popups.push(Popup::new(EventTemplateInput::new(
             "new_event_template_popup",
             user_id,
         )));
     }
+    pub fn open_event_template_from_event(&mut self, event: &Event) {
+        self.popups.push(Popup::new(EventTemplateInput::from_event(
+            format!("event_template_from_event_popup_{}", event.id),
+            event,
+        )));
+    }
     pub fn open_update_event_template(&mut self, template: &EventTemplate) {
         self.popups.push(Popup::new(EventTemplateInput::change(
             format!("update_event_template_popup_{}", template.id),
@@ -108,23 +134,44 @@ impl PopupManager {
             user_id,
         )));
     }
+    pub fn open_schedule_from_event(&mut self, event: &Event, templates: &[EventTemplate]) {
+        self.popups.push(Popup::new(ScheduleInput::from_event(
+            format!("schedule_from_event_popup_{}", event.id),
+            event,
+            templates,
+        )));
+    }
     pub fn open_update_schedule(&mut self, schedule: &Schedule) {
         self.popups.push(Popup::new(ScheduleInput::change(
             format!("update_schedule_popup_{}", schedule.id),
             schedule,
         )));
     }
+    pub fn open_plan_exception(&mut self, event: &Event, plan_id: TableId, date: NaiveDate) {
+        self.popups.push(Popup::new(PlanExceptionInput::new(
+            format!("plan_exception_popup_{}_{}", plan_id, date),
+            event,
+            plan_id,
+            date,
+        )));
+    }
     pub fn open_new_permission(&mut self, giver_user_id: TableId) {
         self.popups.push(Popup::new(PermissionInput::new(
             format!("new_permission_popup_{}", giver_user_id),
             giver_user_id,
         )));
     }
-    pub fn open_update_permission(&mut self, permission: &GrantedPermission, user: &User) {
+    pub fn open_update_permission(
+        &mut self,
+        permission: &GrantedPermission,
+        user: &User,
+        validity: Option<&ShareValidity>,
+    ) {
         self.popups.push(Popup::new(PermissionInput::change(
             format!("update_permission_popup_{}", permission.id),
             permission,
             user,
+            validity,
         )));
     }
     pub fn open_change_access_levels(
@@ -138,4 +185,42 @@ impl PopupManager {
             access_levels,
         )));
     }
+    pub fn open_share_expiry_reminder(&mut self, shares: Vec<(String, i64)>) {
+        self.popups
+            .push(Popup::new(ShareExpiryReminder::new(shares)));
+    }
+    pub fn open_new_share_link(&mut self, user_id: TableId) {
+        self.popups.push(Popup::new(ShareLinkInput::new(
+            format!("new_share_link_popup_{}", user_id),
+            user_id,
+        )));
+    }
+    pub fn open_revoke_share_link(&mut self, link: &ShareLink) {
+        self.popups.push(Popup::new(ShareLinkRevoke::new(link)));
+    }
+    pub fn open_backup_restore(&mut self, user_id: TableId) {
+        self.popups.push(Popup::new(BackupRestoreInput::new(
+            format!("backup_restore_popup_{}", user_id),
+            user_id,
+        )));
+    }
+    pub fn open_table_import<T: TableItemImport + Send + 'static>(&mut self, user_id: TableId) {
+        self.popups.push(Popup::new(TableImport::<T>::new(
+            format!("table_import_popup_{}", user_id),
+            user_id,
+        )));
+    }
+    pub fn open_admin_new_user(&mut self) {
+        self.popups.push(Popup::new(AdminUserInput::new()));
+    }
+    pub fn open_admin_update_user(&mut self, user: &User) {
+        self.popups.push(Popup::new(AdminUserInput::change(user)));
+    }
+    pub fn open_admin_password_reset(&mut self, user: &User) {
+        self.popups.push(Popup::new(AdminPasswordReset::new(user)));
+    }
+    pub fn open_admin_user_confirm(&mut self, user: &User, action: AdminUserAction) {
+        self.popups
+            .push(Popup::new(AdminUserConfirm::new(user, action)));
+    }
 }
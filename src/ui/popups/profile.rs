@@ -1,15 +1,19 @@
 use super::{
+    backup_restore::BackupRestoreInput,
     popup::PopupType,
     popup_content::{ContentInfo, PopupContent},
 };
 use crate::app::CalendarApp;
 use egui::{Align, Layout, Vec2};
 
-pub struct Profile {}
+pub struct Profile {
+    /// Shown inline, as popups can't open other popups
+    restore: Option<BackupRestoreInput>,
+}
 
 impl Profile {
     pub fn new() -> Self {
-        Self {}
+        Self { restore: None }
     }
 }
 
@@ -18,6 +22,12 @@ impl PopupContent for Profile {
         PopupType::Profile
     }
 
+    fn init_frame(&mut self, app: &CalendarApp, info: &mut ContentInfo) {
+        if let Some(restore) = self.restore.as_mut() {
+            restore.init_frame(app, info);
+        }
+    }
+
     fn show_title(&mut self, app: &CalendarApp, ui: &mut egui::Ui, info: &mut ContentInfo) {
         ui.with_layout(Layout::right_to_left(Align::TOP), |ui| {
             ui.spacing_mut().item_spacing = Vec2::new(4., 0.);
@@ -31,12 +41,44 @@ impl PopupContent for Profile {
         ui.separator();
     }
 
-    fn show_content(&mut self, app: &CalendarApp, ui: &mut egui::Ui, _info: &mut ContentInfo) {
+    fn show_content(&mut self, app: &CalendarApp, ui: &mut egui::Ui, info: &mut ContentInfo) {
         ui.with_layout(Layout::top_down(Align::LEFT), |ui| {
             ui.horizontal(|ui| {
                 ui.label("Email: ");
                 ui.label(&app.state.get_me().email);
             });
+            ui.separator();
+            let me = app.state.get_me().id;
+            ui.horizontal(|ui| {
+                // Copied to the clipboard, as there are no files on the web
+                if ui
+                    .button("Backup")
+                    .on_hover_text("Copy the whole calendar to the clipboard")
+                    .clicked()
+                {
+                    let backup = app.state.get_user_state(me).make_backup();
+                    ui.output_mut(|o| o.copied_text = backup.to_json());
+                }
+                if ui
+                    .add_enabled(self.restore.is_none(), egui::Button::new("Restore"))
+                    .clicked()
+                {
+                    self.restore = Some(BackupRestoreInput::new("profile_restore", me));
+                }
+            });
+            if let Some(restore) = self.restore.as_mut() {
+                restore.show_content(app, ui, info);
+            }
         });
     }
+
+    fn show_buttons(&mut self, app: &CalendarApp, ui: &mut egui::Ui, _info: &mut ContentInfo) {
+        if let Some(restore) = self.restore.as_mut() {
+            let mut restore_info = ContentInfo::new();
+            restore.show_buttons(app, ui, &mut restore_info);
+            if restore_info.take() {
+                self.restore = None;
+            }
+        }
+    }
 }
@@ -5,18 +5,58 @@ use super::{
 use crate::{
     app::CalendarApp,
     db::request::RequestIdentifier,
-    state::table_requests::{TableInsertRequest, TableUpdateRequest},
-    tables::DbTable,
-    ui::{access_level_picker::AccessLevelPicker, time_picker::TimePicker},
-    utils::weekday_human_name,
+    state::{
+        capabilities::{CapabilityAction, CapabilityTable},
+        custom_requests::{SetPlanOverridesRequest, SetScheduleTemplateRequest},
+        state_table::StateTable,
+        table_requests::TableInsertRequest,
+    },
+    tables::{plan_overrides::PlanOverride, DbTable, DbTableGetById},
+    ui::{
+        access_level_picker::AccessLevelPicker,
+        conflict_resolver::{
+            find_conflict, ConflictEdit, ConflictEditor, ConflictResolver, FieldConflict,
+        },
+        schedule_designer::{DesignerAction, DesignerPlan, PlanKey},
+        schedule_plan_editor::{get_busy_week, PlanDesignerPanel, PlanOverrideEditor},
+        time_picker::TimePicker,
+        utils::UiUtils,
+    },
+    utils::{access_levels_human_name, weekday_human_name, weekday_human_name_short},
 };
-use calendar_lib::api::{schedules::types::*, utils::*};
-use chrono::{Days, Local, NaiveDate, NaiveTime, Weekday};
+use calendar_lib::api::{
+    event_templates::types::{EventTemplate, NewEventTemplate},
+    events::types::Event,
+    schedules::types::*,
+    utils::*,
+};
+use chrono::{Datelike, Days, Local, NaiveDate, NaiveTime, Weekday};
 use egui::{Button, TextEdit};
 use egui_extras::DatePickerButton;
 use itertools::Itertools;
 use num_traits::FromPrimitive;
-use std::hash::Hash;
+use std::{collections::HashMap, hash::Hash, time::Duration};
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum ScheduleField {
+    Name,
+    Description,
+    Template,
+    Days,
+    AccessLevel,
+    Events,
+}
+
+impl ScheduleField {
+    const ALL: [ScheduleField; 6] = [
+        ScheduleField::Name,
+        ScheduleField::Description,
+        ScheduleField::Template,
+        ScheduleField::Days,
+        ScheduleField::AccessLevel,
+        ScheduleField::Events,
+    ];
+}
 
 pub struct ScheduleInput {
     eid: egui::Id,
@@ -35,9 +75,30 @@ pub struct ScheduleInput {
     pub init_events: Option<Vec<EventPlan>>,
     pub new_event_start: NaiveTime,
     pub events: [Vec<NewEventPlan>; 7],
+    /// Event plans are identified by weekday and time, until they are saved
+    overrides: HashMap<(Weekday, NaiveTime), PlanOverride>,
+    /// None until read from the state
+    init_overrides: Option<HashMap<(Weekday, NaiveTime), PlanOverride>>,
+    selected_plan: Option<(Weekday, NaiveTime)>,
+    designer_view: bool,
+
+    /// Template, that is created on save, if there is no matching one
+    template_from_event: Option<NewEventTemplate>,
+    /// Schedule is inserted, once the new template is loaded
+    create_after_template: bool,
+
+    /// Changes of an existing schedule
+    edit: Option<ConflictEdit<Schedule, ScheduleField>>,
 
-    update_request: Option<RequestIdentifier<TableUpdateRequest<Schedule>>>,
     insert_request: Option<RequestIdentifier<TableInsertRequest<Schedule>>>,
+    template_insert_request: Option<RequestIdentifier<TableInsertRequest<EventTemplate>>>,
+    /// Overrides are sent, once the saved event plans get their ids
+    is_saved: bool,
+    overrides_sent: bool,
+    overrides_request: Option<RequestIdentifier<SetPlanOverridesRequest>>,
+    set_template_request: Option<RequestIdentifier<SetScheduleTemplateRequest>>,
+    /// Part of the save, that failed after the schedule itself was saved
+    save_error: Option<&'static str>,
 }
 
 impl ScheduleInput {
@@ -65,9 +126,23 @@ impl ScheduleInput {
             init_events: None,
             new_event_start: now_time,
             events: Default::default(),
+            overrides: HashMap::new(),
+            init_overrides: Some(HashMap::new()),
+            selected_plan: None,
+            designer_view: true,
+
+            template_from_event: None,
+            create_after_template: false,
+
+            edit: None,
 
-            update_request: None,
             insert_request: None,
+            template_insert_request: None,
+            is_saved: false,
+            overrides_sent: false,
+            overrides_request: None,
+            set_template_request: None,
+            save_error: None,
         }
     }
 
@@ -94,21 +169,616 @@ impl ScheduleInput {
 
             init_events: Some(schedule.event_plans.clone()),
             new_event_start: now_time,
-            events: schedule
-                .event_plans
+            events: Self::events_by_weekday(&schedule.event_plans),
+            overrides: HashMap::new(),
+            init_overrides: None,
+            selected_plan: None,
+            designer_view: true,
+
+            template_from_event: None,
+            create_after_template: false,
+
+            edit: Some(ConflictEdit::new(schedule.clone())),
+
+            insert_request: None,
+            template_insert_request: None,
+            is_saved: false,
+            overrides_sent: false,
+            overrides_request: None,
+            set_template_request: None,
+            save_error: None,
+        }
+    }
+
+    /// Weekly schedule, that repeats the event on its weekday and time
+    pub fn from_event(eid: impl Hash, event: &Event, templates: &[EventTemplate]) -> Self {
+        let template = NewEventTemplate {
+            user_id: event.user_id,
+            name: event.name.clone(),
+            event_name: event.name.clone(),
+            event_description: event.description.clone(),
+            duration: (event.end - event.start).to_std().unwrap_or_default(),
+            access_level: event.access_level,
+        };
+        let weekday = event.start.weekday();
+        let mut events: [Vec<NewEventPlan>; 7] = Default::default();
+        events[weekday.num_days_from_monday() as usize].push(NewEventPlan {
+            weekday,
+            time: event.start.time(),
+        });
+
+        Self {
+            template_id: Self::find_template(templates, &template),
+            name: event.name.clone(),
+            description: event.description.clone().unwrap_or_default(),
+            first_day: event.start.date(),
+            access_level: event.access_level,
+            new_event_start: event.start.time(),
+            events,
+            template_from_event: Some(template),
+            ..Self::new(eid, event.user_id)
+        }
+    }
+
+    fn find_template(templates: &[EventTemplate], template: &NewEventTemplate) -> Option<TableId> {
+        templates
+            .iter()
+            .filter(|t| {
+                t.event_name == template.event_name
+                    && t.event_description == template.event_description
+                    && t.duration == template.duration
+            })
+            .map(|t| t.id)
+            .max()
+    }
+
+    fn insert_schedule(
+        &self,
+        app: &CalendarApp,
+        template_id: TableId,
+    ) -> RequestIdentifier<TableInsertRequest<Schedule>> {
+        app.state
+            .get_user_state(self.user_id)
+            .schedules
+            .insert(NewSchedule {
+                user_id: self.user_id,
+                template_id,
+                name: self.name.clone(),
+                description: (!self.description.is_empty()).then_some(self.description.clone()),
+                first_day: self.first_day,
+                last_day: self.last_day_enabled.then_some(self.last_day),
+                access_level: self.access_level,
+                events: self.events.clone().into_iter().flatten().collect(),
+            })
+    }
+
+    fn events_by_weekday(event_plans: &[EventPlan]) -> [Vec<NewEventPlan>; 7] {
+        event_plans
+            .iter()
+            .fold(Default::default(), |mut acc, event| {
+                let weekday_ind = event.weekday.num_days_from_monday() as usize;
+                acc[weekday_ind].push(NewEventPlan {
+                    weekday: event.weekday,
+                    time: event.time,
+                });
+                acc[weekday_ind].sort_by_key(|e| e.time);
+                acc
+            })
+    }
+
+    fn overrides_by_plan(
+        event_plans: &[EventPlan],
+        overrides: &[PlanOverride],
+    ) -> HashMap<(Weekday, NaiveTime), PlanOverride> {
+        event_plans
+            .iter()
+            .filter_map(|plan| {
+                overrides
+                    .iter()
+                    .find(|plan_override| plan_override.plan_id == plan.id)
+                    .map(|plan_override| {
+                        (
+                            (plan.weekday, plan.time),
+                            PlanOverride {
+                                plan_id: -1,
+                                ..*plan_override
+                            },
+                        )
+                    })
+            })
+            .collect()
+    }
+
+    /// Overrides of the current event plans, that change anything
+    fn get_overrides(&self) -> HashMap<(Weekday, NaiveTime), PlanOverride> {
+        self.overrides
+            .iter()
+            .filter(|((weekday, time), plan_override)| {
+                !plan_override.is_empty()
+                    && self.events[weekday.num_days_from_monday() as usize]
+                        .iter()
+                        .any(|plan| plan.time == *time)
+            })
+            .map(|(key, plan_override)| (*key, *plan_override))
+            .collect()
+    }
+
+    /// Saved version of the schedule, with all the event plans
+    fn find_saved_schedule<'a>(&self, app: &'a CalendarApp) -> Option<&'a Schedule> {
+        let plans = Self::plans_key(
+            self.events
                 .iter()
-                .fold(Default::default(), |mut acc, event| {
-                    let weekday_ind = event.weekday.num_days_from_monday() as usize;
-                    acc[weekday_ind].push(NewEventPlan {
-                        weekday: event.weekday,
-                        time: event.time,
+                .flatten()
+                .map(|plan| (&plan.weekday, &plan.time)),
+        );
+        app.state
+            .get_user_state(self.user_id)
+            .schedules
+            .get_table()
+            .get()
+            .iter()
+            .filter(|schedule| match self.id {
+                Some(id) => schedule.id == id,
+                None => {
+                    schedule.name == self.name
+                        && schedule.first_day == self.first_day
+                        && Some(schedule.template_id) == self.template_id
+                }
+            })
+            .filter(|schedule| {
+                Self::plans_key(
+                    schedule
+                        .event_plans
+                        .iter()
+                        .map(|plan| (&plan.weekday, &plan.time)),
+                ) == plans
+            })
+            .max_by_key(|schedule| schedule.id)
+    }
+
+    fn send_overrides(&mut self, app: &CalendarApp) {
+        let schedule = match self.find_saved_schedule(app) {
+            Some(schedule) => schedule,
+            None => return,
+        };
+        self.overrides_sent = true;
+        let overrides = self.get_overrides();
+        if Some(&overrides) == self.init_overrides.as_ref() {
+            return;
+        }
+        let overrides = schedule
+            .event_plans
+            .iter()
+            .filter_map(|plan| {
+                overrides
+                    .get(&(plan.weekday, plan.time))
+                    .map(|plan_override| PlanOverride {
+                        plan_id: plan.id,
+                        ..*plan_override
+                    })
+            })
+            .collect();
+        self.overrides_request = Some(
+            app.state
+                .get_user_state(self.user_id)
+                .set_plan_overrides(schedule.id, overrides),
+        );
+    }
+
+    fn has_plan(&self, (weekday, time): PlanKey) -> bool {
+        self.events[weekday.num_days_from_monday() as usize]
+            .iter()
+            .any(|plan| plan.time == time)
+    }
+
+    fn add_plan(&mut self, (weekday, time): PlanKey) {
+        if !self.has_plan((weekday, time)) {
+            let plans = &mut self.events[weekday.num_days_from_monday() as usize];
+            plans.push(NewEventPlan { weekday, time });
+            plans.sort_by_key(|plan| plan.time);
+        }
+    }
+
+    fn remove_plan(&mut self, (weekday, time): PlanKey) {
+        self.events[weekday.num_days_from_monday() as usize].retain(|plan| plan.time != time);
+        if self.selected_plan == Some((weekday, time)) {
+            self.selected_plan = None;
+        }
+    }
+
+    fn apply_designer_action(&mut self, action: DesignerAction) {
+        match action {
+            DesignerAction::Select(key) => {
+                self.selected_plan = (self.selected_plan != Some(key)).then_some(key);
+            }
+            DesignerAction::Add(key) => {
+                self.add_plan(key);
+                self.selected_plan = Some(key);
+            }
+            DesignerAction::Move { from, to } => {
+                if self.has_plan(to) {
+                    return;
+                }
+                let is_selected = self.selected_plan == Some(from);
+                self.remove_plan(from);
+                self.add_plan(to);
+                if let Some(plan_override) = self.overrides.remove(&from) {
+                    self.overrides.insert(to, plan_override);
+                }
+                if is_selected {
+                    self.selected_plan = Some(to);
+                }
+            }
+            DesignerAction::CopyToAllWeekdays(key) => {
+                let plan_override = self.overrides.get(&key).copied();
+                (0..7)
+                    .map(|weekday_ind| (Weekday::from_usize(weekday_ind).unwrap(), key.1))
+                    .filter(|other| *other != key && !self.has_plan(*other))
+                    .collect_vec()
+                    .into_iter()
+                    .for_each(|other| {
+                        self.add_plan(other);
+                        if let Some(plan_override) = plan_override {
+                            self.overrides.insert(other, plan_override);
+                        }
                     });
-                    acc[weekday_ind].sort_by_key(|e| e.time);
-                    acc
-                }),
+            }
+            DesignerAction::Delete(key) => {
+                self.remove_plan(key);
+                self.overrides.remove(&key);
+            }
+        }
+    }
 
-            update_request: None,
-            insert_request: None,
+    fn get_plan_duration(&self, app: &CalendarApp, key: PlanKey) -> Duration {
+        let plan_override = self.overrides.get(&key);
+        if let Some(duration) = plan_override.and_then(|plan_override| plan_override.duration) {
+            return duration;
+        }
+        let template_id = plan_override
+            .and_then(|plan_override| plan_override.template_id)
+            .or(self.template_id);
+        template_id
+            .and_then(|template_id| {
+                app.state
+                    .get_user_state(self.user_id)
+                    .event_templates
+                    .get_table()
+                    .get_by_id(template_id)
+                    .map(|template| template.duration)
+            })
+            .or(self
+                .template_from_event
+                .as_ref()
+                .map(|template| template.duration))
+            .unwrap_or(Duration::from_secs(60 * 60))
+    }
+
+    fn show_designer(&mut self, app: &CalendarApp, ui: &mut egui::Ui) {
+        let user_state = app.state.get_user_state(self.user_id);
+        let plans = self
+            .events
+            .iter()
+            .flatten()
+            .map(|plan| {
+                let key = (plan.weekday, plan.time);
+                let plan_override = self.overrides.get(&key);
+                let template_id = plan_override
+                    .and_then(|plan_override| plan_override.template_id)
+                    .or(self.template_id);
+                DesignerPlan {
+                    key,
+                    duration: self.get_plan_duration(app, key),
+                    name: template_id
+                        .and_then(|template_id| {
+                            user_state
+                                .event_templates
+                                .get_table()
+                                .get_by_id(template_id)
+                                .map(|template| template.event_name.clone())
+                        })
+                        .unwrap_or_default(),
+                    is_overridden: plan_override
+                        .is_some_and(|plan_override| !plan_override.is_empty()),
+                }
+            })
+            .collect_vec();
+        let busy = get_busy_week(
+            user_state,
+            self.first_day,
+            self.edit.as_ref().map(|edit| edit.get_base()),
+        );
+
+        let mut action = None;
+        ui.add(
+            PlanDesignerPanel::new(self.eid.with("designer"), &plans, &busy, &mut action)
+                .with_selected(self.selected_plan),
+        );
+        if let Some(action) = action {
+            self.apply_designer_action(action);
+        }
+    }
+
+    fn show_override(&mut self, app: &CalendarApp, ui: &mut egui::Ui, key: PlanKey) {
+        let user_state = app.state.get_user_state(self.user_id);
+        let templates = user_state.event_templates.get_table();
+        let default_template = self
+            .template_id
+            .and_then(|template_id| templates.get_by_id(template_id));
+        let plan_override = self.overrides.entry(key).or_insert(PlanOverride::new(-1));
+        ui.add(
+            PlanOverrideEditor::new(
+                self.eid.with("override"),
+                key,
+                plan_override,
+                templates.get(),
+                user_state.access_levels.get_table().get(),
+            )
+            .with_defaults(default_template, self.access_level),
+        );
+    }
+
+    /// Event plans in comparable form, ordered by weekday and time
+    fn plans_key<'a>(
+        event_plans: impl Iterator<Item = (&'a Weekday, &'a NaiveTime)>,
+    ) -> Vec<(u32, NaiveTime)> {
+        event_plans
+            .map(|(weekday, time)| (weekday.num_days_from_monday(), *time))
+            .sorted()
+            .collect()
+    }
+}
+
+impl ConflictEditor for ScheduleInput {
+    type Item = Schedule;
+    type Field = ScheduleField;
+
+    const FIELDS: &'static [ScheduleField] = &ScheduleField::ALL;
+
+    fn get_edit(&mut self) -> &mut Option<ConflictEdit<Schedule, ScheduleField>> {
+        &mut self.edit
+    }
+
+    fn get_state_table<'a>(&self, app: &'a CalendarApp) -> &'a StateTable<Schedule> {
+        &app.state.get_user_state(self.user_id).schedules
+    }
+
+    fn find_conflicts(
+        &self,
+        app: &CalendarApp,
+        base: &Schedule,
+        theirs: &Schedule,
+    ) -> Vec<FieldConflict<ScheduleField>> {
+        let access_levels = app
+            .state
+            .get_user_state(self.user_id)
+            .access_levels
+            .get_table()
+            .get();
+        let templates = app
+            .state
+            .get_user_state(self.user_id)
+            .event_templates
+            .get_table();
+        let description = (!self.description.is_empty()).then_some(self.description.clone());
+        let days = (
+            self.first_day,
+            self.last_day_enabled.then_some(self.last_day),
+        );
+        let plans = Self::plans_key(
+            self.events
+                .iter()
+                .flatten()
+                .map(|plan| (&plan.weekday, &plan.time)),
+        );
+        let base_plans = Self::plans_key(
+            base.event_plans
+                .iter()
+                .map(|plan| (&plan.weekday, &plan.time)),
+        );
+        let their_plans = Self::plans_key(
+            theirs
+                .event_plans
+                .iter()
+                .map(|plan| (&plan.weekday, &plan.time)),
+        );
+
+        [
+            find_conflict(
+                ScheduleField::Name,
+                "Name",
+                &base.name,
+                &self.name,
+                &theirs.name,
+                |name| name.clone(),
+            ),
+            find_conflict(
+                ScheduleField::Description,
+                "Description",
+                &base.description,
+                &description,
+                &theirs.description,
+                |description| description.clone().unwrap_or_default(),
+            ),
+            find_conflict(
+                ScheduleField::Template,
+                "Template",
+                &base.template_id,
+                &self.template_id.unwrap_or(base.template_id),
+                &theirs.template_id,
+                |template_id| {
+                    templates
+                        .get_by_id(*template_id)
+                        .map_or_else(|| format!("#{template_id}"), |t| t.name.clone())
+                },
+            ),
+            find_conflict(
+                ScheduleField::Days,
+                "Days",
+                &(base.first_day, base.last_day),
+                &days,
+                &(theirs.first_day, theirs.last_day),
+                |(first_day, last_day)| match last_day {
+                    Some(last_day) => format!("{first_day} - {last_day}"),
+                    None => format!("From {first_day}"),
+                },
+            ),
+            find_conflict(
+                ScheduleField::AccessLevel,
+                "Access level",
+                &base.access_level,
+                &self.access_level,
+                &theirs.access_level,
+                |level| access_levels_human_name(access_levels, *level),
+            ),
+            find_conflict(
+                ScheduleField::Events,
+                "Events",
+                &base_plans,
+                &plans,
+                &their_plans,
+                |plans| {
+                    plans
+                        .iter()
+                        .map(|(weekday, time)| {
+                            format!(
+                                "{} {}",
+                                weekday_human_name_short(Weekday::from_u32(*weekday).unwrap()),
+                                time.format("%H:%M")
+                            )
+                        })
+                        .join(", ")
+                },
+            ),
+        ]
+        .into_iter()
+        .flatten()
+        .collect()
+    }
+
+    fn is_changed(&self, field: ScheduleField, base: &Schedule) -> bool {
+        match field {
+            ScheduleField::Name => self.name != base.name,
+            ScheduleField::Description => {
+                (!self.description.is_empty()).then_some(&self.description)
+                    != base.description.as_ref()
+            }
+            ScheduleField::Template => self
+                .template_id
+                .is_some_and(|template_id| template_id != base.template_id),
+            ScheduleField::Days => {
+                (
+                    self.first_day,
+                    self.last_day_enabled.then_some(self.last_day),
+                ) != (base.first_day, base.last_day)
+            }
+            ScheduleField::AccessLevel => self.access_level != base.access_level,
+            ScheduleField::Events => {
+                Self::plans_key(
+                    self.events
+                        .iter()
+                        .flatten()
+                        .map(|plan| (&plan.weekday, &plan.time)),
+                ) != Self::plans_key(
+                    base.event_plans
+                        .iter()
+                        .map(|plan| (&plan.weekday, &plan.time)),
+                )
+            }
+        }
+    }
+
+    fn take_theirs(&mut self, field: ScheduleField, theirs: &Schedule) {
+        match field {
+            ScheduleField::Name => self.name = theirs.name.clone(),
+            ScheduleField::Description => {
+                self.description = theirs.description.clone().unwrap_or_default()
+            }
+            ScheduleField::Template => self.template_id = Some(theirs.template_id),
+            ScheduleField::Days => {
+                self.first_day = theirs.first_day;
+                self.last_day_enabled = theirs.last_day.is_some();
+                if let Some(last_day) = theirs.last_day {
+                    self.last_day = last_day;
+                }
+            }
+            ScheduleField::AccessLevel => self.access_level = theirs.access_level,
+            ScheduleField::Events => self.events = Self::events_by_weekday(&theirs.event_plans),
+        }
+    }
+
+    fn make_update(&self, id: TableId, base: &Schedule) -> UpdateSchedule {
+        let description = (!self.description.is_empty()).then_some(self.description.clone());
+        let last_day = self.last_day_enabled.then_some(self.last_day);
+
+        let events = self.events.iter().flatten().collect_vec();
+        let init_events = self.init_events.clone().unwrap_or(vec![]);
+        let delete_events = init_events
+            .iter()
+            .filter_map(|event_plan| {
+                (!events.iter().any(|new_event_plan| {
+                    event_plan.weekday == new_event_plan.weekday
+                        && event_plan.time == new_event_plan.time
+                }))
+                .then_some(event_plan.id)
+            })
+            .collect_vec();
+        let new_events = events
+            .iter()
+            .filter_map(|&new_event_plan| {
+                (!init_events.iter().any(|event_plan| {
+                    event_plan.weekday == new_event_plan.weekday
+                        && event_plan.time == new_event_plan.time
+                }))
+                .then_some(new_event_plan.clone())
+            })
+            .collect_vec();
+
+        UpdateSchedule {
+            id,
+            name: if self.name != base.name {
+                USome(self.name.clone())
+            } else {
+                UNone
+            },
+            description: if description != base.description {
+                USome(description)
+            } else {
+                UNone
+            },
+            first_day: if self.first_day != base.first_day {
+                USome(self.first_day)
+            } else {
+                UNone
+            },
+            last_day: if last_day != base.last_day {
+                USome(last_day)
+            } else {
+                UNone
+            },
+            access_level: if self.access_level != base.access_level {
+                USome(self.access_level)
+            } else {
+                UNone
+            },
+            delete_events,
+            new_events,
+        }
+    }
+
+    fn on_rebase(&mut self, base: &Schedule) {
+        // Event plans are diffed against the server version from now on
+        self.init_events = Some(base.event_plans.clone());
+    }
+
+    /// Template is set by its own request, so it isn't sent again after a stale rejection
+    fn on_update(&mut self, app: &CalendarApp, base: &Schedule) {
+        if let Some(template_id) = self.template_id.filter(|t| *t != base.template_id) {
+            self.set_template_request = Some(
+                app.state
+                    .get_user_state(self.user_id)
+                    .set_schedule_template(base.id, template_id),
+            );
         }
     }
 }
@@ -123,22 +793,82 @@ impl PopupContent for ScheduleInput {
     }
 
     fn init_frame(&mut self, app: &CalendarApp, info: &mut ContentInfo) {
-        if let Some(identifier) = self.update_request.as_ref() {
-            if let Some(response_info) = app.state.get_response(&identifier) {
-                self.update_request = None;
-                if !response_info.is_err() {
-                    info.close();
-                }
-            }
+        if self.update_edit(app) == Some(true) {
+            self.is_saved = true;
         }
         if let Some(identifier) = self.insert_request.as_ref() {
-            if let Some(response_info) = app.state.get_response(&identifier) {
+            if let Some(succeeded) = app.state.get_request_result(identifier) {
                 self.insert_request = None;
-                if !response_info.is_err() {
-                    info.close();
+                self.is_saved = succeeded;
+            }
+        }
+        // Schedule itself is saved anyway, so the popup stays open to show the failure
+        if let Some(identifier) = self.set_template_request.as_ref() {
+            if let Some(succeeded) = app.state.get_request_result(identifier) {
+                self.set_template_request = None;
+                if !succeeded {
+                    self.save_error = Some("Schedule is saved, but its template wasn't changed");
+                }
+            }
+        }
+        if let Some(identifier) = self.overrides_request.as_ref() {
+            if let Some(succeeded) = app.state.get_request_result(identifier) {
+                self.overrides_request = None;
+                if !succeeded {
+                    self.save_error =
+                        Some("Schedule is saved, but its event plan overrides weren't");
+                }
+            }
+        }
+        if self.is_saved && !self.overrides_sent {
+            self.send_overrides(app);
+        }
+        if self.is_saved
+            && self.overrides_sent
+            && self.overrides_request.is_none()
+            && self.set_template_request.is_none()
+            && self.save_error.is_none()
+        {
+            info.close();
+        }
+
+        if let (None, Some(edit)) = (&self.init_overrides, &self.edit) {
+            let base = edit.get_base();
+            let init_overrides = Self::overrides_by_plan(
+                &base.event_plans,
+                app.state
+                    .get_user_state(self.user_id)
+                    .plan_overrides
+                    .get_table()
+                    .get(),
+            );
+            self.overrides = init_overrides.clone();
+            self.init_overrides = Some(init_overrides);
+        }
+
+        if let Some(identifier) = self.template_insert_request.as_ref() {
+            if let Some(succeeded) = app.state.get_request_result(identifier) {
+                self.template_insert_request = None;
+                if !succeeded {
+                    self.create_after_template = false;
                 }
             }
         }
+        // Template, created from the event, appears once the templates are reloaded
+        if let (None, Some(template)) = (self.template_id, self.template_from_event.as_ref()) {
+            self.template_id = Self::find_template(
+                app.state
+                    .get_user_state(self.user_id)
+                    .event_templates
+                    .get_table()
+                    .get(),
+                template,
+            );
+            if let Some(template_id) = self.template_id.filter(|_| self.create_after_template) {
+                self.create_after_template = false;
+                self.insert_request = Some(self.insert_schedule(app, template_id));
+            }
+        }
 
         if self.access_level == -1 {
             self.access_level = app.get_selected_access_level();
@@ -155,10 +885,18 @@ impl PopupContent for ScheduleInput {
 
     fn show_content(&mut self, app: &CalendarApp, ui: &mut egui::Ui, info: &mut ContentInfo) {
         ui.vertical(|ui| {
+            if let Some(edit) = self.edit.as_mut().filter(|edit| !edit.conflicts.is_empty()) {
+                ui.add(ConflictResolver::new(
+                    self.eid.with("conflicts"),
+                    &mut edit.conflicts,
+                ));
+            }
+
             ui.add(TextEdit::singleline(&mut self.name).hint_text("Name"));
             ui.add(TextEdit::multiline(&mut self.description).hint_text("Description"));
 
-            if self.id.is_none() {
+            ui.horizontal(|ui| {
+                ui.label("Template: ");
                 egui::ComboBox::from_id_source("schedule_template_list")
                     .selected_text(
                         match self.template_id.and_then(|template_id| {
@@ -171,10 +909,14 @@ impl PopupContent for ScheduleInput {
                                 .find(|t| t.id == template_id)
                         }) {
                             Some(template) => &template.name,
+                            None if self.template_from_event.is_some() => "New from event",
                             None => "Template",
                         },
                     )
                     .show_ui(ui, |ui| {
+                        if self.template_from_event.is_some() {
+                            ui.selectable_value(&mut self.template_id, None, "New from event");
+                        }
                         app.state
                             .get_user_state(self.user_id)
                             .event_templates
@@ -189,7 +931,7 @@ impl PopupContent for ScheduleInput {
                                 );
                             });
                     });
-            }
+            });
 
             egui::Grid::new(self.eid.with("time_grid")).show(ui, |ui| {
                 ui.label("First day:");
@@ -230,128 +972,162 @@ impl PopupContent for ScheduleInput {
 
             ui.separator();
 
-            ui.add(TimePicker::new(
-                "schedule_event_start",
-                &mut self.new_event_start,
-            ));
-
-            egui::Grid::new(self.eid.with("weekday_grid"))
-                .min_col_width(0.)
-                .show(ui, |ui| {
-                    (0..7).for_each(|weekday_ind| {
-                        let weekday = Weekday::from_usize(weekday_ind).unwrap();
-                        let mut to_delete = vec![];
-
-                        ui.label(weekday_human_name(weekday));
-                        if ui
-                            .add_enabled(
-                                !self.events[weekday_ind]
-                                    .iter()
-                                    .any(|e| e.time == self.new_event_start),
-                                Button::new("Add"),
-                            )
-                            .clicked()
-                        {
-                            self.events[weekday_ind].push(NewEventPlan {
-                                weekday,
-                                time: self.new_event_start,
-                            });
-                            self.events[weekday_ind].sort_by_key(|e| e.time);
-                        }
-                        ui.add_space(4.);
-                        self.events[weekday_ind].iter().enumerate().for_each(
-                            |(i, new_event_plan)| {
-                                ui.spacing_mut().item_spacing = egui::Vec2::default();
-                                ui.label(new_event_plan.time.format("%H:%M").to_string());
-                                if ui.small_button("X").clicked() {
-                                    to_delete.push(i);
-                                }
-                            },
-                        );
+            ui.horizontal(|ui| {
+                ui.selectable_value(&mut self.designer_view, true, "Week grid");
+                ui.selectable_value(&mut self.designer_view, false, "List");
+            });
+            if self.designer_view {
+                self.show_designer(app, ui);
+            } else {
+                ui.add(TimePicker::new(
+                    "schedule_event_start",
+                    &mut self.new_event_start,
+                ));
+
+                egui::Grid::new(self.eid.with("weekday_grid"))
+                    .min_col_width(0.)
+                    .show(ui, |ui| {
+                        (0..7).for_each(|weekday_ind| {
+                            let weekday = Weekday::from_usize(weekday_ind).unwrap();
+                            let mut to_delete = vec![];
+
+                            ui.label(weekday_human_name(weekday));
+                            if ui
+                                .add_enabled(
+                                    !self.events[weekday_ind]
+                                        .iter()
+                                        .any(|e| e.time == self.new_event_start),
+                                    Button::new("Add"),
+                                )
+                                .clicked()
+                            {
+                                self.events[weekday_ind].push(NewEventPlan {
+                                    weekday,
+                                    time: self.new_event_start,
+                                });
+                                self.events[weekday_ind].sort_by_key(|e| e.time);
+                            }
+                            ui.add_space(4.);
+                            self.events[weekday_ind].iter().enumerate().for_each(
+                                |(i, new_event_plan)| {
+                                    ui.spacing_mut().item_spacing = egui::Vec2::default();
+                                    let key = (weekday, new_event_plan.time);
+                                    let is_overridden = self
+                                        .overrides
+                                        .get(&key)
+                                        .is_some_and(|plan_override| !plan_override.is_empty());
+                                    let mut text = new_event_plan.time.format("%H:%M").to_string();
+                                    if is_overridden {
+                                        text.push('*');
+                                    }
+                                    if ui
+                                        .selectable_label(self.selected_plan == Some(key), text)
+                                        .on_hover_text(
+                                            "Override template, duration or access level",
+                                        )
+                                        .clicked()
+                                    {
+                                        self.selected_plan =
+                                            (self.selected_plan != Some(key)).then_some(key);
+                                    }
+                                    if ui.small_button("X").clicked() {
+                                        to_delete.push(i);
+                                    }
+                                },
+                            );
 
-                        to_delete.into_iter().rev().for_each(|i| {
-                            self.events[weekday_ind].remove(i);
+                            to_delete.into_iter().rev().for_each(|i| {
+                                let plan = self.events[weekday_ind].remove(i);
+                                if self.selected_plan == Some((weekday, plan.time)) {
+                                    self.selected_plan = None;
+                                }
+                            });
+                            ui.end_row();
                         });
-                        ui.end_row();
                     });
-                });
+            }
+
+            if let Some(key) = self.selected_plan {
+                ui.separator();
+                self.show_override(app, ui, key);
+            }
 
             info.error(self.name.is_empty(), "Name cannot be empty");
             info.error(self.name.len() > 200, "Name is too long");
             info.error(
-                self.id.is_none() && self.template_id.is_none(),
+                self.id.is_none()
+                    && self.template_id.is_none()
+                    && self.template_from_event.is_none(),
                 "Template must be set",
             );
+            info.error(
+                self.edit.as_ref().is_some_and(|edit| edit.is_deleted()),
+                "Schedule was deleted by someone else",
+            );
+            if let Some(error) = self.save_error {
+                info.error(true, error);
+            }
         });
     }
 
     fn show_buttons(&mut self, app: &CalendarApp, ui: &mut egui::Ui, info: &mut ContentInfo) {
-        if let Some(id) = self.id {
+        let capabilities = app.state.get_capabilities(self.user_id);
+        if let Some(edit) = self.edit.as_ref() {
+            let label = if edit.conflicts.is_empty() {
+                "Save"
+            } else {
+                "Resolve"
+            };
             if ui
-                .add_enabled(!info.is_error(), egui::Button::new("Save"))
+                .add_enabled_permitted(
+                    !info.is_error() && !self.is_saved,
+                    capabilities.item(
+                        CapabilityTable::Schedules,
+                        CapabilityAction::Edit,
+                        self.access_level,
+                    ),
+                    egui::Button::new(label),
+                )
                 .clicked()
             {
-                let events = self.events.iter().flatten().collect_vec();
-                let init_events = self.init_events.clone().unwrap_or(vec![]);
-                let delete_events = init_events
-                    .iter()
-                    .filter_map(|event_plan| {
-                        (!events.iter().any(|new_event_plan| {
-                            event_plan.weekday == new_event_plan.weekday
-                                && event_plan.time == new_event_plan.time
-                        }))
-                        .then_some(event_plan.id)
-                    })
-                    .collect_vec();
-                let new_events = events
-                    .iter()
-                    .filter_map(|&new_event_plan| {
-                        (!init_events.iter().any(|event_plan| {
-                            event_plan.weekday == new_event_plan.weekday
-                                && event_plan.time == new_event_plan.time
-                        }))
-                        .then_some(new_event_plan.clone())
-                    })
-                    .collect_vec();
-                self.update_request = Some(
-                    app.state
-                        .get_user_state(self.user_id)
-                        .schedules
-                        .update(UpdateSchedule {
-                            id,
-                            name: USome(self.name.clone()),
-                            description: USome(
-                                (!self.description.is_empty()).then_some(self.description.clone()),
-                            ),
-                            first_day: USome(self.first_day),
-                            last_day: USome(self.last_day_enabled.then_some(self.last_day)),
-                            access_level: USome(self.access_level),
-                            delete_events,
-                            new_events,
-                        }),
-                );
+                self.save_edit(app);
             }
         } else {
+            let mut capability = capabilities.item(
+                CapabilityTable::Schedules,
+                CapabilityAction::Create,
+                self.access_level,
+            );
+            if self.template_id.is_none() {
+                capability = capability.and(capabilities.item(
+                    CapabilityTable::EventTemplates,
+                    CapabilityAction::Create,
+                    self.access_level,
+                ));
+            }
             if ui
-                .add_enabled(!info.is_error(), egui::Button::new("Create"))
+                .add_enabled_permitted(
+                    !info.is_error() && !self.create_after_template && !self.is_saved,
+                    capability,
+                    egui::Button::new("Create"),
+                )
                 .clicked()
             {
-                self.insert_request = Some(
-                    app.state
-                        .get_user_state(self.user_id)
-                        .schedules
-                        .insert(NewSchedule {
-                            user_id: self.user_id,
-                            template_id: self.template_id.unwrap(),
-                            name: self.name.clone(),
-                            description: (!self.description.is_empty())
-                                .then_some(self.description.clone()),
-                            first_day: self.first_day,
-                            last_day: self.last_day_enabled.then_some(self.last_day),
-                            access_level: self.access_level,
-                            events: self.events.clone().into_iter().flatten().collect(),
-                        }),
-                );
+                match (self.template_id, self.template_from_event.as_ref()) {
+                    (Some(template_id), _) => {
+                        self.insert_request = Some(self.insert_schedule(app, template_id));
+                    }
+                    (None, Some(template)) => {
+                        self.template_insert_request = Some(
+                            app.state
+                                .get_user_state(self.user_id)
+                                .event_templates
+                                .insert(template.clone()),
+                        );
+                        self.create_after_template = true;
+                    }
+                    (None, None) => {}
+                }
             }
         }
         if ui.button("Cancel").clicked() {
@@ -0,0 +1,46 @@
+use super::{
+    popup::PopupType,
+    popup_content::{ContentInfo, PopupContent},
+};
+use crate::app::CalendarApp;
+use egui::{Align, Layout};
+
+pub struct ShareExpiryReminder {
+    /// Receiver name and days left
+    shares: Vec<(String, i64)>,
+}
+
+impl ShareExpiryReminder {
+    pub fn new(shares: Vec<(String, i64)>) -> Self {
+        Self { shares }
+    }
+}
+
+impl PopupContent for ShareExpiryReminder {
+    fn get_type(&self) -> PopupType {
+        PopupType::ShareExpiryReminder
+    }
+
+    fn get_title(&mut self) -> Option<String> {
+        Some("Shares are expiring".to_owned())
+    }
+
+    fn show_content(&mut self, _app: &CalendarApp, ui: &mut egui::Ui, _info: &mut ContentInfo) {
+        ui.with_layout(Layout::top_down(Align::LEFT), |ui| {
+            self.shares.iter().for_each(|(name, days_left)| {
+                ui.label(match days_left {
+                    1 => format!("{name}: last day today"),
+                    _ => format!("{name}: {days_left} days left"),
+                });
+            });
+            ui.add_space(4.);
+            ui.label("Shares can be extended in Manage Access");
+        });
+    }
+
+    fn show_buttons(&mut self, _app: &CalendarApp, ui: &mut egui::Ui, info: &mut ContentInfo) {
+        if ui.button("Ok").clicked() {
+            info.close();
+        }
+    }
+}
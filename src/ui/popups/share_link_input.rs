@@ -0,0 +1,110 @@
+use super::{
+    popup::PopupType,
+    popup_content::{ContentInfo, PopupContent},
+};
+use crate::{
+    app::CalendarApp,
+    db::request::RequestIdentifier,
+    state::table_requests::TableInsertRequest,
+    tables::{
+        share_links::{NewShareLink, ShareLink},
+        DbTable,
+    },
+    ui::{access_level_picker::AccessLevelPicker, utils::UiUtils},
+};
+use calendar_lib::api::utils::TableId;
+use std::hash::Hash;
+
+pub struct ShareLinkInput {
+    eid: egui::Id,
+    user_id: TableId,
+
+    access_level: i32,
+
+    insert_request: Option<RequestIdentifier<TableInsertRequest<ShareLink>>>,
+}
+
+impl ShareLinkInput {
+    pub fn new(eid: impl Hash, user_id: TableId) -> Self {
+        Self {
+            eid: egui::Id::new(eid),
+            user_id,
+            access_level: -1,
+            insert_request: None,
+        }
+    }
+}
+
+impl PopupContent for ShareLinkInput {
+    fn get_type(&self) -> PopupType {
+        PopupType::NewShareLink
+    }
+
+    fn init_frame(&mut self, app: &CalendarApp, info: &mut ContentInfo) {
+        if let Some(identifier) = self.insert_request.as_ref() {
+            if let Some(response_info) = app.state.get_response(&identifier) {
+                self.insert_request = None;
+                if !response_info.is_err() {
+                    info.close();
+                }
+            }
+        }
+
+        // Links are public, so the lowest level is the safest default
+        if self.access_level == -1 {
+            self.access_level = app
+                .state
+                .get_user_state(self.user_id)
+                .access_levels
+                .get_table()
+                .get()
+                .iter()
+                .map(|al| al.level)
+                .min()
+                .unwrap_or(0);
+        }
+    }
+
+    fn get_title(&mut self) -> Option<String> {
+        Some("Create Link".to_owned())
+    }
+
+    fn show_content(&mut self, app: &CalendarApp, ui: &mut egui::Ui, _info: &mut ContentInfo) {
+        ui.vertical(|ui| {
+            ui.label("Anyone with the link can view events up to this access level");
+            ui.horizontal(|ui| {
+                ui.label("Access level: ");
+                ui.add(AccessLevelPicker::new(
+                    self.eid.with("access_level"),
+                    &mut self.access_level,
+                    app.state
+                        .get_user_state(self.user_id)
+                        .access_levels
+                        .get_table()
+                        .get(),
+                ));
+            });
+        });
+    }
+
+    fn show_buttons(&mut self, app: &CalendarApp, ui: &mut egui::Ui, info: &mut ContentInfo) {
+        if ui
+            .add_enabled_permitted(
+                self.insert_request.is_none() && !info.is_error(),
+                app.state.get_capabilities(self.user_id).share(),
+                egui::Button::new("Create"),
+            )
+            .clicked()
+        {
+            self.insert_request = Some(app.state.get_user_state(self.user_id).share_links.insert(
+                NewShareLink {
+                    user_id: self.user_id,
+                    access_level: self.access_level,
+                },
+            ));
+        }
+        if ui.button("Cancel").clicked() {
+            info.close();
+        }
+    }
+}
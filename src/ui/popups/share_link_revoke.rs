@@ -0,0 +1,76 @@
+use egui::{Color32, RichText};
+
+use super::{
+    popup::PopupType,
+    popup_content::{ContentInfo, PopupContent},
+};
+use crate::{
+    app::CalendarApp, db::request::RequestIdentifier, state::table_requests::TableDeleteRequest,
+    tables::share_links::ShareLink, ui::utils::UiUtils,
+};
+
+pub struct ShareLinkRevoke {
+    link: ShareLink,
+
+    delete_request: Option<RequestIdentifier<TableDeleteRequest<ShareLink>>>,
+}
+
+impl ShareLinkRevoke {
+    pub fn new(link: &ShareLink) -> Self {
+        Self {
+            link: link.clone(),
+            delete_request: None,
+        }
+    }
+}
+
+impl PopupContent for ShareLinkRevoke {
+    fn get_type(&self) -> PopupType {
+        PopupType::RevokeShareLink
+    }
+
+    fn init_frame(&mut self, app: &CalendarApp, info: &mut ContentInfo) {
+        if let Some(identifier) = self.delete_request.as_ref() {
+            if let Some(response_info) = app.state.get_response(identifier) {
+                self.delete_request = None;
+                if !response_info.is_err() {
+                    info.close();
+                }
+            }
+        }
+    }
+
+    fn get_title(&mut self) -> Option<String> {
+        Some("Revoke Link?".to_owned())
+    }
+
+    fn show_content(&mut self, app: &CalendarApp, ui: &mut egui::Ui, _info: &mut ContentInfo) {
+        ui.vertical(|ui| {
+            if let Some(url) = app.config.make_share_link(&self.link.token) {
+                ui.label(url);
+            }
+            ui.label("Everyone, who has the link, loses access. It can't be restored");
+        });
+    }
+
+    fn show_buttons(&mut self, app: &CalendarApp, ui: &mut egui::Ui, info: &mut ContentInfo) {
+        if ui
+            .add_enabled_permitted(
+                self.delete_request.is_none() && !info.is_error(),
+                app.state.get_capabilities(self.link.user_id).share(),
+                egui::Button::new(RichText::new("Revoke").color(Color32::RED)),
+            )
+            .clicked()
+        {
+            self.delete_request = Some(
+                app.state
+                    .get_user_state(self.link.user_id)
+                    .share_links
+                    .delete(self.link.id),
+            );
+        }
+        if ui.button("Cancel").clicked() {
+            info.close();
+        }
+    }
+}
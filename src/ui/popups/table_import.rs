@@ -0,0 +1,214 @@
+use super::{
+    popup::PopupType,
+    popup_content::{ContentInfo, PopupContent},
+};
+use crate::{
+    app::CalendarApp,
+    db::request::RequestIdentifier,
+    state::{
+        capabilities::CapabilityAction,
+        table_import::{ImportFormat, ImportRow, ImportRowStatus, TableItemImport},
+        table_requests::TableInsertRequest,
+        undo_history::UndoHistory,
+    },
+    ui::utils::UiUtils,
+};
+use calendar_lib::api::utils::TableId;
+use egui::{Color32, RichText, TextEdit};
+use itertools::Itertools;
+use std::hash::Hash;
+
+pub struct TableImport<T: TableItemImport> {
+    eid: egui::Id,
+    user_id: TableId,
+
+    format: ImportFormat,
+    text: String,
+    /// Dry run of the current text
+    rows: Option<Result<Vec<ImportRow<T>>, String>>,
+
+    insert_requests: Vec<RequestIdentifier<TableInsertRequest<T>>>,
+    imported: usize,
+    failed: usize,
+}
+
+impl<T: TableItemImport> TableImport<T> {
+    pub fn new(eid: impl Hash, user_id: TableId) -> Self {
+        Self {
+            eid: egui::Id::new(eid),
+            user_id,
+            format: ImportFormat::Csv,
+            text: String::new(),
+            rows: None,
+            insert_requests: vec![],
+            imported: 0,
+            failed: 0,
+        }
+    }
+
+    fn count(&self, check: impl Fn(&ImportRowStatus<T>) -> bool) -> usize {
+        match &self.rows {
+            Some(Ok(rows)) => rows.iter().filter(|row| check(&row.status)).count(),
+            _ => 0,
+        }
+    }
+
+    fn show_rows(ui: &mut egui::Ui, eid: egui::Id, rows: &[ImportRow<T>]) {
+        egui::ScrollArea::vertical()
+            .id_source(eid.with("rows"))
+            .max_height(240.)
+            .show(ui, |ui| {
+                egui::Grid::new(eid.with("rows_grid"))
+                    .striped(true)
+                    .show(ui, |ui| {
+                        rows.iter().for_each(|row| {
+                            ui.label(format!("#{}", row.number));
+                            ui.label(&row.name);
+                            match &row.status {
+                                ImportRowStatus::New(_) => {
+                                    ui.label(RichText::new("New").color(Color32::GREEN));
+                                }
+                                ImportRowStatus::Exists => {
+                                    ui.label(RichText::new("Already exists").weak());
+                                }
+                                ImportRowStatus::Invalid(error) => {
+                                    ui.add(
+                                        egui::Label::new(RichText::new(error).color(Color32::RED))
+                                            .wrap(true),
+                                    );
+                                }
+                            }
+                            ui.end_row();
+                        });
+                    });
+            });
+    }
+}
+
+impl<T: TableItemImport> PopupContent for TableImport<T> {
+    fn get_type(&self) -> PopupType {
+        PopupType::TableImport
+    }
+
+    fn init_frame(&mut self, app: &CalendarApp, info: &mut ContentInfo) {
+        let (done, pending): (Vec<_>, Vec<_>) = std::mem::take(&mut self.insert_requests)
+            .into_iter()
+            .partition_map(
+                |identifier| match app.state.get_request_result(&identifier) {
+                    Some(succeeded) => itertools::Either::Left(!succeeded),
+                    None => itertools::Either::Right(identifier),
+                },
+            );
+        self.insert_requests = pending;
+        done.into_iter().for_each(|is_err| {
+            if is_err {
+                self.failed += 1;
+            } else {
+                self.imported += 1;
+            }
+        });
+        if !done.is_empty() && self.insert_requests.is_empty() && self.failed == 0 {
+            info.close();
+        }
+
+        if let Some(Err(error)) = &self.rows {
+            info.error(true, error);
+        }
+    }
+
+    fn get_title(&mut self) -> Option<String> {
+        Some(format!("Import {}", T::AUDIT_TABLE.human_name()))
+    }
+
+    fn show_content(&mut self, app: &CalendarApp, ui: &mut egui::Ui, _info: &mut ContentInfo) {
+        ui.vertical(|ui| {
+            ui.horizontal(|ui| {
+                let format = self.format;
+                ui.selectable_value(&mut self.format, ImportFormat::Csv, "CSV");
+                ui.selectable_value(&mut self.format, ImportFormat::Json, "JSON");
+                if format != self.format {
+                    self.rows = None;
+                }
+            });
+            ui.label(format!(
+                "Fields: {}",
+                T::IMPORT_COLUMNS.iter().map(|(name, _)| name).join(", ")
+            ));
+            match self.format {
+                ImportFormat::Csv => ui.weak("First row names the fields, the same as in export"),
+                ImportFormat::Json => ui.weak("List of objects with these fields"),
+            };
+            let text = ui.add(
+                TextEdit::multiline(&mut self.text)
+                    .code_editor()
+                    .desired_rows(8)
+                    .desired_width(f32::INFINITY),
+            );
+            if text.changed() {
+                self.rows = None;
+            }
+
+            if ui.button("Check").clicked() {
+                self.rows = Some(
+                    app.state
+                        .get_user_state(self.user_id)
+                        .check_import(&self.text, self.format),
+                );
+            }
+            if let Some(Ok(rows)) = &self.rows {
+                ui.label(format!(
+                    "{} new, {} already exist, {} invalid",
+                    self.count(|status| matches!(status, ImportRowStatus::New(_))),
+                    self.count(|status| matches!(status, ImportRowStatus::Exists)),
+                    self.count(|status| matches!(status, ImportRowStatus::Invalid(_))),
+                ));
+                Self::show_rows(ui, self.eid, rows);
+            }
+
+            if !self.insert_requests.is_empty() {
+                ui.horizontal(|ui| {
+                    ui.spinner();
+                    ui.label(format!("Importing, {} left", self.insert_requests.len()));
+                });
+            }
+            if self.failed > 0 {
+                ui.colored_label(
+                    Color32::RED,
+                    format!("{} imported, {} failed", self.imported, self.failed),
+                );
+            }
+        });
+    }
+
+    fn show_buttons(&mut self, app: &CalendarApp, ui: &mut egui::Ui, info: &mut ContentInfo) {
+        let new_count = self.count(|status| matches!(status, ImportRowStatus::New(_)));
+        if ui
+            .add_enabled_permitted(
+                new_count > 0 && self.insert_requests.is_empty(),
+                app.state
+                    .get_capabilities(self.user_id)
+                    .table(T::CAPABILITY_TABLE, CapabilityAction::Create),
+                egui::Button::new(format!("Import {new_count}")),
+            )
+            .clicked()
+        {
+            if let Some(Ok(rows)) = self.rows.take() {
+                let table = T::get_state_table(app.state.get_user_state(self.user_id));
+                self.imported = 0;
+                self.failed = 0;
+                UndoHistory::get().begin_group();
+                self.insert_requests = rows
+                    .into_iter()
+                    .filter_map(|row| match row.status {
+                        ImportRowStatus::New(item) => Some(table.insert(item)),
+                        _ => None,
+                    })
+                    .collect();
+                UndoHistory::get().end_group();
+            }
+        }
+        if ui.button("Cancel").clicked() {
+            info.close();
+        }
+    }
+}
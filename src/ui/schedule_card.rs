@@ -1,14 +1,22 @@
-use super::popups::popup_manager::PopupManager;
-use crate::{app::CalendarApp, db::aliases::Schedule};
-use calendar_lib::api::permissions::types::TablePermissions;
+use super::{popups::popup_manager::PopupManager, utils::UiUtils};
+use crate::{
+    app::CalendarApp,
+    db::aliases::Schedule,
+    state::capabilities::{Capabilities, CapabilityAction, CapabilityTable},
+    tables::{
+        plan_exceptions::PlanExceptionKind, schedule_policies::MaterializePolicy, DbTable,
+        DbTableGetById,
+    },
+};
+use chrono::NaiveDateTime;
 use egui::{Align, Color32, Layout, Stroke, Vec2, Widget};
+use itertools::Itertools;
 
 pub struct ScheduleCard<'a> {
     app: &'a CalendarApp,
     desired_size: Vec2,
     schedule: &'a Schedule,
-    permission: TablePermissions,
-    access_level: i32,
+    capabilities: Capabilities,
 }
 
 impl<'a> ScheduleCard<'a> {
@@ -16,19 +24,88 @@ impl<'a> ScheduleCard<'a> {
         app: &'a CalendarApp,
         desired_size: Vec2,
         schedule: &'a Schedule,
-        access_level: i32,
-        permission: TablePermissions,
+        capabilities: Capabilities,
     ) -> Self {
         Self {
             app,
             desired_size,
             schedule,
-            permission,
-            access_level,
+            capabilities,
         }
     }
 }
 
+impl<'a> ScheduleCard<'a> {
+    fn get_policy(&self) -> MaterializePolicy {
+        self.app
+            .get_selected_user_state()
+            .schedule_policies
+            .get_table()
+            .get_by_id(self.schedule.id)
+            .map_or(MaterializePolicy::Manual, |policy| policy.policy)
+    }
+
+    fn show_exceptions(&self, ui: &mut egui::Ui) {
+        let user_state = self.app.get_selected_user_state();
+        let exceptions = user_state
+            .plan_exceptions
+            .get_table()
+            .get()
+            .iter()
+            .filter_map(|exception| {
+                self.schedule
+                    .event_plans
+                    .iter()
+                    .find(|plan| plan.id == exception.plan_id)
+                    .map(|plan| (exception, plan.time))
+            })
+            .sorted_by_key(|(exception, time)| (exception.date, *time))
+            .collect_vec();
+        if exceptions.is_empty() {
+            return;
+        }
+
+        let can_edit = self.capabilities.item(
+            CapabilityTable::Schedules,
+            CapabilityAction::Edit,
+            self.schedule.access_level,
+        );
+        ui.separator();
+        egui::CollapsingHeader::new(format!("Exceptions ({})", exceptions.len()))
+            .id_source(("schedule_exceptions", self.schedule.id))
+            .show(ui, |ui| {
+                exceptions.iter().for_each(|(exception, time)| {
+                    ui.horizontal(|ui| {
+                        let planned = NaiveDateTime::new(exception.date, *time);
+                        ui.label(match exception.kind {
+                            PlanExceptionKind::Skip => {
+                                format!("{} skipped", planned.format("%Y-%m-%d %H:%M"))
+                            }
+                            PlanExceptionKind::Change { start, duration } => {
+                                let mut text = format!(
+                                    "{} moved to {}",
+                                    planned.format("%Y-%m-%d %H:%M"),
+                                    start.format("%Y-%m-%d %H:%M")
+                                );
+                                if let Some(duration) = duration {
+                                    text += &format!(" for {} min", duration.as_secs() / 60);
+                                }
+                                text
+                            }
+                        });
+                        if ui
+                            .add_permitted(can_edit, egui::Button::new("X").small())
+                            .on_hover_text("Restore as planned")
+                            .clicked()
+                        {
+                            user_state.plan_exceptions.delete(exception.id);
+                        }
+                    });
+                });
+            });
+    }
+}
+
 impl<'a> Widget for ScheduleCard<'a> {
     fn ui(self, ui: &mut egui::Ui) -> egui::Response {
         ui.allocate_ui(self.desired_size, |ui| {
@@ -50,29 +127,73 @@ impl<'a> Widget for ScheduleCard<'a> {
                             ui.separator();
                             ui.label(description);
                         }
+                        let policy = self.get_policy();
+                        if policy != MaterializePolicy::Manual {
+                            ui.weak(format!(
+                                "Events are created {}",
+                                policy.human_name().to_lowercase()
+                            ));
+                        }
+                        self.show_exceptions(ui);
                     })
                 })
                 .response;
 
-            if (self.permission.edit || self.permission.delete)
-                && self.access_level >= self.schedule.access_level
-            {
+            let can_edit = self.capabilities.item(
+                CapabilityTable::Schedules,
+                CapabilityAction::Edit,
+                self.schedule.access_level,
+            );
+            let can_delete = self.capabilities.item(
+                CapabilityTable::Schedules,
+                CapabilityAction::Delete,
+                self.schedule.access_level,
+            );
+            if self.capabilities.can_change_any(CapabilityTable::Schedules) {
                 response.context_menu(|ui| {
-                    if self.permission.edit {
-                        if ui.button("Edit").clicked() {
-                            PopupManager::get().open_update_schedule(&self.schedule);
-                            ui.close_menu();
-                        }
+                    if ui
+                        .add_permitted(can_edit, egui::Button::new("Edit"))
+                        .clicked()
+                    {
+                        PopupManager::get().open_update_schedule(&self.schedule);
+                        ui.close_menu();
                     }
-                    if self.permission.delete {
-                        if ui.button("Delete").clicked() {
-                            self.app
-                                .get_selected_user_state()
-                                .schedules
-                                .delete(*schedule_id);
-                            ui.close_menu();
-                        }
+                    if ui
+                        .add_permitted(can_delete, egui::Button::new("Delete"))
+                        .clicked()
+                    {
+                        self.app
+                            .get_selected_user_state()
+                            .schedules
+                            .delete(*schedule_id);
+                        ui.close_menu();
                     }
+                    ui.separator();
+                    ui.menu_button("Create events", |ui| {
+                        let current = self.get_policy();
+                        [
+                            MaterializePolicy::Manual,
+                            MaterializePolicy::DaysAhead(1),
+                            MaterializePolicy::DaysAhead(7),
+                            MaterializePolicy::DaysAhead(30),
+                            MaterializePolicy::WhenPassed,
+                        ]
+                        .into_iter()
+                        .for_each(|policy| {
+                            if ui
+                                .add_permitted(
+                                    can_edit,
+                                    egui::RadioButton::new(policy == current, policy.human_name()),
+                                )
+                                .clicked()
+                            {
+                                self.app
+                                    .get_selected_user_state()
+                                    .set_schedule_policy(*schedule_id, policy);
+                                ui.close_menu();
+                            }
+                        });
+                    });
                 });
             };
         })
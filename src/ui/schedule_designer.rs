@@ -0,0 +1,270 @@
+use chrono::{NaiveTime, Timelike, Weekday};
+use egui::{Align2, FontId, Id, Pos2, Rect, Rounding, Sense, Stroke, Vec2, Widget};
+use num_traits::FromPrimitive;
+use std::{hash::Hash, time::Duration};
+
+use crate::utils::weekday_human_name_short;
+
+/// Event plan is identified by weekday and time, until it is saved
+pub type PlanKey = (Weekday, NaiveTime);
+
+pub struct DesignerPlan {
+    pub key: PlanKey,
+    pub duration: Duration,
+    pub name: String,
+    pub is_overridden: bool,
+}
+
+/// Time, that is already taken by an event or another schedule
+pub struct DesignerBusy {
+    pub weekday: Weekday,
+    pub start: NaiveTime,
+    pub duration: Duration,
+    pub name: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DesignerAction {
+    Select(PlanKey),
+    Add(PlanKey),
+    Move { from: PlanKey, to: PlanKey },
+    CopyToAllWeekdays(PlanKey),
+    Delete(PlanKey),
+}
+
+/// Minutes from the start of the day, end is capped by midnight
+fn get_span(start: NaiveTime, duration: Duration) -> (u32, u32) {
+    let start = start.num_seconds_from_midnight() / 60;
+    let end = (start + (duration.as_secs() / 60) as u32).min(24 * 60);
+    (start, end.max(start + 1))
+}
+
+fn overlaps(a: (u32, u32), b: (u32, u32)) -> bool {
+    a.0 < b.1 && b.0 < a.1
+}
+
+/// Week grid, where event plans are dragged around as blocks
+pub struct ScheduleDesigner<'a> {
+    id: Id,
+    plans: &'a [DesignerPlan],
+    busy: &'a [DesignerBusy],
+    selected: Option<PlanKey>,
+    action: &'a mut Option<DesignerAction>,
+    hour_height: f32,
+}
+
+impl<'a> ScheduleDesigner<'a> {
+    const SNAP_MINUTES: u32 = 15;
+    const GUTTER: f32 = 36.;
+    const HEADER: f32 = 18.;
+
+    pub fn new(
+        id: impl Hash,
+        plans: &'a [DesignerPlan],
+        busy: &'a [DesignerBusy],
+        action: &'a mut Option<DesignerAction>,
+    ) -> Self {
+        Self {
+            id: Id::new(id),
+            plans,
+            busy,
+            selected: None,
+            action,
+            hour_height: 20.,
+        }
+    }
+
+    pub fn with_selected(self, selected: Option<PlanKey>) -> Self {
+        Self { selected, ..self }
+    }
+
+    /// Names of everything, that takes the time of the plan
+    pub fn find_overlaps(
+        plans: &[DesignerPlan],
+        busy: &[DesignerBusy],
+        key: PlanKey,
+    ) -> Vec<String> {
+        let plan = match plans.iter().find(|plan| plan.key == key) {
+            Some(plan) => plan,
+            None => return vec![],
+        };
+        let span = get_span(plan.key.1, plan.duration);
+        let other_plans = plans
+            .iter()
+            .filter(|other| other.key != key && other.key.0 == key.0)
+            .filter(|other| overlaps(span, get_span(other.key.1, other.duration)))
+            .map(|other| format!("{} at {}", other.name, other.key.1.format("%H:%M")));
+        let busy = busy
+            .iter()
+            .filter(|busy| busy.weekday == key.0)
+            .filter(|busy| overlaps(span, get_span(busy.start, busy.duration)))
+            .map(|busy| busy.name.clone());
+        other_plans.chain(busy).collect()
+    }
+
+    fn column_width(&self, rect: Rect) -> f32 {
+        (rect.width() - Self::GUTTER) / 7.
+    }
+
+    fn get_rect(&self, rect: Rect, weekday: Weekday, start: NaiveTime, duration: Duration) -> Rect {
+        let column_width = self.column_width(rect);
+        let (start, end) = get_span(start, duration);
+        let left =
+            rect.left() + Self::GUTTER + column_width * weekday.num_days_from_monday() as f32;
+        let top = rect.top() + Self::HEADER + start as f32 / 60. * self.hour_height;
+        let bottom = rect.top() + Self::HEADER + end as f32 / 60. * self.hour_height;
+        Rect::from_min_max(
+            Pos2::new(left + 1., top),
+            Pos2::new(left + column_width - 1., bottom),
+        )
+    }
+
+    /// Snapped weekday and time under the point
+    fn get_key(&self, rect: Rect, pos: Pos2) -> PlanKey {
+        let column = ((pos.x - rect.left() - Self::GUTTER) / self.column_width(rect))
+            .floor()
+            .clamp(0., 6.);
+        let minutes = ((pos.y - rect.top() - Self::HEADER) / self.hour_height * 60.).max(0.) as u32;
+        let minutes = (minutes + Self::SNAP_MINUTES / 2) / Self::SNAP_MINUTES * Self::SNAP_MINUTES;
+        let minutes = minutes.min(24 * 60 - Self::SNAP_MINUTES);
+        (
+            Weekday::from_u32(column as u32).unwrap(),
+            NaiveTime::from_hms_opt(minutes / 60, minutes % 60, 0).unwrap(),
+        )
+    }
+}
+
+impl<'a> Widget for ScheduleDesigner<'a> {
+    fn ui(self, ui: &mut egui::Ui) -> egui::Response {
+        let size = Vec2::new(ui.available_width(), Self::HEADER + 24. * self.hour_height);
+        let (rect, response) = ui.allocate_exact_size(size, Sense::click());
+        let visuals = ui.visuals().clone();
+        let painter = ui.painter_at(rect);
+        let font = FontId::proportional(10.);
+        let column_width = self.column_width(rect);
+
+        (0..7).for_each(|column| {
+            let left = rect.left() + Self::GUTTER + column_width * column as f32;
+            painter.text(
+                Pos2::new(left + column_width / 2., rect.top()),
+                Align2::CENTER_TOP,
+                weekday_human_name_short(Weekday::from_u32(column).unwrap()),
+                font.clone(),
+                visuals.text_color(),
+            );
+            painter.vline(
+                left,
+                (rect.top() + Self::HEADER)..=rect.bottom(),
+                visuals.widgets.noninteractive.bg_stroke,
+            );
+        });
+        (0..24).for_each(|hour| {
+            let y = rect.top() + Self::HEADER + hour as f32 * self.hour_height;
+            painter.hline(
+                (rect.left() + Self::GUTTER)..=rect.right(),
+                y,
+                Stroke::new(0.5, visuals.widgets.noninteractive.bg_stroke.color),
+            );
+            painter.text(
+                Pos2::new(rect.left() + Self::GUTTER - 4., y),
+                Align2::RIGHT_TOP,
+                format!("{hour:02}:00"),
+                font.clone(),
+                visuals.weak_text_color(),
+            );
+        });
+
+        self.busy.iter().for_each(|busy| {
+            let block = self.get_rect(rect, busy.weekday, busy.start, busy.duration);
+            painter.rect_filled(block, 2., visuals.weak_text_color().gamma_multiply(0.25));
+        });
+
+        // Empty space is double clicked to add a plan
+        if response.double_clicked() {
+            if let Some(pos) = response.interact_pointer_pos() {
+                *self.action = Some(DesignerAction::Add(self.get_key(rect, pos)));
+            }
+        }
+
+        let mut response = response;
+        let mut action = None;
+        self.plans.iter().for_each(|plan| {
+            let (weekday, time) = plan.key;
+            let block = self.get_rect(rect, weekday, time, plan.duration);
+            let id = self.id.with(("plan", weekday.num_days_from_monday(), time));
+            let block_response = ui.interact(block, id, Sense::click_and_drag());
+
+            let overlaps = Self::find_overlaps(self.plans, self.busy, plan.key);
+            let fill = if overlaps.is_empty() {
+                visuals.selection.bg_fill
+            } else {
+                visuals.error_fg_color.gamma_multiply(0.7)
+            };
+            let stroke = if self.selected == Some(plan.key) {
+                Stroke::new(2., visuals.strong_text_color())
+            } else {
+                Stroke::NONE
+            };
+            painter.rect(block, Rounding::same(3.), fill, stroke);
+            let mut label = format!("{} {}", time.format("%H:%M"), plan.name);
+            if plan.is_overridden {
+                label.push('*');
+            }
+            ui.painter_at(block).text(
+                block.left_top() + Vec2::new(2., 1.),
+                Align2::LEFT_TOP,
+                label,
+                font.clone(),
+                visuals.strong_text_color(),
+            );
+
+            if block_response.clicked() {
+                action = Some(DesignerAction::Select(plan.key));
+            }
+            // Block keeps the point it was grabbed by under the pointer
+            if block_response.drag_started() {
+                if let Some(origin) = ui.input(|input| input.pointer.press_origin()) {
+                    ui.data_mut(|data| data.insert_temp(id, origin - block.left_top()));
+                }
+            }
+            let target = ui
+                .data(|data| data.get_temp::<Vec2>(id))
+                .zip(ui.input(|input| input.pointer.interact_pos()))
+                .map(|(grab, pos)| {
+                    self.get_key(rect, pos - grab + Vec2::new(block.width() / 2., 0.))
+                });
+            if let (true, Some((to_weekday, to_time))) = (block_response.dragged(), target) {
+                let ghost = self.get_rect(rect, to_weekday, to_time, plan.duration);
+                painter.rect_stroke(ghost, 3., Stroke::new(1.5, visuals.strong_text_color()));
+            }
+            if block_response.drag_released() {
+                ui.data_mut(|data| data.remove::<Vec2>(id));
+                if let Some(to) = target.filter(|to| *to != plan.key) {
+                    action = Some(DesignerAction::Move { from: plan.key, to });
+                }
+            }
+
+            let block_response = if overlaps.is_empty() {
+                block_response
+            } else {
+                block_response.on_hover_text(format!("Overlaps {}", overlaps.join(", ")))
+            };
+            block_response.context_menu(|ui| {
+                if ui.button("Same on all weekdays").clicked() {
+                    action = Some(DesignerAction::CopyToAllWeekdays(plan.key));
+                    ui.close_menu();
+                }
+                if ui.button("Delete").clicked() {
+                    action = Some(DesignerAction::Delete(plan.key));
+                    ui.close_menu();
+                }
+            });
+            response |= block_response;
+        });
+        if action.is_some() {
+            *self.action = action;
+        }
+
+        response
+    }
+}
@@ -0,0 +1,264 @@
+use calendar_lib::api::{
+    auth::types::AccessLevel, event_templates::types::EventTemplate, schedules::types::Schedule,
+};
+use chrono::{Datelike, Days, Local, NaiveDate};
+use egui::{Button, DragValue, Id, Widget};
+use itertools::Itertools;
+use std::{hash::Hash, time::Duration};
+
+use super::{
+    access_level_picker::AccessLevelPicker,
+    schedule_designer::{DesignerAction, DesignerBusy, DesignerPlan, PlanKey, ScheduleDesigner},
+};
+use crate::{
+    state::user_state::UserState,
+    tables::{plan_overrides::PlanOverride, DbTable},
+    utils::{get_monday, weekday_human_name},
+};
+
+/// Events and other schedules of the first week, the schedule is active.
+/// Events of the `schedule` itself are left out, as they are shown as its plans
+pub fn get_busy_week(
+    user_state: &UserState,
+    first_day: NaiveDate,
+    schedule: Option<&Schedule>,
+) -> Vec<DesignerBusy> {
+    let monday = get_monday(&first_day.max(Local::now().naive_local().date()));
+    let sunday = monday + Days::new(6);
+    let own_plans = schedule
+        .map(|schedule| {
+            schedule
+                .event_plans
+                .iter()
+                .map(|plan| plan.id)
+                .collect_vec()
+        })
+        .unwrap_or_default();
+
+    let events = user_state
+        .events
+        .get_table()
+        .get()
+        .iter()
+        .filter(|event| monday <= event.start.date() && event.start.date() <= sunday)
+        .filter(|event| {
+            !event
+                .plan_id
+                .is_some_and(|plan_id| own_plans.contains(&plan_id))
+        })
+        .map(|event| DesignerBusy {
+            weekday: event.start.weekday(),
+            start: event.start.time(),
+            duration: (event.end - event.start).to_std().unwrap_or_default(),
+            name: event.name.clone(),
+        });
+    let schedules = user_state
+        .schedules
+        .get_table()
+        .get()
+        .iter()
+        .filter(|other| schedule.map_or(true, |schedule| other.id != schedule.id))
+        .filter(|other| other.first_day <= sunday)
+        .filter(|other| other.last_day.map_or(true, |last_day| monday <= last_day))
+        .flat_map(|other| {
+            other.event_plans.iter().filter_map(|plan| {
+                user_state
+                    .get_plan_template(other, plan.id)
+                    .map(|(template, plan_override)| DesignerBusy {
+                        weekday: plan.weekday,
+                        start: plan.time,
+                        duration: plan_override.duration.unwrap_or(template.duration),
+                        name: other.name.clone(),
+                    })
+            })
+        });
+    events.chain(schedules).collect()
+}
+
+/// Week grid of the schedule with the hints and the overlap warning
+pub struct PlanDesignerPanel<'a> {
+    id: Id,
+    plans: &'a [DesignerPlan],
+    busy: &'a [DesignerBusy],
+    selected: Option<PlanKey>,
+    action: &'a mut Option<DesignerAction>,
+}
+
+impl<'a> PlanDesignerPanel<'a> {
+    pub fn new(
+        id: impl Hash,
+        plans: &'a [DesignerPlan],
+        busy: &'a [DesignerBusy],
+        action: &'a mut Option<DesignerAction>,
+    ) -> Self {
+        Self {
+            id: Id::new(id),
+            plans,
+            busy,
+            selected: None,
+            action,
+        }
+    }
+
+    pub fn with_selected(self, selected: Option<PlanKey>) -> Self {
+        Self { selected, ..self }
+    }
+}
+
+impl<'a> Widget for PlanDesignerPanel<'a> {
+    fn ui(self, ui: &mut egui::Ui) -> egui::Response {
+        ui.vertical(|ui| {
+            ui.set_min_width(480.);
+            egui::ScrollArea::vertical()
+                .id_source(self.id.with("scroll"))
+                .max_height(320.)
+                .show(ui, |ui| {
+                    ui.add(
+                        ScheduleDesigner::new(self.id, self.plans, self.busy, self.action)
+                            .with_selected(self.selected),
+                    );
+                });
+            ui.weak("Double click to add, drag to move, right click for more");
+            let overlapping = self
+                .plans
+                .iter()
+                .filter(|plan| {
+                    !ScheduleDesigner::find_overlaps(self.plans, self.busy, plan.key).is_empty()
+                })
+                .count();
+            if overlapping > 0 {
+                ui.colored_label(
+                    ui.visuals().warn_fg_color,
+                    format!("{overlapping} event plans overlap other events"),
+                );
+            }
+        })
+        .response
+    }
+}
+
+/// Template, duration and access level of one event plan, that differ from the schedule
+pub struct PlanOverrideEditor<'a> {
+    id: Id,
+    key: PlanKey,
+    plan_override: &'a mut PlanOverride,
+    templates: &'a [EventTemplate],
+    default_template: Option<&'a EventTemplate>,
+    access_levels: &'a [AccessLevel],
+    default_access_level: i32,
+}
+
+impl<'a> PlanOverrideEditor<'a> {
+    pub fn new(
+        id: impl Hash,
+        key: PlanKey,
+        plan_override: &'a mut PlanOverride,
+        templates: &'a [EventTemplate],
+        access_levels: &'a [AccessLevel],
+    ) -> Self {
+        Self {
+            id: Id::new(id),
+            key,
+            plan_override,
+            templates,
+            default_template: None,
+            access_levels,
+            default_access_level: 0,
+        }
+    }
+
+    /// Template and access level of the schedule, that the overrides start from
+    pub fn with_defaults(
+        self,
+        default_template: Option<&'a EventTemplate>,
+        default_access_level: i32,
+    ) -> Self {
+        Self {
+            default_template,
+            default_access_level,
+            ..self
+        }
+    }
+}
+
+impl<'a> Widget for PlanOverrideEditor<'a> {
+    fn ui(self, ui: &mut egui::Ui) -> egui::Response {
+        let plan_override = self.plan_override;
+        ui.vertical(|ui| {
+            ui.label(format!(
+                "{} {} overrides",
+                weekday_human_name(self.key.0),
+                self.key.1.format("%H:%M")
+            ));
+            egui::Grid::new(self.id.with("grid")).show(ui, |ui| {
+                ui.label("Template:");
+                let selected_template = plan_override.template_id.and_then(|template_id| {
+                    self.templates
+                        .iter()
+                        .find(|template| template.id == template_id)
+                });
+                egui::ComboBox::from_id_source(self.id.with("template"))
+                    .selected_text(match selected_template {
+                        Some(template) => template.name.as_str(),
+                        None => "Schedule template",
+                    })
+                    .show_ui(ui, |ui| {
+                        ui.selectable_value(
+                            &mut plan_override.template_id,
+                            None,
+                            "Schedule template",
+                        );
+                        self.templates.iter().for_each(|template| {
+                            ui.selectable_value(
+                                &mut plan_override.template_id,
+                                Some(template.id),
+                                &template.name,
+                            );
+                        });
+                    });
+                ui.end_row();
+
+                ui.label("Duration:");
+                let mut has_duration = plan_override.duration.is_some();
+                if ui.checkbox(&mut has_duration, "").changed() {
+                    plan_override.duration = has_duration.then(|| {
+                        selected_template
+                            .or(self.default_template)
+                            .map_or(Duration::from_secs(60 * 60), |template| template.duration)
+                    });
+                }
+                if let Some(duration) = plan_override.duration.as_mut() {
+                    let mut minutes = duration.as_secs() / 60;
+                    ui.add(
+                        DragValue::new(&mut minutes)
+                            .clamp_range(1..=24 * 60)
+                            .suffix(" min"),
+                    );
+                    *duration = Duration::from_secs(minutes * 60);
+                }
+                ui.end_row();
+
+                ui.label("Access level:");
+                let mut has_level = plan_override.access_level.is_some();
+                if ui.checkbox(&mut has_level, "").changed() {
+                    plan_override.access_level = has_level.then_some(self.default_access_level);
+                }
+                if let Some(level) = plan_override.access_level.as_mut() {
+                    ui.add(AccessLevelPicker::new(
+                        self.id.with("access_level"),
+                        level,
+                        self.access_levels,
+                    ));
+                }
+                ui.end_row();
+            });
+            if ui
+                .add_enabled(!plan_override.is_empty(), Button::new("Reset"))
+                .clicked()
+            {
+                *plan_override = PlanOverride::new(plan_override.plan_id);
+            }
+        })
+        .response
+    }
+}
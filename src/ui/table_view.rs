@@ -1,19 +1,89 @@
-use std::{hash::Hash, marker::PhantomData};
+use std::{collections::HashSet, fmt::Display, hash::Hash, marker::PhantomData};
 
-use egui::{Button, InnerResponse, Layout, Response};
+use chrono::{NaiveDate, NaiveDateTime};
+use egui::{Button, InnerResponse, Layout, Response, TextEdit};
 use egui_extras::{Column, TableBuilder};
 use itertools::Itertools;
 use serde::{Deserialize, Serialize};
 
-pub trait TableViewItem {
+use crate::utils::{bytes_human_name, csv_line};
+
+/// Cell value, that keeps its type for sorting
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum TableViewValue {
+    None,
+    Number(i64),
+    Bytes(u64),
+    Duration(std::time::Duration),
+    Date(NaiveDate),
+    DateTime(NaiveDateTime),
+    Text(String),
+}
+
+impl TableViewValue {
+    pub fn text(text: impl Into<String>) -> Self {
+        Self::Text(text.into())
+    }
+
+    pub fn optional<V>(value: Option<V>, make: impl FnOnce(V) -> Self) -> Self {
+        value.map_or(Self::None, make)
+    }
+
+    fn contains(&self, pattern: &str) -> bool {
+        self.to_string().to_lowercase().contains(pattern)
+    }
+}
+
+impl Display for TableViewValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TableViewValue::None => write!(f, "None"),
+            TableViewValue::Number(number) => write!(f, "{number}"),
+            TableViewValue::Bytes(bytes) => write!(f, "{}", bytes_human_name(*bytes)),
+            TableViewValue::Duration(duration) => write!(f, "{} minutes", duration.as_secs() / 60),
+            TableViewValue::Date(date) => write!(f, "{date}"),
+            TableViewValue::DateTime(date_time) => write!(f, "{date_time}"),
+            TableViewValue::Text(text) => write!(f, "{text}"),
+        }
+    }
+}
+
+/// Exported with the serde field names, so that it can be imported back
+pub trait TableViewItem: Serialize {
     fn get_names() -> Vec<String>;
-    fn get_fields(&self) -> Vec<String>;
+    /// One value per name
+    fn get_values(&self) -> Vec<TableViewValue>;
+
+    fn get_fields(&self) -> Vec<String> {
+        self.get_values()
+            .into_iter()
+            .map(|value| value.to_string())
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ExportFormat {
+    Csv,
+    Json,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SortOrder {
+    Ascending,
+    Descending,
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone)]
 struct TableViewData {
     page: usize,
     page_size: usize,
+    /// Column and order
+    sort: Option<(usize, SortOrder)>,
+    search: String,
+    /// Per column, empty filter matches everything
+    filters: Vec<String>,
+    page_str: String,
 }
 
 impl Default for TableViewData {
@@ -21,7 +91,93 @@ impl Default for TableViewData {
         Self {
             page: 0,
             page_size: 20,
+            sort: None,
+            search: String::new(),
+            filters: vec![],
+            page_str: "1".to_owned(),
+        }
+    }
+}
+
+impl TableViewData {
+    const PAGE_SIZES: [usize; 4] = [10, 20, 50, 100];
+
+    fn pages_count(&self, rows: usize) -> usize {
+        rows.div_ceil(self.page_size).max(1)
+    }
+
+    fn set_page(&mut self, page: usize) {
+        self.page = page;
+        self.page_str = (page + 1).to_string();
+    }
+
+    /// Indices of the items, that pass the search and filters, in the sort order
+    fn get_rows<T: TableViewItem>(&self, data: &[T]) -> Vec<usize> {
+        let search = self.search.trim().to_lowercase();
+        let filters = self
+            .filters
+            .iter()
+            .map(|filter| filter.trim().to_lowercase())
+            .collect_vec();
+        let values = data.iter().map(|item| item.get_values()).collect_vec();
+
+        let mut rows = (0..data.len())
+            .filter(|&i| {
+                let values = &values[i];
+                (search.is_empty() || values.iter().any(|value| value.contains(&search)))
+                    && filters
+                        .iter()
+                        .zip(values)
+                        .all(|(filter, value)| filter.is_empty() || value.contains(filter))
+            })
+            .collect_vec();
+        if let Some((column, order)) = self.sort {
+            rows.sort_by(|&a, &b| {
+                let ordering = values[a].get(column).cmp(&values[b].get(column));
+                match order {
+                    SortOrder::Ascending => ordering,
+                    SortOrder::Descending => ordering.reverse(),
+                }
+            });
         }
+        rows
+    }
+}
+
+/// Strings are written as is, other values as JSON
+fn csv_cell(value: &serde_json::Value) -> String {
+    match value {
+        serde_json::Value::Null => String::new(),
+        serde_json::Value::String(text) => text.clone(),
+        value => value.to_string(),
+    }
+}
+
+fn export<'a, T: TableViewItem + 'a>(
+    items: impl Iterator<Item = &'a T>,
+    format: ExportFormat,
+) -> String {
+    let items = items
+        .filter_map(|item| serde_json::to_value(item).ok())
+        .collect_vec();
+    match format {
+        ExportFormat::Csv => {
+            let names = items
+                .first()
+                .and_then(|item| item.as_object())
+                .map(|item| item.keys().cloned().collect_vec())
+                .unwrap_or_default();
+            std::iter::once(csv_line(&names))
+                .chain(items.iter().map(|item| {
+                    csv_line(
+                        names
+                            .iter()
+                            .map(|name| item.get(name).map(csv_cell).unwrap_or_default()),
+                    )
+                }))
+                .join("\n")
+        }
+        ExportFormat::Json => serde_json::to_string_pretty(&items).unwrap_or_default(),
     }
 }
 
@@ -51,6 +207,21 @@ impl<T> TableViewActions<T> {
     }
 }
 
+/// Adds a checkbox column
+pub struct TableViewSelection<'a, T> {
+    selected: &'a mut HashSet<i32>,
+    get_item_id: Box<dyn Fn(&T) -> i32>,
+}
+
+impl<'a, T> TableViewSelection<'a, T> {
+    pub fn new<F: Fn(&T) -> i32 + 'static>(selected: &'a mut HashSet<i32>, get_item_id: F) -> Self {
+        Self {
+            selected,
+            get_item_id: Box::new(get_item_id),
+        }
+    }
+}
+
 #[derive(Debug, Default)]
 pub struct TableViewResponse {
     pub actions: Vec<(u8, i32)>,
@@ -86,6 +257,26 @@ impl<T: TableViewItem> TableView<T> {
         ui: &mut egui::Ui,
         data: &Vec<T>,
         actions: Option<TableViewActions<T>>,
+    ) -> InnerResponse<TableViewResponse> {
+        self.show_impl(ui, data, actions, None)
+    }
+
+    pub fn show_selectable(
+        &self,
+        ui: &mut egui::Ui,
+        data: &Vec<T>,
+        actions: Option<TableViewActions<T>>,
+        selection: TableViewSelection<T>,
+    ) -> InnerResponse<TableViewResponse> {
+        self.show_impl(ui, data, actions, Some(selection))
+    }
+
+    fn show_impl(
+        &self,
+        ui: &mut egui::Ui,
+        data: &Vec<T>,
+        actions: Option<TableViewActions<T>>,
+        selection: Option<TableViewSelection<T>>,
     ) -> InnerResponse<TableViewResponse> {
         let mut table_data = ui.memory(|memory| {
             memory
@@ -93,9 +284,18 @@ impl<T: TableViewItem> TableView<T> {
                 .get_temp::<TableViewData>(self.id)
                 .unwrap_or_default()
         });
+        table_data
+            .filters
+            .resize(T::get_names().len(), String::new());
         let response = ui.vertical(|ui| {
-            let response = self.show_table(ui, data, &mut table_data, actions);
-            self.show_page_switch(ui, data, &mut table_data);
+            self.show_toolbar(ui, data, &mut table_data);
+            let rows = table_data.get_rows(data);
+            let pages_count = table_data.pages_count(rows.len());
+            if table_data.page >= pages_count {
+                table_data.set_page(pages_count - 1);
+            }
+            let response = self.show_table(ui, data, &rows, &mut table_data, actions, selection);
+            self.show_page_switch(ui, rows.len(), &mut table_data);
             response
         });
         ui.memory_mut(|memory| {
@@ -104,24 +304,129 @@ impl<T: TableViewItem> TableView<T> {
         response
     }
 
+    fn show_toolbar(&self, ui: &mut egui::Ui, data: &[T], table_data: &mut TableViewData) {
+        ui.horizontal(|ui| {
+            let search = ui.add(
+                TextEdit::singleline(&mut table_data.search)
+                    .hint_text("Search")
+                    .desired_width(200.),
+            );
+            if search.changed() {
+                table_data.set_page(0);
+            }
+            if ui
+                .add_enabled(
+                    !table_data.search.is_empty()
+                        || table_data.filters.iter().any(|filter| !filter.is_empty()),
+                    Button::new("Clear filters").small(),
+                )
+                .clicked()
+            {
+                table_data.search.clear();
+                table_data
+                    .filters
+                    .iter_mut()
+                    .for_each(|filter| filter.clear());
+                table_data.set_page(0);
+            }
+
+            // Copied to the clipboard, as there are no files on the web
+            ui.menu_button("Export", |ui| {
+                let mut exported = None;
+                [(ExportFormat::Csv, "CSV"), (ExportFormat::Json, "JSON")]
+                    .into_iter()
+                    .for_each(|(format, name)| {
+                        if ui.button(format!("Filtered rows as {name}")).clicked() {
+                            let rows = table_data.get_rows(data);
+                            exported = Some(export(rows.into_iter().map(|i| &data[i]), format));
+                        }
+                        if ui.button(format!("All rows as {name}")).clicked() {
+                            exported = Some(export(data.iter(), format));
+                        }
+                    });
+                if let Some(exported) = exported {
+                    ui.output_mut(|o| o.copied_text = exported);
+                    ui.close_menu();
+                }
+            });
+        });
+    }
+
     fn show_table(
         &self,
         ui: &mut egui::Ui,
         data: &Vec<T>,
+        rows: &[usize],
         table_data: &mut TableViewData,
         actions: Option<TableViewActions<T>>,
+        mut selection: Option<TableViewSelection<T>>,
     ) -> TableViewResponse {
         let columns = T::get_names();
         let mut response = TableViewResponse::default();
-        TableBuilder::new(ui)
+        let first = table_data.page * table_data.page_size;
+        let page = &rows[first.min(rows.len())..rows.len().min(first + table_data.page_size)];
+        let mut builder = TableBuilder::new(ui);
+        if selection.is_some() {
+            builder = builder.column(Column::exact(20.));
+        }
+        builder
             .columns(
                 Column::auto().at_least(100.).resizable(true),
                 columns.len() + if actions.is_some() { 1 } else { 0 },
             )
-            .header(20.0, |mut header| {
-                columns.into_iter().for_each(|name| {
+            .header(48.0, |mut header| {
+                if let Some(selection) = selection.as_mut() {
+                    header.col(|ui| {
+                        // Selects the whole page
+                        let page_ids = page
+                            .iter()
+                            .map(|&i| (selection.get_item_id)(&data[i]))
+                            .collect_vec();
+                        let mut all_selected = !page_ids.is_empty()
+                            && page_ids.iter().all(|id| selection.selected.contains(id));
+                        if ui.checkbox(&mut all_selected, "").changed() {
+                            page_ids.into_iter().for_each(|id| {
+                                if all_selected {
+                                    selection.selected.insert(id);
+                                } else {
+                                    selection.selected.remove(&id);
+                                }
+                            });
+                        }
+                    });
+                }
+                columns.into_iter().enumerate().for_each(|(column, name)| {
                     header.col(|ui| {
-                        ui.heading(name);
+                        ui.vertical(|ui| {
+                            let arrow = match table_data.sort {
+                                Some((sorted, SortOrder::Ascending)) if sorted == column => " ⏶",
+                                Some((sorted, SortOrder::Descending)) if sorted == column => " ⏷",
+                                _ => "",
+                            };
+                            if ui
+                                .add(Button::new(format!("{name}{arrow}")).frame(false))
+                                .clicked()
+                            {
+                                // Ascending, descending, unsorted
+                                table_data.sort = match table_data.sort {
+                                    Some((sorted, SortOrder::Ascending)) if sorted == column => {
+                                        Some((column, SortOrder::Descending))
+                                    }
+                                    Some((sorted, SortOrder::Descending)) if sorted == column => {
+                                        None
+                                    }
+                                    _ => Some((column, SortOrder::Ascending)),
+                                };
+                            }
+                            let filter = ui.add(
+                                TextEdit::singleline(&mut table_data.filters[column])
+                                    .hint_text("Filter")
+                                    .desired_width(f32::INFINITY),
+                            );
+                            if filter.changed() {
+                                table_data.set_page(0);
+                            }
+                        });
                     });
                 });
                 if actions.is_some() {
@@ -129,10 +434,22 @@ impl<T: TableViewItem> TableView<T> {
                 }
             })
             .body(|mut body| {
-                let first = table_data.page * table_data.page_size;
-                (first..data.len().min(first + table_data.page_size)).for_each(|i| {
+                page.iter().for_each(|&i| {
                     body.row(30.0, |mut row| {
                         let item = &data[i];
+                        if let Some(selection) = selection.as_mut() {
+                            row.col(|ui| {
+                                let id = (selection.get_item_id)(item);
+                                let mut selected = selection.selected.contains(&id);
+                                if ui.checkbox(&mut selected, "").changed() {
+                                    if selected {
+                                        selection.selected.insert(id);
+                                    } else {
+                                        selection.selected.remove(&id);
+                                    }
+                                }
+                            });
+                        }
                         item.get_fields().into_iter().for_each(|field| {
                             row.col(|ui| {
                                 ui.label(field);
@@ -160,33 +477,53 @@ impl<T: TableViewItem> TableView<T> {
     fn show_page_switch(
         &self,
         ui: &mut egui::Ui,
-        data: &Vec<T>,
+        rows: usize,
         table_data: &mut TableViewData,
     ) -> Response {
+        let pages_count = table_data.pages_count(rows);
         ui.with_layout(Layout::right_to_left(egui::Align::TOP), |ui| {
             // RTL
             if ui
-                .add_enabled(
-                    (table_data.page + 1) * table_data.page_size < data.len(),
-                    Button::new(">").small(),
-                )
+                .add_enabled(table_data.page + 1 < pages_count, Button::new(">").small())
                 .clicked()
             {
-                table_data.page += 1;
+                table_data.set_page(table_data.page + 1);
             }
-            /*let response = ui.text_edit_singleline(&mut self.page_str);
+            ui.label(format!("of {pages_count}"));
+            let response =
+                ui.add(TextEdit::singleline(&mut table_data.page_str).desired_width(32.));
             if response.lost_focus() {
-                match self.page_str.parse::<usize>() {
-                    Ok(page) if page > 0 => self.page = page - 1,
-                    _ => self.page_str = (self.page + 1).to_string(),
+                match table_data.page_str.parse::<usize>() {
+                    Ok(page) if page > 0 => table_data.set_page((page - 1).min(pages_count - 1)),
+                    _ => table_data.set_page(table_data.page),
                 }
-            }*/
+            }
             if ui
                 .add_enabled(table_data.page > 0, Button::new("<").small())
                 .clicked()
             {
-                table_data.page -= 1;
+                table_data.set_page(table_data.page - 1);
+            }
+
+            ui.separator();
+            let mut page_size = table_data.page_size;
+            egui::ComboBox::from_id_source(self.id.with("page_size"))
+                .width(48.)
+                .selected_text(page_size.to_string())
+                .show_ui(ui, |ui| {
+                    TableViewData::PAGE_SIZES.into_iter().for_each(|size| {
+                        ui.selectable_value(&mut page_size, size, size.to_string());
+                    });
+                });
+            ui.label("Page size");
+            if page_size != table_data.page_size {
+                // Keeps the first visible row on the page
+                let first = table_data.page * table_data.page_size;
+                table_data.page_size = page_size;
+                table_data.set_page(first / page_size);
             }
+            ui.separator();
+            ui.label(format!("{rows} rows"));
         })
         .response
     }
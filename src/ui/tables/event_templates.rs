@@ -1,6 +1,6 @@
 use calendar_lib::api::event_templates::types::EventTemplate;
 
-use crate::ui::table_view::TableViewItem;
+use crate::ui::table_view::{TableViewItem, TableViewValue};
 
 impl TableViewItem for EventTemplate {
     fn get_names() -> Vec<String> {
@@ -12,12 +12,12 @@ impl TableViewItem for EventTemplate {
         ]
     }
 
-    fn get_fields(&self) -> Vec<String> {
+    fn get_values(&self) -> Vec<TableViewValue> {
         vec![
-            self.name.clone(),
-            self.event_name.clone(),
-            self.event_description.clone().unwrap_or_default(),
-            format!("{} minutes", (self.duration.as_secs() / 60)),
+            TableViewValue::text(&self.name),
+            TableViewValue::text(&self.event_name),
+            TableViewValue::text(self.event_description.clone().unwrap_or_default()),
+            TableViewValue::Duration(self.duration),
         ]
     }
 }
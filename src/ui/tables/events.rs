@@ -1,6 +1,6 @@
 use calendar_lib::api::events::types::Event;
 
-use crate::ui::table_view::TableViewItem;
+use crate::ui::table_view::{TableViewItem, TableViewValue};
 
 impl TableViewItem for Event {
     fn get_names() -> Vec<String> {
@@ -10,12 +10,12 @@ impl TableViewItem for Event {
             .collect()
     }
 
-    fn get_fields(&self) -> Vec<String> {
+    fn get_values(&self) -> Vec<TableViewValue> {
         vec![
-            self.name.clone(),
-            self.description.clone().unwrap_or_default(),
-            self.start.to_string(),
-            self.end.to_string(),
+            TableViewValue::text(&self.name),
+            TableViewValue::text(self.description.clone().unwrap_or_default()),
+            TableViewValue::DateTime(self.start),
+            TableViewValue::DateTime(self.end),
         ]
     }
 }
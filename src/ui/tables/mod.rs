@@ -1,4 +1,5 @@
 pub mod event_templates;
 pub mod events;
 pub mod schedules;
+pub mod usage;
 pub mod users;
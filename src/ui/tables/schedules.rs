@@ -1,6 +1,6 @@
 use calendar_lib::api::schedules::types::Schedule;
 
-use crate::ui::table_view::TableViewItem;
+use crate::ui::table_view::{TableViewItem, TableViewValue};
 
 impl TableViewItem for Schedule {
     fn get_names() -> Vec<String> {
@@ -12,15 +12,12 @@ impl TableViewItem for Schedule {
         ]
     }
 
-    fn get_fields(&self) -> Vec<String> {
+    fn get_values(&self) -> Vec<TableViewValue> {
         vec![
-            self.name.clone(),
-            self.description.clone().unwrap_or_default(),
-            self.first_day.to_string(),
-            match self.last_day {
-                Some(last_day) => last_day.to_string(),
-                None => "None".to_owned(),
-            },
+            TableViewValue::text(&self.name),
+            TableViewValue::text(self.description.clone().unwrap_or_default()),
+            TableViewValue::Date(self.first_day),
+            TableViewValue::optional(self.last_day, TableViewValue::Date),
         ]
     }
 }
@@ -0,0 +1,39 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    state::admin_requests::UserUsage,
+    ui::table_view::{TableViewItem, TableViewValue},
+};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UserUsageRow {
+    pub name: String,
+    pub usage: UserUsage,
+}
+
+impl TableViewItem for UserUsageRow {
+    fn get_names() -> Vec<String> {
+        vec![
+            "Name",
+            "Events",
+            "Templates",
+            "Schedules",
+            "Shares",
+            "Storage",
+        ]
+        .into_iter()
+        .map(|v| v.to_owned())
+        .collect()
+    }
+
+    fn get_values(&self) -> Vec<TableViewValue> {
+        vec![
+            TableViewValue::text(&self.name),
+            TableViewValue::Number(self.usage.events as i64),
+            TableViewValue::Number(self.usage.event_templates as i64),
+            TableViewValue::Number(self.usage.schedules as i64),
+            TableViewValue::Number(self.usage.granted_permissions as i64),
+            TableViewValue::Bytes(self.usage.storage_bytes),
+        ]
+    }
+}
@@ -1,6 +1,11 @@
 use calendar_lib::api::utils::User;
+use itertools::Itertools;
+use serde::{Deserialize, Serialize};
 
-use crate::ui::table_view::TableViewItem;
+use crate::{
+    state::admin_requests::AdminUserInfo,
+    ui::table_view::{TableViewItem, TableViewValue},
+};
 
 impl TableViewItem for User {
     fn get_names() -> Vec<String> {
@@ -10,7 +15,66 @@ impl TableViewItem for User {
             .collect()
     }
 
-    fn get_fields(&self) -> Vec<String> {
-        vec![self.name.clone(), self.email.clone()]
+    fn get_values(&self) -> Vec<TableViewValue> {
+        vec![
+            TableViewValue::text(&self.name),
+            TableViewValue::text(&self.email),
+        ]
+    }
+}
+
+/// User with the details, that only admins see
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AdminUserRow {
+    pub user: User,
+    pub info: Option<AdminUserInfo>,
+}
+
+impl TableViewItem for AdminUserRow {
+    fn get_names() -> Vec<String> {
+        vec![
+            "Name",
+            "Email",
+            "Roles",
+            "Status",
+            "Last Login",
+            "Events",
+            "Templates",
+            "Schedules",
+        ]
+        .into_iter()
+        .map(|v| v.to_owned())
+        .collect()
+    }
+
+    fn get_values(&self) -> Vec<TableViewValue> {
+        let info = self.info.as_ref();
+        let count = |count: fn(&AdminUserInfo) -> usize| {
+            TableViewValue::optional(info.map(count), |count| {
+                TableViewValue::Number(count as i64)
+            })
+        };
+        vec![
+            TableViewValue::text(&self.user.name),
+            TableViewValue::text(&self.user.email),
+            TableViewValue::text(
+                self.user
+                    .roles
+                    .iter()
+                    .map(|role| format!("{role:?}"))
+                    .join(", "),
+            ),
+            TableViewValue::text(match info {
+                Some(info) if info.disabled => "Disabled",
+                Some(_) => "Active",
+                None => "",
+            }),
+            TableViewValue::optional(info.and_then(|info| info.last_login), |last_login| {
+                TableViewValue::DateTime(last_login)
+            }),
+            count(|info| info.events),
+            count(|info| info.event_templates),
+            count(|info| info.schedules),
+        ]
     }
 }
@@ -0,0 +1,167 @@
+use chrono::NaiveDate;
+use egui::{Align2, FontId, Pos2, Rect, Sense, Shape, Stroke, Vec2, Widget};
+
+/// Line chart of a value over days
+pub struct UsageChart<'a> {
+    points: &'a [(NaiveDate, u64)],
+    format: fn(u64) -> String,
+    height: f32,
+}
+
+impl<'a> UsageChart<'a> {
+    pub fn new(points: &'a [(NaiveDate, u64)], format: fn(u64) -> String) -> Self {
+        Self {
+            points,
+            format,
+            height: 160.,
+        }
+    }
+}
+
+impl<'a> Widget for UsageChart<'a> {
+    fn ui(self, ui: &mut egui::Ui) -> egui::Response {
+        let (rect, response) =
+            ui.allocate_exact_size(Vec2::new(ui.available_width(), self.height), Sense::hover());
+        let visuals = ui.visuals();
+        let painter = ui.painter_at(rect);
+        painter.rect_stroke(rect, 2., visuals.widgets.noninteractive.bg_stroke);
+
+        let font = FontId::proportional(10.);
+        let text_color = visuals.weak_text_color();
+        if self.points.len() < 2 {
+            painter.text(
+                rect.center(),
+                Align2::CENTER_CENTER,
+                "Not enough history",
+                font,
+                text_color,
+            );
+            return response;
+        }
+
+        // Space for the labels
+        let plot = rect.shrink2(Vec2::new(8., 14.));
+        let max = self
+            .points
+            .iter()
+            .map(|(_, value)| *value)
+            .max()
+            .unwrap_or(0)
+            .max(1);
+        let step = plot.width() / (self.points.len() - 1) as f32;
+        let positions = self
+            .points
+            .iter()
+            .enumerate()
+            .map(|(i, (_, value))| {
+                Pos2::new(
+                    plot.left() + step * i as f32,
+                    plot.bottom() - plot.height() * (*value as f32 / max as f32),
+                )
+            })
+            .collect::<Vec<_>>();
+
+        painter.add(Shape::line(
+            positions.clone(),
+            Stroke::new(1.5, visuals.selection.bg_fill),
+        ));
+        painter.text(
+            rect.left_top() + Vec2::new(4., 2.),
+            Align2::LEFT_TOP,
+            (self.format)(max),
+            font.clone(),
+            text_color,
+        );
+        painter.text(
+            rect.left_bottom() + Vec2::new(4., -2.),
+            Align2::LEFT_BOTTOM,
+            self.points[0].0.to_string(),
+            font.clone(),
+            text_color,
+        );
+        painter.text(
+            rect.right_bottom() + Vec2::new(-4., -2.),
+            Align2::RIGHT_BOTTOM,
+            self.points[self.points.len() - 1].0.to_string(),
+            font.clone(),
+            text_color,
+        );
+
+        if let Some(hover) = response.hover_pos() {
+            let i = (((hover.x - plot.left()) / step).round().max(0.) as usize)
+                .min(self.points.len() - 1);
+            let (date, value) = self.points[i];
+            painter.circle_filled(positions[i], 3., visuals.selection.bg_fill);
+            painter.text(
+                positions[i] + Vec2::new(0., -6.),
+                Align2::CENTER_BOTTOM,
+                format!("{date}: {}", (self.format)(value)),
+                font,
+                visuals.text_color(),
+            );
+        }
+        response
+    }
+}
+
+/// Horizontal bars, largest value takes the whole width
+pub struct UsageBars<'a> {
+    bars: &'a [(String, u64)],
+    format: fn(u64) -> String,
+}
+
+impl<'a> UsageBars<'a> {
+    pub fn new(bars: &'a [(String, u64)], format: fn(u64) -> String) -> Self {
+        Self { bars, format }
+    }
+}
+
+impl<'a> Widget for UsageBars<'a> {
+    fn ui(self, ui: &mut egui::Ui) -> egui::Response {
+        let row_height = 16.;
+        let (rect, response) = ui.allocate_exact_size(
+            Vec2::new(
+                ui.available_width(),
+                row_height * self.bars.len().max(1) as f32,
+            ),
+            Sense::hover(),
+        );
+        let visuals = ui.visuals();
+        let painter = ui.painter_at(rect);
+        let font = FontId::proportional(11.);
+        let max = self
+            .bars
+            .iter()
+            .map(|(_, value)| *value)
+            .max()
+            .unwrap_or(0)
+            .max(1);
+        // Names take the first third
+        let bars_left = rect.left() + rect.width() / 3.;
+
+        self.bars.iter().enumerate().for_each(|(i, (name, value))| {
+            let top = rect.top() + row_height * i as f32;
+            painter.text(
+                Pos2::new(rect.left(), top + row_height / 2.),
+                Align2::LEFT_CENTER,
+                name,
+                font.clone(),
+                visuals.text_color(),
+            );
+            let width = (rect.right() - bars_left) * (*value as f32 / max as f32);
+            let bar = Rect::from_min_size(
+                Pos2::new(bars_left, top + 2.),
+                Vec2::new(width, row_height - 4.),
+            );
+            painter.rect_filled(bar, 2., visuals.selection.bg_fill.gamma_multiply(0.6));
+            painter.text(
+                Pos2::new(bars_left + 4., top + row_height / 2.),
+                Align2::LEFT_CENTER,
+                (self.format)(*value),
+                font.clone(),
+                visuals.strong_text_color(),
+            );
+        });
+        response
+    }
+}
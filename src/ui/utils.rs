@@ -1,5 +1,7 @@
 use egui::{Direction, Response, RichText, Sense, Shape, Stroke, Ui, Vec2, Widget};
 
+use crate::state::capabilities::Capability;
+
 pub trait UiUtils {
     fn enabled_selectable_rich_text<F: FnOnce()>(
         &mut self,
@@ -21,6 +23,21 @@ pub trait UiUtils {
         is_selected: bool,
         on_clicked: F,
     ) -> Response;
+    fn permitted_selectable_header<F: FnOnce()>(
+        &mut self,
+        text: &str,
+        capability: Capability,
+        is_selected: bool,
+        on_clicked: F,
+    ) -> Response;
+    /// Disabled widget explains the missing permission on hover
+    fn add_permitted<W: Widget>(&mut self, capability: Capability, widget: W) -> Response;
+    fn add_enabled_permitted<W: Widget>(
+        &mut self,
+        is_enabled: bool,
+        capability: Capability,
+        widget: W,
+    ) -> Response;
 }
 
 impl UiUtils for Ui {
@@ -67,6 +84,38 @@ impl UiUtils for Ui {
     ) -> Response {
         self.enabled_selectable_header(text, true, is_selected, on_clicked)
     }
+
+    fn permitted_selectable_header<F: FnOnce()>(
+        &mut self,
+        text: &str,
+        capability: Capability,
+        is_selected: bool,
+        on_clicked: F,
+    ) -> Response {
+        let response =
+            self.enabled_selectable_header(text, capability.is_ok(), is_selected, on_clicked);
+        match capability {
+            Ok(()) => response,
+            Err(denied) => response.on_disabled_hover_text(denied.explain()),
+        }
+    }
+
+    fn add_permitted<W: Widget>(&mut self, capability: Capability, widget: W) -> Response {
+        self.add_enabled_permitted(true, capability, widget)
+    }
+
+    fn add_enabled_permitted<W: Widget>(
+        &mut self,
+        is_enabled: bool,
+        capability: Capability,
+        widget: W,
+    ) -> Response {
+        let response = self.add_enabled(is_enabled && capability.is_ok(), widget);
+        match capability {
+            Ok(()) => response,
+            Err(denied) => response.on_disabled_hover_text(denied.explain()),
+        }
+    }
 }
 
 pub struct DirectionSymbol {